@@ -1,5 +1,5 @@
 // Imports
-use crate::{export, import, test};
+use crate::{export, import, pages, simplify, template, test, validators};
 use anyhow::Context;
 use clap::Parser;
 use rnote_compose::SplitOrder;
@@ -7,10 +7,14 @@ use rnote_engine::engine::export::{
     DocExportFormat, DocPagesExportFormat, DocPagesExportPrefs, SelectionExportFormat,
     SelectionExportPrefs,
 };
-use rnote_engine::engine::import::XoppImportPrefs;
-use rnote_engine::SelectionCollision;
+use rnote_engine::engine::import::{
+    PdfImportPageSpacing, PdfImportPagesType, PdfImportPrefs, XoppImportPrefs,
+};
+use rnote_engine::engine::EngineSnapshot;
+use rnote_engine::{Engine, SelectionCollision};
 use smol::fs::File;
 use smol::io::{AsyncReadExt, AsyncWriteExt};
+use std::future::Future;
 use std::path::{Path, PathBuf};
 use std::time::Duration;
 
@@ -33,7 +37,9 @@ pub(crate) enum Command {
         rnote_files: Vec<PathBuf>,
     },
     /// Imports the specified input file and saves it as a rnote save file.{n}
-    /// Currently only `.xopp` files can be imported.
+    /// The importer is picked from the file extension of the input file.{n}
+    /// Supported are `.pdf`, `.svg`, `.png`, `.jpg`/`.jpeg` and `.xopp` files.{n}
+    /// Files with other or no extensions are imported as `.xopp` files.
     Import {
         /// The rnote save file.
         rnote_file: PathBuf,
@@ -43,6 +49,27 @@ pub(crate) enum Command {
         /// When importing a .xopp file, the import dpi can be specified.
         #[arg(long, default_value_t = XoppImportPrefs::default().dpi)]
         xopp_dpi: f64,
+        /// When importing a .pdf file, whether the pages are imported as vector or bitmap images.
+        #[arg(long, value_enum, default_value_t = PdfImportPrefs::default().pages_type)]
+        pdf_pages_type: PdfImportPagesType,
+        /// When importing a .pdf file, the spacing between the imported pages.
+        #[arg(long, value_enum, default_value_t = PdfImportPrefs::default().page_spacing)]
+        pdf_page_spacing: PdfImportPageSpacing,
+        /// When importing a .pdf file, the page width in percentage to the format width.
+        #[arg(long, default_value_t = PdfImportPrefs::default().page_width_perc)]
+        pdf_page_width_perc: f64,
+        /// When importing a .pdf file as bitmap images, the bitmap scale-factor.
+        #[arg(long, default_value_t = PdfImportPrefs::default().bitmap_scalefactor)]
+        pdf_bitmap_scalefactor: f64,
+        /// When importing a .pdf file, don't draw borders around the imported pages.
+        #[arg(long, action = clap::ArgAction::SetTrue)]
+        pdf_no_page_borders: bool,
+        /// When importing a .pdf file, adjust the document format and layout to the Pdf.
+        #[arg(long, action = clap::ArgAction::SetTrue)]
+        pdf_adjust_document: bool,
+        /// When importing a .svg or bitmap image file, resize the image so that it does not cross page borders.
+        #[arg(long, action = clap::ArgAction::SetTrue)]
+        respect_borders: bool,
     },
     /// Exports the Rnote file(s) and saves it/them in the desired format.{n}
     /// See sub-commands for usage.
@@ -69,6 +96,32 @@ pub(crate) enum Command {
         #[arg(long, action = clap::ArgAction::SetTrue, global = true)]
         open: bool,
     },
+    /// Merges, extracts and reorders the pages of Rnote file(s).{n}
+    /// See sub-commands for usage.
+    Pages {
+        #[command(subcommand)]
+        pages_command: PagesCommand,
+    },
+    /// Saves Rnote files as templates and creates new Rnote files from templates.{n}
+    /// Templates are regular Rnote files. When creating a new file from a template, the placeholders{n}
+    /// "{title}" and "{date}" in its text fields are replaced.{n}
+    /// See sub-commands for usage.
+    Template {
+        #[command(subcommand)]
+        template_command: TemplateCommand,
+    },
+    /// Simplifies the brush strokes of a Rnote file to reduce its size.{n}
+    /// Removes the elements of the strokes that deviate less than the tolerance in position and pressure.
+    Simplify {
+        /// The rnote save file.
+        rnote_file: PathBuf,
+        /// The maximum deviation in position of the simplified strokes from the original ones.
+        #[arg(long, default_value_t = rnote_engine::Engine::SIMPLIFY_TOLERANCE_DEFAULT)]
+        tolerance: f64,
+        /// The simplified output rnote file. Overwrites the input file when not specified.
+        #[arg(short = 'o', long)]
+        output_file: Option<PathBuf>,
+    },
 }
 
 #[derive(clap::ValueEnum, Debug, Clone, Copy, Default)]
@@ -96,7 +149,8 @@ pub(crate) enum ExportCommand {
     /// When using "--output-file", only a single input file can be specified.{n}
     /// The export format will be recognized from the file extension of the output file.{n}
     /// When using "--output-format", the file name and path of the rnote file is used with the extension changed.{n}
-    /// "--output-file and "--output-format" are mutually exclusive and specifying one of them is required.
+    /// "--output-file and "--output-format" are mutually exclusive and specifying one of them is required.{n}
+    /// Markdown (".md") and plain text (".txt") exports only contain the text of all text fields, in reading order.
     Doc {
         #[command(flatten)]
         file_args: FileArgs<DocExportFormat>,
@@ -104,6 +158,16 @@ pub(crate) enum ExportCommand {
         /// pages.
         #[arg(long, default_value_t = Default::default())]
         page_order: SplitOrder,
+        /// When exporting as Pdf, recognize the handwriting with this local command-line program and embed the
+        /// recognized text as invisible text layer.{n}
+        /// The strokes are written to the standard input of the program as Png image, the program is expected to
+        /// print the recognized text to its standard output.
+        #[arg(long)]
+        recognizer_command: Option<PathBuf>,
+        /// An argument that is passed to the recognizer program. Can be specified multiple times.{n}
+        /// For example, `tesseract` can be used with "--recognizer-arg=- --recognizer-arg=stdout".
+        #[arg(long = "recognizer-arg", allow_hyphen_values = true)]
+        recognizer_args: Vec<String>,
     },
     /// Export each page of the document(s) individually.{n}
     /// Both "--output-dir" and "--output-format" need to be set.
@@ -173,6 +237,74 @@ pub(crate) enum SelectionCommand {
     },
 }
 
+#[derive(clap::Subcommand, Debug, Clone)]
+pub(crate) enum PagesCommand {
+    /// Concatenates the pages of the rnote files in the given order and saves them as a new rnote file.{n}
+    /// The document format and layout of the first file is used.
+    Merge {
+        /// The rnote save files.
+        #[arg(required = true)]
+        rnote_files: Vec<PathBuf>,
+        /// The merged output rnote file.
+        #[arg(short = 'o', long)]
+        output_file: PathBuf,
+    },
+    /// Extracts a range of pages and saves them as a new rnote file.{n}
+    /// Page numbers start at 1.
+    Extract {
+        /// The rnote save file.
+        rnote_file: PathBuf,
+        /// The extracted output rnote file.
+        #[arg(short = 'o', long)]
+        output_file: PathBuf,
+        /// The first page of the range.
+        #[arg(long)]
+        first: usize,
+        /// The last page of the range. Defaults to the last page of the document.
+        #[arg(long)]
+        last: Option<usize>,
+    },
+    /// Reorders the pages of a rnote file with fixed-size layout, moving the strokes along with their pages.{n}
+    /// Page numbers start at 1.
+    Reorder {
+        /// The rnote save file.
+        rnote_file: PathBuf,
+        /// The new page order as comma separated list of the current page numbers, e.g. "3,1,2".{n}
+        /// Every page must appear exactly once.
+        #[arg(long, value_delimiter = ',', required = true)]
+        order: Vec<usize>,
+        /// The reordered output rnote file. Overwrites the input file when not specified.
+        #[arg(short = 'o', long)]
+        output_file: Option<PathBuf>,
+    },
+}
+
+#[derive(clap::Subcommand, Debug, Clone)]
+pub(crate) enum TemplateCommand {
+    /// Saves the rnote file as a template.
+    Save {
+        /// The rnote save file.
+        rnote_file: PathBuf,
+        /// The output template file.
+        #[arg(short = 'o', long)]
+        output_file: PathBuf,
+    },
+    /// Creates a new rnote file from the template.
+    New {
+        /// The template file.
+        template_file: PathBuf,
+        /// The created output rnote file.
+        #[arg(short = 'o', long)]
+        output_file: PathBuf,
+        /// The title that replaces the "{title}" placeholders. Defaults to the output file name without extension.
+        #[arg(long)]
+        title: Option<String>,
+        /// The date that replaces the "{date}" placeholders. Defaults to the current date.
+        #[arg(long)]
+        date: Option<String>,
+    },
+}
+
 #[derive(clap::Args, Debug, Clone)]
 #[group(required = true, multiple = false)]
 pub(crate) struct FileArgs<T: clap::ValueEnum + 'static + Send + Sync> {
@@ -215,9 +347,31 @@ pub(crate) async fn run() -> anyhow::Result<()> {
             rnote_file,
             input_file,
             xopp_dpi,
+            pdf_pages_type,
+            pdf_page_spacing,
+            pdf_page_width_perc,
+            pdf_bitmap_scalefactor,
+            pdf_no_page_borders,
+            pdf_adjust_document,
+            respect_borders,
         } => {
             println!("Importing..");
-            import::run_import(&rnote_file, &input_file, xopp_dpi).await?;
+            let pdf_import_prefs = PdfImportPrefs {
+                page_width_perc: pdf_page_width_perc,
+                page_spacing: pdf_page_spacing,
+                pages_type: pdf_pages_type,
+                bitmap_scalefactor: pdf_bitmap_scalefactor,
+                page_borders: !pdf_no_page_borders,
+                adjust_document: pdf_adjust_document,
+            };
+            import::run_import(
+                &rnote_file,
+                &input_file,
+                xopp_dpi,
+                pdf_import_prefs,
+                respect_borders,
+            )
+            .await?;
             println!("Import finished!");
         }
         Command::Export {
@@ -242,6 +396,25 @@ pub(crate) async fn run() -> anyhow::Result<()> {
             .await?;
             println!("Export finished!");
         }
+        Command::Pages { pages_command } => {
+            println!("Processing pages..");
+            pages::run_pages(pages_command).await?;
+            println!("Processing pages finished!");
+        }
+        Command::Template { template_command } => {
+            println!("Processing template..");
+            template::run_template(template_command).await?;
+            println!("Processing template finished!");
+        }
+        Command::Simplify {
+            rnote_file,
+            tolerance,
+            output_file,
+        } => {
+            println!("Simplifying..");
+            simplify::run_simplify(&rnote_file, tolerance, output_file).await?;
+            println!("Simplify finished!");
+        }
     }
 
     Ok(())
@@ -254,6 +427,68 @@ pub(crate) fn new_progressbar(message: String) -> indicatif::ProgressBar {
     pb
 }
 
+/// Runs the task that writes the output file while showing a progressbar with the message.
+///
+/// The details are appended to the message when the task succeeded.
+pub(crate) async fn run_w_progressbar<T>(
+    message: String,
+    output_file: &Path,
+    task: impl Future<Output = anyhow::Result<T>>,
+    finish_details: impl FnOnce(&T) -> String,
+) -> anyhow::Result<T> {
+    let output_file_disp = output_file.display().to_string();
+    let progressbar = new_progressbar(format!("{message} to: \"{output_file_disp}\""));
+
+    match task.await {
+        Err(e) => {
+            let abandon_msg = format!("{message} to \"{output_file_disp}\" failed, Err: {e:?}");
+            if progressbar.is_hidden() {
+                println!("{abandon_msg}");
+            }
+            progressbar.abandon_with_message(abandon_msg);
+            Err(e)
+        }
+        Ok(res) => {
+            let finish_msg = format!(
+                "{message} to \"{output_file_disp}\" succeeded{}",
+                finish_details(&res)
+            );
+            if progressbar.is_hidden() {
+                println!("{finish_msg}");
+            }
+            progressbar.finish_with_message(finish_msg);
+            Ok(res)
+        }
+    }
+}
+
+pub(crate) async fn load_rnote_snapshot(rnote_file: &Path) -> anyhow::Result<EngineSnapshot> {
+    validators::file_has_ext(rnote_file, "rnote")?;
+    let rnote_bytes = read_bytes_from_file(rnote_file).await?;
+    EngineSnapshot::load_from_rnote_bytes(rnote_bytes).await
+}
+
+pub(crate) async fn load_rnote_engine(rnote_file: &Path) -> anyhow::Result<Engine> {
+    let snapshot = load_rnote_snapshot(rnote_file).await?;
+    let mut engine = Engine::default();
+    let _ = engine.load_snapshot(snapshot);
+    Ok(engine)
+}
+
+pub(crate) async fn save_rnote_snapshot(
+    snapshot: EngineSnapshot,
+    output_file: &Path,
+) -> anyhow::Result<()> {
+    let Some(output_file_name) = output_file
+        .file_name()
+        .map(|s| s.to_string_lossy().to_string())
+    else {
+        return Err(anyhow::anyhow!("Failed to get filename from output file"));
+    };
+    let rnote_bytes = snapshot.save_as_rnote_bytes(output_file_name).await??;
+    create_overwrite_file_w_bytes(output_file, &rnote_bytes).await
+}
+
 pub(crate) async fn read_bytes_from_file(file_path: impl AsRef<Path>) -> anyhow::Result<Vec<u8>> {
     let mut bytes = vec![];
     let mut fh = File::open(file_path).await?;
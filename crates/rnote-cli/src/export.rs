@@ -9,9 +9,11 @@ use rnote_engine::engine::export::{
     SelectionExportFormat, SelectionExportPrefs,
 };
 use rnote_engine::engine::EngineSnapshot;
+use rnote_engine::recognition::CommandRecognizer;
 use rnote_engine::{Engine, SelectionCollision};
 use std::io::{self, IsTerminal};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 #[allow(clippy::too_many_arguments)]
 pub(crate) async fn run_export(
@@ -54,6 +56,17 @@ pub(crate) async fn run_export(
         no_pattern,
         optimize_printing,
     )?;
+    if let cli::ExportCommand::Doc {
+        recognizer_command: Some(recognizer_command),
+        recognizer_args,
+        ..
+    } = &export_command
+    {
+        engine.set_handwriting_recognizer(Some(Arc::new(CommandRecognizer::new(
+            recognizer_command.clone(),
+            recognizer_args.clone(),
+        ))));
+    }
 
     match output_file {
         Some(output_file) => {
@@ -195,6 +208,7 @@ fn apply_export_prefs(
         cli::ExportCommand::Doc {
             file_args,
             page_order,
+            ..
         } => {
             engine.export_prefs.doc_export_prefs = create_doc_export_prefs_from_args(
                 output_file,
@@ -314,6 +328,8 @@ fn doc_export_format_from_ext_str(format: &str) -> anyhow::Result<DocExportForma
         "svg" => Ok(DocExportFormat::Svg),
         "xopp" => Ok(DocExportFormat::Xopp),
         "pdf" => Ok(DocExportFormat::Pdf),
+        "md" | "markdown" => Ok(DocExportFormat::Markdown),
+        "txt" => Ok(DocExportFormat::PlainText),
         ext => Err(anyhow::anyhow!(
             "Exporting document to format with extension \"{ext}\" is not supported."
         )),
@@ -558,8 +574,23 @@ pub(crate) async fn export_to_file(
                     output_file.as_ref().display()
                 ));
             };
-            let export_bytes = engine.export_doc(export_file_name, None).await??;
+            let export_bytes = engine.export_doc(export_file_name.clone(), None).await??;
             cli::create_overwrite_file_w_bytes(&output_file, &export_bytes).await?;
+            if engine.export_prefs.doc_export_prefs.export_format == DocExportFormat::Xopp {
+                // Background images are attached next to the xopp file
+                let output_dir = output_file
+                    .as_ref()
+                    .parent()
+                    .map(Path::to_path_buf)
+                    .unwrap_or_default();
+                for (attachment_name, bytes) in engine
+                    .export_doc_xopp_attachments(&export_file_name, None)
+                    .await??
+                {
+                    cli::create_overwrite_file_w_bytes(output_dir.join(attachment_name), &bytes)
+                        .await?;
+                }
+            }
             if open {
                 cli::open_file_default_app(output_file)?;
             }
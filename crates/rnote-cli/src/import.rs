@@ -1,13 +1,44 @@
 // Imports
 use crate::{cli, validators};
+use rnote_engine::engine::import::PdfImportPrefs;
 use rnote_engine::engine::EngineSnapshot;
+use rnote_engine::strokes::Stroke;
 use rnote_engine::Engine;
 use std::path::Path;
 
+/// The importer that is used for an input file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ImportFileType {
+    Xopp,
+    Pdf,
+    VectorImage,
+    BitmapImage,
+}
+
+impl ImportFileType {
+    /// Picks the importer from the file extension of the input file.
+    ///
+    /// Xopp files don't require file extensions, so they are the fallback.
+    pub(crate) fn from_input_file(input_file: &Path) -> Self {
+        match input_file
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .as_deref()
+        {
+            Some("pdf") => Self::Pdf,
+            Some("svg") => Self::VectorImage,
+            Some("png") | Some("jpg") | Some("jpeg") => Self::BitmapImage,
+            _ => Self::Xopp,
+        }
+    }
+}
+
 pub(crate) async fn run_import(
     rnote_file: &Path,
     input_file: &Path,
     xopp_dpi: f64,
+    pdf_import_prefs: PdfImportPrefs,
+    respect_borders: bool,
 ) -> anyhow::Result<()> {
     validators::file_has_ext(rnote_file, "rnote")?;
     // Xopp files don't require file extensions
@@ -15,7 +46,7 @@ pub(crate) async fn run_import(
 
     let mut engine = Engine::default();
 
-    apply_import_prefs(&mut engine, xopp_dpi)?;
+    apply_import_prefs(&mut engine, xopp_dpi, pdf_import_prefs)?;
 
     let rnote_file_disp = rnote_file.display().to_string();
     let input_file_disp = input_file.display().to_string();
@@ -23,7 +54,7 @@ pub(crate) async fn run_import(
         "Importing \"{input_file_disp}\" to: \"{rnote_file_disp}\""
     ));
 
-    if let Err(e) = import_file(&mut engine, input_file, rnote_file).await {
+    if let Err(e) = import_file(&mut engine, input_file, rnote_file, respect_borders).await {
         let abandon_msg =
             format!("Import \"{input_file_disp}\" to \"{rnote_file_disp}\" failed, Err: {e:?}");
         if progressbar.is_hidden() {
@@ -42,8 +73,13 @@ pub(crate) async fn run_import(
     Ok(())
 }
 
-pub(crate) fn apply_import_prefs(engine: &mut Engine, xopp_dpi: f64) -> anyhow::Result<()> {
+pub(crate) fn apply_import_prefs(
+    engine: &mut Engine,
+    xopp_dpi: f64,
+    pdf_import_prefs: PdfImportPrefs,
+) -> anyhow::Result<()> {
     engine.import_prefs.xopp_import_prefs.dpi = xopp_dpi;
+    engine.import_prefs.pdf_import_prefs = pdf_import_prefs;
     Ok(())
 }
 
@@ -51,6 +87,7 @@ pub(crate) async fn import_file(
     engine: &mut Engine,
     input_file: &Path,
     rnote_file: &Path,
+    respect_borders: bool,
 ) -> anyhow::Result<()> {
     let Some(rnote_file_name) = rnote_file
         .file_name()
@@ -59,10 +96,41 @@ pub(crate) async fn import_file(
         return Err(anyhow::anyhow!("Failed to get filename from rnote_file"));
     };
     let input_bytes = cli::read_bytes_from_file(&input_file).await?;
-    let snapshot =
-        EngineSnapshot::load_from_xopp_bytes(input_bytes, engine.import_prefs.xopp_import_prefs)
+    let insert_pos = Stroke::IMPORT_OFFSET_DEFAULT;
+
+    match ImportFileType::from_input_file(input_file) {
+        ImportFileType::Xopp => {
+            let snapshot = EngineSnapshot::load_from_xopp_bytes(
+                input_bytes,
+                Some(input_file.to_path_buf()),
+                engine.import_prefs.xopp_import_prefs,
+            )
             .await?;
-    let _ = engine.load_snapshot(snapshot);
+            let _ = engine.load_snapshot(snapshot);
+        }
+        ImportFileType::Pdf => {
+            let adjust_document = engine.import_prefs.pdf_import_prefs.adjust_document;
+            let strokes = engine
+                .generate_pdf_pages_from_bytes(input_bytes, insert_pos, None)
+                .await??;
+            let _ = engine.import_generated_content(strokes, adjust_document);
+        }
+        ImportFileType::VectorImage => {
+            let vectorimage = engine
+                .generate_vectorimage_from_bytes(insert_pos, input_bytes, respect_borders)
+                .await??;
+            let _ = engine
+                .import_generated_content(vec![(Stroke::VectorImage(vectorimage), None)], false);
+        }
+        ImportFileType::BitmapImage => {
+            let bitmapimage = engine
+                .generate_bitmapimage_from_bytes(insert_pos, input_bytes, respect_borders)
+                .await??;
+            let _ = engine
+                .import_generated_content(vec![(Stroke::BitmapImage(bitmapimage), None)], false);
+        }
+    }
+
     let rnote_bytes = engine.save_as_rnote_bytes(rnote_file_name).await??;
     cli::create_overwrite_file_w_bytes(&rnote_file, &rnote_bytes).await?;
 
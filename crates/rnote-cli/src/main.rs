@@ -6,6 +6,9 @@
 pub(crate) mod cli;
 pub(crate) mod export;
 pub(crate) mod import;
+pub(crate) mod pages;
+pub(crate) mod simplify;
+pub(crate) mod template;
 pub(crate) mod test;
 pub(crate) mod validators;
 
@@ -0,0 +1,154 @@
+// Imports
+use crate::{cli, validators};
+use rnote_compose::SplitOrder;
+use rnote_engine::document::Layout;
+use rnote_engine::engine::pages::PageContent;
+use rnote_engine::Engine;
+use std::path::{Path, PathBuf};
+
+pub(crate) async fn run_pages(pages_command: cli::PagesCommand) -> anyhow::Result<()> {
+    let (message, output_file) = match &pages_command {
+        cli::PagesCommand::Merge {
+            rnote_files,
+            output_file,
+        } => (
+            format!("Merging {} files", rnote_files.len()),
+            output_file.clone(),
+        ),
+        cli::PagesCommand::Extract {
+            rnote_file,
+            output_file,
+            ..
+        } => (
+            format!("Extracting pages of \"{}\"", rnote_file.display()),
+            output_file.clone(),
+        ),
+        cli::PagesCommand::Reorder {
+            rnote_file,
+            output_file,
+            ..
+        } => (
+            format!("Reordering pages of \"{}\"", rnote_file.display()),
+            output_file.clone().unwrap_or_else(|| rnote_file.clone()),
+        ),
+    };
+    validators::path_has_ext(&output_file, "rnote")?;
+
+    cli::run_w_progressbar(
+        message,
+        &output_file,
+        pages(pages_command, &output_file),
+        |_| String::new(),
+    )
+    .await
+}
+
+async fn pages(pages_command: cli::PagesCommand, output_file: &Path) -> anyhow::Result<()> {
+    let engine = match pages_command {
+        cli::PagesCommand::Merge { rnote_files, .. } => merge(&rnote_files).await?,
+        cli::PagesCommand::Extract {
+            rnote_file,
+            first,
+            last,
+            ..
+        } => extract(&rnote_file, first, last).await?,
+        cli::PagesCommand::Reorder {
+            rnote_file, order, ..
+        } => reorder(&rnote_file, &order).await?,
+    };
+    cli::save_rnote_snapshot(engine.take_snapshot(), output_file).await
+}
+
+fn extract_all_pages(engine: &Engine) -> Vec<PageContent> {
+    engine.extract_pages(&engine.doc_pages_bounds(SplitOrder::default()))
+}
+
+/// Concatenate the pages of all files. The document of the first file is kept.
+///
+/// The user layers of the other files are added to the layers of the first file, layers that are equal are merged.
+async fn merge(rnote_files: &[PathBuf]) -> anyhow::Result<Engine> {
+    let Some((first_file, other_files)) = rnote_files.split_first() else {
+        return Err(anyhow::anyhow!(
+            "There must be at least one rnote file specified for merging."
+        ));
+    };
+    let mut engine = cli::load_rnote_engine(first_file).await?;
+    let mut pages = extract_all_pages(&engine);
+
+    for (i, rnote_file) in other_files.iter().enumerate() {
+        let other_engine = cli::load_rnote_engine(rnote_file).await?;
+        let user_layers_map = engine.import_user_layers(other_engine.user_layers());
+        pages.extend(
+            extract_all_pages(&other_engine)
+                .into_iter()
+                .map(|mut page| {
+                    page.remap_user_layers(&user_layers_map);
+                    page.origin = i + 1;
+                    page
+                }),
+        );
+    }
+
+    let _ = engine.replace_pages(pages);
+    Ok(engine)
+}
+
+/// Keep only the pages in the range `first..=last`. Page numbers start at 1.
+async fn extract(rnote_file: &Path, first: usize, last: Option<usize>) -> anyhow::Result<Engine> {
+    let mut engine = cli::load_rnote_engine(rnote_file).await?;
+    let pages = extract_all_pages(&engine);
+    let n_pages = pages.len();
+    let last = last.unwrap_or(n_pages);
+
+    if first == 0 || first > last || last > n_pages {
+        return Err(anyhow::anyhow!(
+            "Invalid page range {first}..={last}, the document has {n_pages} pages."
+        ));
+    }
+
+    let _ = engine.replace_pages(
+        pages
+            .into_iter()
+            .skip(first - 1)
+            .take(last - first + 1)
+            .collect(),
+    );
+    Ok(engine)
+}
+
+/// Reorder the pages of a document in fixed-size layout. Page numbers start at 1.
+async fn reorder(rnote_file: &Path, order: &[usize]) -> anyhow::Result<Engine> {
+    let mut engine = cli::load_rnote_engine(rnote_file).await?;
+    if engine.document.layout != Layout::FixedSize {
+        return Err(anyhow::anyhow!(
+            "Reordering pages is only supported for documents with fixed-size layout."
+        ));
+    }
+    let mut pages = extract_all_pages(&engine)
+        .into_iter()
+        .map(Some)
+        .collect::<Vec<Option<PageContent>>>();
+    let n_pages = pages.len();
+
+    if order.len() != n_pages {
+        return Err(anyhow::anyhow!(
+            "The new page order must contain every page exactly once, the document has {n_pages} pages."
+        ));
+    }
+    let reordered = order
+        .iter()
+        .map(|&page| {
+            page.checked_sub(1)
+                .and_then(|i| pages.get_mut(i))
+                .and_then(|page_content| page_content.take())
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Page {page} in the new page order is invalid or appears more than once."
+                    )
+                })
+        })
+        .collect::<anyhow::Result<Vec<PageContent>>>()?;
+
+    let _ = engine.replace_pages(reordered);
+    Ok(engine)
+}
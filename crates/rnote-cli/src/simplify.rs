@@ -0,0 +1,35 @@
+// Imports
+use crate::{cli, validators};
+use std::path::{Path, PathBuf};
+
+pub(crate) async fn run_simplify(
+    rnote_file: &Path,
+    tolerance: f64,
+    output_file: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    let output_file = output_file.unwrap_or_else(|| rnote_file.to_path_buf());
+    validators::path_has_ext(&output_file, "rnote")?;
+    if tolerance.is_nan() || tolerance <= 0.0 {
+        return Err(anyhow::anyhow!(
+            "The tolerance must be greater than zero, is {tolerance}."
+        ));
+    }
+
+    cli::run_w_progressbar(
+        format!("Simplifying strokes of \"{}\"", rnote_file.display()),
+        &output_file,
+        simplify(rnote_file, tolerance, &output_file),
+        |removed| format!(", removed {removed} elements"),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Simplifies all brush strokes of the file and returns the number of removed elements.
+async fn simplify(rnote_file: &Path, tolerance: f64, output_file: &Path) -> anyhow::Result<usize> {
+    let mut engine = cli::load_rnote_engine(rnote_file).await?;
+    let (removed, _) = engine.simplify_doc_brushstrokes(tolerance);
+    cli::save_rnote_snapshot(engine.take_snapshot(), output_file).await?;
+    Ok(removed)
+}
@@ -0,0 +1,70 @@
+// Imports
+use crate::{cli, validators};
+use rnote_engine::engine::TemplateValues;
+use rnote_engine::Engine;
+use std::path::Path;
+
+pub(crate) async fn run_template(template_command: cli::TemplateCommand) -> anyhow::Result<()> {
+    let (message, output_file) = match &template_command {
+        cli::TemplateCommand::Save {
+            rnote_file,
+            output_file,
+        } => (
+            format!("Saving \"{}\" as template", rnote_file.display()),
+            output_file.clone(),
+        ),
+        cli::TemplateCommand::New {
+            template_file,
+            output_file,
+            ..
+        } => (
+            format!(
+                "Creating file from template \"{}\"",
+                template_file.display()
+            ),
+            output_file.clone(),
+        ),
+    };
+    validators::path_has_ext(&output_file, "rnote")?;
+
+    cli::run_w_progressbar(
+        message,
+        &output_file,
+        template(template_command, &output_file),
+        |_| String::new(),
+    )
+    .await
+}
+
+async fn template(
+    template_command: cli::TemplateCommand,
+    output_file: &Path,
+) -> anyhow::Result<()> {
+    let snapshot = match template_command {
+        cli::TemplateCommand::Save { rnote_file, .. } => {
+            cli::load_rnote_snapshot(&rnote_file).await?.into_template()
+        }
+        cli::TemplateCommand::New {
+            template_file,
+            title,
+            date,
+            ..
+        } => {
+            let template = cli::load_rnote_snapshot(&template_file).await?;
+            let title = title.unwrap_or_else(|| {
+                output_file
+                    .file_stem()
+                    .map(|s| s.to_string_lossy().to_string())
+                    .unwrap_or_default()
+            });
+            let mut values = TemplateValues::new(title);
+            if let Some(date) = date {
+                values.date = date;
+            }
+            let mut engine = Engine::default();
+            let _ = engine.load_template(template, &values);
+            engine.take_snapshot()
+        }
+    };
+    cli::save_rnote_snapshot(snapshot, output_file).await
+}
@@ -22,6 +22,11 @@ pub(crate) fn path_is_file(path: &Path) -> anyhow::Result<()> {
 
 pub(crate) fn file_has_ext(path: &Path, expected_ext: &str) -> anyhow::Result<()> {
     path_is_file(path)?;
+    path_has_ext(path, expected_ext)
+}
+
+/// Checks only the extension, so it can be used for output files that don't exist yet.
+pub(crate) fn path_has_ext(path: &Path, expected_ext: &str) -> anyhow::Result<()> {
     match path.extension() {
         Some(ext) if ext == expected_ext => Ok(()),
         Some(ext) => Err(anyhow::anyhow!(
@@ -147,20 +147,20 @@ impl Buildable for CubBezBuilder {
     }
 
     fn bounds(&self, style: &Style, zoom: f64) -> Option<Aabb> {
-        let stroke_width = style.stroke_width();
+        let bounds_margin = style.bounds_margin();
 
         match &self.state {
             CubBezBuilderState::Cp1 { start, cp1 }
             | CubBezBuilderState::Cp1Finished { start, cp1 } => Some(
                 Aabb::new_positive((*start).into(), (*cp1).into())
-                    .loosened(stroke_width.max(indicators::POS_INDICATOR_RADIUS) / zoom),
+                    .loosened(bounds_margin.max(indicators::POS_INDICATOR_RADIUS) / zoom),
             ),
             CubBezBuilderState::Cp2 { start, cp1, cp2 }
             | CubBezBuilderState::Cp2Finished { start, cp1, cp2 } => {
                 let mut aabb = Aabb::new_positive((*start).into(), (*cp2).into());
                 aabb.take_point((*cp1).into());
 
-                Some(aabb.loosened(stroke_width.max(indicators::POS_INDICATOR_RADIUS) / zoom))
+                Some(aabb.loosened(bounds_margin.max(indicators::POS_INDICATOR_RADIUS) / zoom))
             }
             CubBezBuilderState::End {
                 start,
@@ -172,7 +172,7 @@ impl Buildable for CubBezBuilder {
                 aabb.take_point((*cp1).into());
                 aabb.take_point((*cp2).into());
 
-                Some(aabb.loosened(stroke_width.max(indicators::POS_INDICATOR_RADIUS) / zoom))
+                Some(aabb.loosened(bounds_margin.max(indicators::POS_INDICATOR_RADIUS) / zoom))
             }
         }
     }
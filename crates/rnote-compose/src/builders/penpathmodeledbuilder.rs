@@ -176,7 +176,7 @@ impl PenPathModeledBuilder {
             Ok(results) => self.buffer.extend(results.into_iter().map(|r| {
                 let pos = r.pos;
                 let pressure = r.pressure;
-                Element::new(na::vector![pos.0, pos.1], pressure)
+                Element::new(na::vector![pos.0, pos.1], pressure).with_tilt_of(&element)
             })),
             Err(e) => {
                 match e {
@@ -226,7 +226,7 @@ impl PenPathModeledBuilder {
                     .map(|r| {
                         let pos = r.pos;
                         let pressure = r.pressure;
-                        Element::new(na::vector![pos.0, pos.1], pressure)
+                        Element::new(na::vector![pos.0, pos.1], pressure).with_tilt_of(&element)
                     })
                     .collect::<Vec<Element>>(),
                 Err(e) => {
@@ -258,7 +258,7 @@ impl PenPathModeledBuilder {
                 self.buffer.extend(results.into_iter().map(|r| {
                     let pos = r.pos;
                     let pressure = r.pressure;
-                    Element::new(na::vector![pos.0, pos.1], pressure)
+                    Element::new(na::vector![pos.0, pos.1], pressure).with_tilt_of(&element)
                 }));
             }
             Err(e) => {
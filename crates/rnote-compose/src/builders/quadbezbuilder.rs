@@ -103,21 +103,21 @@ impl Buildable for QuadBezBuilder {
     }
 
     fn bounds(&self, style: &Style, zoom: f64) -> Option<Aabb> {
-        let stroke_width = style.stroke_width();
+        let bounds_margin = style.bounds_margin();
 
         match &self.state {
             QuadBezBuilderState::Cp { start, cp }
             | QuadBezBuilderState::CpFinished { start, cp } => Some(
                 Aabb::new_positive((*start).into(), (*cp).into())
-                    .loosened(stroke_width.max(indicators::POS_INDICATOR_RADIUS) / zoom),
+                    .loosened(bounds_margin.max(indicators::POS_INDICATOR_RADIUS) / zoom),
             ),
             QuadBezBuilderState::End { start, cp, end } => {
-                let stroke_width = style.stroke_width();
+                let bounds_margin = style.bounds_margin();
 
                 let mut aabb = Aabb::new_positive((*start).into(), (*end).into());
                 aabb.take_point((*cp).into());
 
-                Some(aabb.loosened(stroke_width.max(indicators::POS_INDICATOR_RADIUS) / zoom))
+                Some(aabb.loosened(bounds_margin.max(indicators::POS_INDICATOR_RADIUS) / zoom))
             }
         }
     }
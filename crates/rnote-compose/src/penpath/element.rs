@@ -13,6 +13,22 @@ pub struct Element {
     #[serde(rename = "pressure", with = "crate::serialize::f64_dp3")]
     /// The pen pressure. The valid range is [0.0, 1.0].
     pub pressure: f64,
+    #[serde(
+        rename = "tilt",
+        with = "crate::serialize::option_f64_dp3",
+        skip_serializing_if = "Option::is_none"
+    )]
+    /// The tilt of the pen away from the perpendicular in radians, when available.
+    /// The valid range is [0.0, π/2].
+    pub tilt: Option<f64>,
+    #[serde(
+        rename = "azimuth",
+        with = "crate::serialize::option_f64_dp3",
+        skip_serializing_if = "Option::is_none"
+    )]
+    /// The direction the pen is tilted towards in radians, when available.
+    /// Measured from the positive x-axis towards the positive y-axis, in the range [-π, π].
+    pub azimuth: Option<f64>,
 }
 
 impl Default for Element {
@@ -30,10 +46,12 @@ impl Transformable for Element {
         let mut isometry = na::Isometry2::identity();
         isometry.append_rotation_wrt_point_mut(&na::UnitComplex::new(angle), &center);
         self.pos = isometry.transform_point(&self.pos.into()).coords;
+        self.transform_azimuth(|dir| isometry.transform_vector(&dir));
     }
 
     fn scale(&mut self, scale: na::Vector2<f64>) {
         self.pos = self.pos.component_mul(&scale);
+        self.transform_azimuth(|dir| dir.component_mul(&scale));
     }
 }
 
@@ -46,9 +64,32 @@ impl Element {
         Self {
             pos,
             pressure: pressure.clamp(0.0, 1.0),
+            tilt: None,
+            azimuth: None,
         }
     }
 
+    /// The element with the tilt and azimuth, both in radians. The tilt is clamped to the range [0.0, π/2].
+    pub fn with_tilt(mut self, tilt: f64, azimuth: f64) -> Self {
+        self.tilt = Some(tilt.clamp(0.0, std::f64::consts::FRAC_PI_2));
+        self.azimuth = Some(na::UnitComplex::new(azimuth).angle());
+        self
+    }
+
+    /// The element with the tilt and azimuth calculated from the tilt angles in radians along the x and y axis,
+    /// as they are reported by most tablets.
+    pub fn with_xy_tilt(self, xtilt: f64, ytilt: f64) -> Self {
+        let dir = na::vector![xtilt.tan(), ytilt.tan()];
+        self.with_tilt(dir.magnitude().atan(), dir[1].atan2(dir[0]))
+    }
+
+    /// Copies the tilt and azimuth of the other element.
+    pub fn with_tilt_of(mut self, other: &Self) -> Self {
+        self.tilt = other.tilt;
+        self.azimuth = other.azimuth;
+        self
+    }
+
     /// Sets the pressure, clamped to the range [0.0 - 1.0].
     pub fn set_pressure_clamped(&mut self, pressure: f64) {
         self.pressure = pressure.clamp(0.0, 1.0);
@@ -64,5 +105,34 @@ impl Element {
     /// Transforms the element position by the given transform.
     pub fn transform_by(&mut self, transform: na::Affine2<f64>) {
         self.pos = transform.transform_point(&self.pos.into()).coords;
+        self.transform_azimuth(|dir| transform.transform_vector(&dir));
+    }
+
+    /// Linearly interpolates between the elements, including the pressure and the tilt.
+    pub(crate) fn lerp(&self, other: &Self, t: f64) -> Self {
+        let lerp = |a: f64, b: f64| a + (b - a) * t;
+        let mut element = Self::new(
+            self.pos.lerp(&other.pos, t),
+            lerp(self.pressure, other.pressure),
+        );
+
+        element.tilt = self.tilt.zip(other.tilt).map(|(a, b)| lerp(a, b));
+        element.azimuth = self.azimuth.zip(other.azimuth).map(|(a, b)| {
+            // interpolate along the shorter direction
+            let delta = na::UnitComplex::new(b - a).angle();
+            a + delta * t
+        });
+        element
+    }
+
+    /// Transforms the direction of the azimuth, leaving it unchanged when the direction degenerates.
+    fn transform_azimuth(&mut self, transform_dir: impl Fn(na::Vector2<f64>) -> na::Vector2<f64>) {
+        let Some(azimuth) = self.azimuth else {
+            return;
+        };
+        let dir = transform_dir(na::vector![azimuth.cos(), azimuth.sin()]);
+        if dir.magnitude_squared() > 0.0 {
+            self.azimuth = Some(dir[1].atan2(dir[0]));
+        }
     }
 }
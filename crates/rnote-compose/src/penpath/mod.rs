@@ -1,6 +1,7 @@
 // Modules
 mod element;
 mod segment;
+mod smoothing;
 
 // Re-exports
 pub use element::Element;
@@ -1,6 +1,8 @@
 // Imports
 use super::Element;
+use crate::shapes::{CubicBezier, QuadraticBezier, Shapeable};
 use crate::transform::Transformable;
+use kurbo::Shape;
 use serde::{Deserialize, Serialize};
 
 /// A single segment, usually of a pen path.
@@ -106,4 +108,51 @@ impl Segment {
             Segment::CubBezTo { end, .. } => *end,
         }
     }
+
+    /// The elements along the segment, starting with `prev`. Curves are subdivided, with interpolated elements.
+    pub(crate) fn subdivided_elements(&self, prev: &Element) -> Vec<Element> {
+        /// The approximate length of the lines a curve is subdivided into.
+        const SUBDIVISION_LEN: f64 = 2.0;
+        /// The maximum number of lines a curve is subdivided into.
+        const SUBDIVISIONS_MAX: f64 = 64.0;
+
+        let lines = match self {
+            Segment::LineTo { end } => {
+                return vec![*prev, *end];
+            }
+            Segment::QuadBezTo { cp, end } => {
+                let quadbez = QuadraticBezier {
+                    start: prev.pos,
+                    cp: *cp,
+                    end: end.pos,
+                };
+                let n_splits = (quadbez.outline_path().perimeter(0.25) / SUBDIVISION_LEN)
+                    .ceil()
+                    .clamp(2.0, SUBDIVISIONS_MAX) as i32;
+                quadbez.approx_with_lines(n_splits)
+            }
+            Segment::CubBezTo { cp1, cp2, end } => {
+                let cubbez = CubicBezier {
+                    start: prev.pos,
+                    cp1: *cp1,
+                    cp2: *cp2,
+                    end: end.pos,
+                };
+                let n_splits = (cubbez.outline_path().perimeter(0.25) / SUBDIVISION_LEN)
+                    .ceil()
+                    .clamp(2.0, SUBDIVISIONS_MAX) as i32;
+                cubbez.approx_with_lines(n_splits)
+            }
+        };
+        let end = self.end();
+        let n_lines = lines.len() as f64;
+
+        std::iter::once(*prev)
+            .chain(lines.iter().enumerate().map(|(i, line)| {
+                let mut element = prev.lerp(&end, (i + 1) as f64 / n_lines);
+                element.pos = line.end;
+                element
+            }))
+            .collect()
+    }
 }
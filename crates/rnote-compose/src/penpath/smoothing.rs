@@ -0,0 +1,280 @@
+// Imports
+use super::{Element, PenPath, Segment};
+
+impl PenPath {
+    /// The maximum deviation of the pressure when reducing the elements of a path.
+    pub const PRESSURE_TOLERANCE: f64 = 0.05;
+
+    /// Returns a smoothed copy of the path, with the jitter removed and fewer segments.
+    ///
+    /// The elements are averaged with their neighbours in the given window radius, then reduced to the ones that
+    /// deviate more than `tolerance` in position or [Self::PRESSURE_TOLERANCE] in pressure from the path between
+    /// them. The remaining elements are connected by cubic bezier segments of a Catmull-Rom spline.
+    /// The start and end positions and all pressures are kept.
+    pub fn smoothed(&self, window_radius: usize, tolerance: f64) -> Self {
+        let elements = self.clone().into_elements();
+        if elements.len() < 3 {
+            return self.clone();
+        }
+
+        let smoothed = remove_jitter(&elements, window_radius);
+        let reduced = reduce_elements(&smoothed, tolerance, Self::PRESSURE_TOLERANCE);
+
+        Self::new_w_segments(reduced[0], spline_segments(&reduced))
+    }
+
+    /// Returns a simplified copy of the path with fewer elements, without smoothing it.
+    ///
+    /// Elements are removed when neither their position deviates more than `tolerance` nor their pressure more than
+    /// [Self::PRESSURE_TOLERANCE] from the path between the remaining ones.
+    /// Paths made of line segments stay that way, all others are connected by a Catmull-Rom spline.
+    /// A `tolerance` that is not greater than zero leaves the path unchanged.
+    pub fn simplified(&self, tolerance: f64) -> Self {
+        let lines_only = self
+            .segments
+            .iter()
+            .all(|seg| matches!(seg, Segment::LineTo { .. }));
+        let elements = self.clone().into_elements();
+        if elements.len() < 3 || tolerance.is_nan() || tolerance <= 0.0 {
+            return self.clone();
+        }
+
+        let reduced = reduce_elements(&elements, tolerance, Self::PRESSURE_TOLERANCE);
+        let segments = if lines_only {
+            reduced[1..]
+                .iter()
+                .map(|&end| Segment::LineTo { end })
+                .collect()
+        } else {
+            spline_segments(&reduced)
+        };
+
+        Self::new_w_segments(reduced[0], segments)
+    }
+}
+
+/// Averages the elements with their neighbours, with weights that decrease linearly with the distance.
+///
+/// The window shrinks towards the ends, so that the start and end elements stay in place.
+fn remove_jitter(elements: &[Element], window_radius: usize) -> Vec<Element> {
+    let n = elements.len();
+
+    (0..n)
+        .map(|i| {
+            let radius = window_radius.min(i).min(n - 1 - i);
+            let (pos_sum, pressure_sum, weight_sum) = (i - radius..=i + radius).fold(
+                (na::Vector2::<f64>::zeros(), 0.0, 0.0),
+                |(pos_sum, pressure_sum, weight_sum), j| {
+                    let weight = (radius + 1 - i.abs_diff(j)) as f64;
+                    (
+                        pos_sum + elements[j].pos * weight,
+                        pressure_sum + elements[j].pressure * weight,
+                        weight_sum + weight,
+                    )
+                },
+            );
+            let mut element = elements[i];
+            element.pos = pos_sum / weight_sum;
+            element.pressure = pressure_sum / weight_sum;
+            element
+        })
+        .collect()
+}
+
+/// Reduces the elements with the Ramer-Douglas-Peucker algorithm, where an element is kept
+/// when either its position or its pressure deviates more than the tolerances from the interpolation
+/// between the first and last element.
+pub(super) fn reduce_elements(
+    elements: &[Element],
+    tolerance: f64,
+    pressure_tolerance: f64,
+) -> Vec<Element> {
+    if elements.len() < 3 {
+        return elements.to_vec();
+    }
+    let first = elements[0];
+    let last = elements[elements.len() - 1];
+    let v = last.pos - first.pos;
+    let len_squared = v.magnitude_squared();
+
+    let (max_index, max_deviation) = elements[1..elements.len() - 1]
+        .iter()
+        .enumerate()
+        .map(|(i, el)| {
+            let t = if len_squared > 0.0 {
+                ((el.pos - first.pos).dot(&v) / len_squared).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let pos_deviation = (el.pos - (first.pos + v * t)).magnitude();
+            let pressure_deviation =
+                (el.pressure - (first.pressure + (last.pressure - first.pressure) * t)).abs();
+            (
+                i + 1,
+                (pos_deviation / tolerance).max(pressure_deviation / pressure_tolerance),
+            )
+        })
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .unwrap();
+
+    if max_deviation > 1.0 {
+        let mut reduced = reduce_elements(&elements[..=max_index], tolerance, pressure_tolerance);
+        reduced.pop();
+        reduced.extend(reduce_elements(
+            &elements[max_index..],
+            tolerance,
+            pressure_tolerance,
+        ));
+        reduced
+    } else {
+        vec![first, last]
+    }
+}
+
+/// Connects the elements with the cubic bezier segments of a Catmull-Rom spline.
+///
+/// The tangents are shortened at sharp turns and uneven spacing, so that the curve doesn't overshoot.
+fn spline_segments(elements: &[Element]) -> Vec<Segment> {
+    let n = elements.len();
+    let tangent = |i: usize| -> na::Vector2<f64> {
+        let prev = elements[i.saturating_sub(1)].pos;
+        let next = elements[(i + 1).min(n - 1)].pos;
+        let tangent = (next - prev) * 0.5;
+
+        let max_len = [
+            (elements[i].pos - prev).magnitude(),
+            (next - elements[i].pos).magnitude(),
+        ]
+        .into_iter()
+        .filter(|len| *len > 0.0)
+        .fold(f64::MAX, f64::min);
+        let len = tangent.magnitude();
+        if len > max_len {
+            tangent * (max_len / len)
+        } else {
+            tangent
+        }
+    };
+
+    (0..n - 1)
+        .map(|i| Segment::CubBezTo {
+            cp1: elements[i].pos + tangent(i) / 3.0,
+            cp2: elements[i + 1].pos - tangent(i + 1) / 3.0,
+            end: elements[i + 1],
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(elements: &[(f64, f64, f64)]) -> PenPath {
+        PenPath::try_from_elements(
+            elements
+                .iter()
+                .map(|&(x, y, pressure)| Element::new(na::vector![x, y], pressure)),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn simplified_reduces_straight_run() {
+        let straight = path(
+            &(0..=10)
+                .map(|i| (i as f64, 0.0, 0.5))
+                .collect::<Vec<(f64, f64, f64)>>(),
+        );
+        let simplified = straight.clone().simplified(0.1);
+
+        assert!(simplified
+            .segments
+            .iter()
+            .all(|seg| matches!(seg, Segment::LineTo { .. })));
+        assert_eq!(
+            simplified.into_elements(),
+            vec![
+                Element::new(na::vector![0.0, 0.0], 0.5),
+                Element::new(na::vector![10.0, 0.0], 0.5)
+            ]
+        );
+    }
+
+    #[test]
+    fn simplified_keeps_pressure_of_retained_elements() {
+        let original = path(&[
+            (0.0, 0.0, 0.5),
+            (1.0, 0.0, 0.5),
+            (2.0, 0.0, 0.9),
+            (3.0, 0.0, 0.5),
+            (4.0, 0.0, 0.5),
+        ]);
+        let simplified = original.clone().simplified(0.1).into_elements();
+
+        // The pressure peak deviates from the interpolation, so it is kept even though the path is straight.
+        assert_eq!(
+            simplified,
+            vec![
+                Element::new(na::vector![0.0, 0.0], 0.5),
+                Element::new(na::vector![1.0, 0.0], 0.5),
+                Element::new(na::vector![2.0, 0.0], 0.9),
+                Element::new(na::vector![3.0, 0.0], 0.5),
+                Element::new(na::vector![4.0, 0.0], 0.5),
+            ]
+        );
+    }
+
+    #[test]
+    fn simplified_keeps_corners() {
+        let original = path(&[
+            (0.0, 0.0, 0.5),
+            (5.0, 0.0, 0.5),
+            (10.0, 0.0, 0.5),
+            (10.0, 5.0, 0.5),
+            (10.0, 10.0, 0.5),
+        ]);
+        let simplified = original.simplified(0.1).into_elements();
+
+        assert_eq!(
+            simplified
+                .iter()
+                .map(|el| (el.pos.x, el.pos.y))
+                .collect::<Vec<(f64, f64)>>(),
+            vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)]
+        );
+    }
+
+    #[test]
+    fn smoothed_keeps_endpoints() {
+        let jittery = path(
+            &(0..=20)
+                .map(|i| (i as f64, if i % 2 == 0 { 0.1 } else { -0.1 }, 0.5))
+                .collect::<Vec<(f64, f64, f64)>>(),
+        );
+        let original = jittery.clone().into_elements();
+        let smoothed = jittery.smoothed(2, 0.5);
+        let elements = smoothed.clone().into_elements();
+
+        assert!(smoothed
+            .segments
+            .iter()
+            .all(|seg| matches!(seg, Segment::CubBezTo { .. })));
+        assert!(elements.len() < original.len());
+        assert_eq!(elements.first(), original.first());
+        assert_eq!(elements.last(), original.last());
+        assert!(elements.iter().all(|el| el.pressure == 0.5));
+    }
+
+    #[test]
+    fn short_paths_unchanged() {
+        let single = PenPath::new(Element::new(na::vector![1.0, 2.0], 0.3));
+        let pair = path(&[(1.0, 2.0, 0.3), (3.0, 4.0, 0.7)]);
+
+        for original in [single, pair] {
+            let elements = original.clone().into_elements();
+            assert_eq!(original.smoothed(3, 0.5).into_elements(), elements);
+            assert_eq!(original.simplified(0.5).into_elements(), elements);
+        }
+        assert!(reduce_elements(&[], 0.5, PenPath::PRESSURE_TOLERANCE).is_empty());
+    }
+}
@@ -0,0 +1,488 @@
+//! Recognition of shapes in hand-drawn paths.
+//!
+//! The classifier works on point sequences, so that it is independent of how the points were drawn.
+//! The points are first resampled evenly, then a closed path is matched against an ellipse, a rectangle and
+//! a polygon and an open path against a line and an arrow.
+
+// Imports
+use crate::ext::Vector2Ext;
+use crate::shapes::{Arrow, Ellipse, Line, Polygon, Rectangle};
+use crate::transform::Transformable;
+use crate::{PenPath, Shape, Transform};
+
+/// The number of points the path gets evenly resampled to before classifying it.
+const RESAMPLE_N_POINTS: usize = 64;
+/// The minimum diagonal of the path bounds. Smaller paths are never recognized as shapes.
+const MIN_SIZE: f64 = 4.0;
+/// The distance between start and end in relation to the path length, below which a path is closed.
+const CLOSED_DISTANCE_RATIO: f64 = 0.2;
+/// The maximum deviation from a straight line in relation to its length.
+const LINE_DEVIATION_RATIO: f64 = 0.06;
+/// The tolerance for simplifying closed paths in relation to the bounds diagonal.
+const SIMPLIFY_TOLERANCE_RATIO: f64 = 0.06;
+/// The maximum mean deviation from the fitted ellipse, in relation to its radii.
+const ELLIPSE_MAX_DEVIATION: f64 = 0.08;
+/// The maximum mean distance of the points to the fitted polygon in relation to the bounds diagonal.
+const POLYGON_MAX_DEVIATION_RATIO: f64 = 0.04;
+/// The maximum number of polygon corners.
+const POLYGON_MAX_CORNERS: usize = 10;
+/// Corners that turn less than this angle are removed from polygons.
+const CORNER_MIN_ANGLE: f64 = std::f64::consts::PI / 8.0;
+/// The maximum deviation of the corners of a rectangle from a right angle.
+const RECTANGLE_MAX_ANGLE_DEVIATION: f64 = std::f64::consts::PI / 7.0;
+/// The maximum length of the arrow head in relation to the arrow stem.
+const ARROW_HEAD_MAX_RATIO: f64 = 0.6;
+/// The minimum length of the arrow head in relation to the arrow stem.
+const ARROW_HEAD_MIN_RATIO: f64 = 0.1;
+
+/// Recognize the shape of a pen path.
+///
+/// The path is flattened with the given tolerance before classifying it.
+pub fn recognize_penpath(penpath: &PenPath, tolerance: f64) -> Option<Shape> {
+    let points = penpath
+        .to_kurbo_flattened(tolerance)
+        .elements()
+        .iter()
+        .filter_map(|el| match el {
+            kurbo::PathEl::MoveTo(p) | kurbo::PathEl::LineTo(p) => {
+                Some(na::Vector2::from_kurbo_point(*p))
+            }
+            _ => None,
+        })
+        .collect::<Vec<na::Vector2<f64>>>();
+
+    recognize_shape(&points)
+}
+
+/// Recognize the shape of a sequence of points.
+///
+/// Returns a [Shape::Line], [Shape::Arrow], [Shape::Rectangle], [Shape::Ellipse] or [Shape::Polygon],
+/// or None if the points don't resemble any of them.
+pub fn recognize_shape(points: &[na::Vector2<f64>]) -> Option<Shape> {
+    let length = path_length(points);
+    if points.len() < 2 || length <= 0.0 || bounds_diagonal(points) < MIN_SIZE {
+        return None;
+    }
+    let points = resample(points, RESAMPLE_N_POINTS);
+    let start = points[0];
+    let end = points[points.len() - 1];
+
+    if (end - start).magnitude() < length * CLOSED_DISTANCE_RATIO {
+        recognize_closed(&points)
+    } else {
+        recognize_open(&points)
+    }
+}
+
+fn recognize_open(points: &[na::Vector2<f64>]) -> Option<Shape> {
+    let start = points[0];
+    let end = points[points.len() - 1];
+
+    if is_straight(points) {
+        return Some(Shape::Line(Line::new(start, end)));
+    }
+
+    // An arrow drawn in one stroke has a straight stem up to the tip, which is the point farthest from the start,
+    // followed by a short head that goes back towards the start.
+    let (tip_index, tip) = points
+        .iter()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| {
+            (*a - start)
+                .magnitude()
+                .total_cmp(&(*b - start).magnitude())
+        })
+        .map(|(i, p)| (i, *p))?;
+    let stem = tip - start;
+    let stem_len = stem.magnitude();
+    let head = &points[tip_index..];
+    let head_len = path_length(head);
+
+    if tip_index < 2
+        || !is_straight(&points[..=tip_index])
+        || head_len < stem_len * ARROW_HEAD_MIN_RATIO
+        || head_len > stem_len * ARROW_HEAD_MAX_RATIO
+        || head.iter().skip(1).any(|p| {
+            (p - tip).magnitude() > stem_len * ARROW_HEAD_MAX_RATIO || (p - tip).dot(&stem) > 0.0
+        })
+    {
+        return None;
+    }
+
+    Some(Shape::Arrow(Arrow::new(start, tip)))
+}
+
+fn recognize_closed(points: &[na::Vector2<f64>]) -> Option<Shape> {
+    let diagonal = bounds_diagonal(points);
+
+    if let Some(ellipse) = fit_ellipse(points) {
+        return Some(Shape::Ellipse(ellipse));
+    }
+
+    let corners = polygon_corners(points, diagonal * SIMPLIFY_TOLERANCE_RATIO);
+    if corners.len() < 3
+        || corners.len() > POLYGON_MAX_CORNERS
+        || mean_distance_to_polygon(points, &corners) > diagonal * POLYGON_MAX_DEVIATION_RATIO
+    {
+        return None;
+    }
+
+    if corners.len() == 4 {
+        if let Some(rectangle) = fit_rectangle(&corners) {
+            return Some(Shape::Rectangle(rectangle));
+        }
+    }
+
+    let mut polygon = Polygon::new(corners[0]);
+    polygon.extend(corners[1..].iter().copied());
+    Some(Shape::Polygon(polygon))
+}
+
+/// Fits an ellipse along the principal axes of the points.
+fn fit_ellipse(points: &[na::Vector2<f64>]) -> Option<Ellipse> {
+    let n = points.len() as f64;
+    let mean = points.iter().sum::<na::Vector2<f64>>() / n;
+    let (sxx, syy, sxy) = points.iter().fold((0.0, 0.0, 0.0), |(sxx, syy, sxy), p| {
+        let d = p - mean;
+        (sxx + d[0] * d[0], syy + d[1] * d[1], sxy + d[0] * d[1])
+    });
+    let angle = 0.5 * (2.0 * sxy).atan2(sxx - syy);
+    let rotation = na::Rotation2::new(-angle);
+
+    let local = points
+        .iter()
+        .map(|p| rotation * (p - mean))
+        .collect::<Vec<na::Vector2<f64>>>();
+    let mins = local
+        .iter()
+        .fold(na::Vector2::repeat(f64::MAX), |acc, p| acc.inf(p));
+    let maxs = local
+        .iter()
+        .fold(na::Vector2::repeat(f64::MIN), |acc, p| acc.sup(p));
+    let radii = (maxs - mins) * 0.5;
+    if radii[0] <= 0.0 || radii[1] <= 0.0 {
+        return None;
+    }
+    let local_center = (mins + maxs) * 0.5;
+
+    let deviation = local
+        .iter()
+        .map(|p| ((p - local_center).component_div(&radii).magnitude() - 1.0).abs())
+        .sum::<f64>()
+        / n;
+    if deviation > ELLIPSE_MAX_DEVIATION {
+        return None;
+    }
+
+    let center = mean + rotation.inverse() * local_center;
+    Some(Ellipse {
+        radii,
+        transform: Transform::new_w_isometry(na::Isometry2::new(center, angle)),
+    })
+}
+
+/// Fits a rectangle to four corners, if all of them are close to right angles.
+fn fit_rectangle(corners: &[na::Vector2<f64>]) -> Option<Rectangle> {
+    let right_angled = (0..4).all(|i| {
+        let prev = corners[(i + 3) % 4];
+        let corner = corners[i];
+        let next = corners[(i + 1) % 4];
+        let angle = (prev - corner).angle(&(next - corner));
+        (angle - std::f64::consts::FRAC_PI_2).abs() < RECTANGLE_MAX_ANGLE_DEVIATION
+    });
+    if !right_angled {
+        return None;
+    }
+
+    let center = corners.iter().sum::<na::Vector2<f64>>() / 4.0;
+    let first_edge = corners[1] - corners[0];
+    let second_edge = corners[2] - corners[1];
+    let width = (first_edge.magnitude() + (corners[3] - corners[2]).magnitude()) * 0.5;
+    let height = (second_edge.magnitude() + (corners[0] - corners[3]).magnitude()) * 0.5;
+    let angle = na::Vector2::x().angle_ahead(&first_edge);
+
+    let mut rectangle =
+        Rectangle::from_half_extents(na::Vector2::zeros(), na::vector![width * 0.5, height * 0.5]);
+    rectangle.rotate(angle, na::Point2::origin());
+    rectangle.translate(center);
+    Some(rectangle)
+}
+
+/// The corners of a closed path, simplified with the given tolerance.
+fn polygon_corners(points: &[na::Vector2<f64>], tolerance: f64) -> Vec<na::Vector2<f64>> {
+    let start = points[0];
+    // Split at the point farthest from the start, so that both halves can be simplified as open paths
+    let Some(far_index) = (1..points.len()).max_by(|a, b| {
+        (points[*a] - start)
+            .magnitude()
+            .total_cmp(&(points[*b] - start).magnitude())
+    }) else {
+        return vec![];
+    };
+
+    let mut corners = simplify(&points[..=far_index], tolerance);
+    corners.pop();
+    corners.extend(simplify(&points[far_index..], tolerance));
+    // The end closes the path onto the start
+    corners.pop();
+
+    // The path may start in the middle of an edge, or have small wiggles that survived simplifying
+    loop {
+        let n = corners.len();
+        if n < 3 {
+            break;
+        }
+        let Some(flat) = (0..n).find(|i| {
+            let prev = corners[(i + n - 1) % n];
+            let corner = corners[*i];
+            let next = corners[(i + 1) % n];
+            (corner - prev).angle(&(next - corner)) < CORNER_MIN_ANGLE
+        }) else {
+            break;
+        };
+        corners.remove(flat);
+    }
+
+    corners
+}
+
+/// Simplifies an open path with the Ramer-Douglas-Peucker algorithm.
+fn simplify(points: &[na::Vector2<f64>], tolerance: f64) -> Vec<na::Vector2<f64>> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+    let start = points[0];
+    let end = points[points.len() - 1];
+
+    let (max_index, max_distance) = points[1..points.len() - 1]
+        .iter()
+        .enumerate()
+        .map(|(i, p)| (i + 1, distance_to_segment(*p, start, end)))
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .unwrap();
+
+    if max_distance > tolerance {
+        let mut simplified = simplify(&points[..=max_index], tolerance);
+        simplified.pop();
+        simplified.extend(simplify(&points[max_index..], tolerance));
+        simplified
+    } else {
+        vec![start, end]
+    }
+}
+
+fn is_straight(points: &[na::Vector2<f64>]) -> bool {
+    let start = points[0];
+    let end = points[points.len() - 1];
+    let chord = (end - start).magnitude();
+
+    chord > 0.0
+        && points
+            .iter()
+            .all(|p| distance_to_segment(*p, start, end) <= chord * LINE_DEVIATION_RATIO)
+}
+
+fn mean_distance_to_polygon(points: &[na::Vector2<f64>], corners: &[na::Vector2<f64>]) -> f64 {
+    points
+        .iter()
+        .map(|p| {
+            (0..corners.len())
+                .map(|i| distance_to_segment(*p, corners[i], corners[(i + 1) % corners.len()]))
+                .fold(f64::MAX, f64::min)
+        })
+        .sum::<f64>()
+        / points.len() as f64
+}
+
+fn distance_to_segment(p: na::Vector2<f64>, start: na::Vector2<f64>, end: na::Vector2<f64>) -> f64 {
+    let v = end - start;
+    let len_squared = v.magnitude_squared();
+    if len_squared <= 0.0 {
+        return (p - start).magnitude();
+    }
+    let t = ((p - start).dot(&v) / len_squared).clamp(0.0, 1.0);
+    (p - (start + v * t)).magnitude()
+}
+
+fn path_length(points: &[na::Vector2<f64>]) -> f64 {
+    points.windows(2).map(|w| (w[1] - w[0]).magnitude()).sum()
+}
+
+fn bounds_diagonal(points: &[na::Vector2<f64>]) -> f64 {
+    let mins = points
+        .iter()
+        .fold(na::Vector2::repeat(f64::MAX), |acc, p| acc.inf(p));
+    let maxs = points
+        .iter()
+        .fold(na::Vector2::repeat(f64::MIN), |acc, p| acc.sup(p));
+    (maxs - mins).magnitude()
+}
+
+/// Resamples the points to `n` points that are evenly spaced along the path.
+fn resample(points: &[na::Vector2<f64>], n: usize) -> Vec<na::Vector2<f64>> {
+    let interval = path_length(points) / (n - 1) as f64;
+    let mut resampled = Vec::with_capacity(n);
+    resampled.push(points[0]);
+
+    let mut prev = points[0];
+    let mut accumulated = 0.0;
+    for &p in points[1..].iter() {
+        let mut segment_start = prev;
+        let mut segment_len = (p - segment_start).magnitude();
+
+        while accumulated + segment_len >= interval && resampled.len() < n - 1 {
+            let t = (interval - accumulated) / segment_len;
+            let new = segment_start + (p - segment_start) * t;
+            resampled.push(new);
+            segment_start = new;
+            segment_len = (p - segment_start).magnitude();
+            accumulated = 0.0;
+        }
+
+        accumulated += segment_len;
+        prev = p;
+    }
+    resampled.push(points[points.len() - 1]);
+
+    resampled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shapes::Shapeable;
+
+    fn ellipse_points(
+        center: na::Vector2<f64>,
+        radii: na::Vector2<f64>,
+        n: usize,
+    ) -> Vec<na::Vector2<f64>> {
+        (0..=n)
+            .map(|i| {
+                let t = i as f64 / n as f64 * std::f64::consts::TAU;
+                center + na::vector![radii[0] * t.cos(), radii[1] * t.sin()]
+            })
+            .collect()
+    }
+
+    fn polyline_points(corners: &[na::Vector2<f64>], per_edge: usize) -> Vec<na::Vector2<f64>> {
+        let mut points = Vec::new();
+        for w in corners.windows(2) {
+            for i in 0..per_edge {
+                points.push(w[0] + (w[1] - w[0]) * (i as f64 / per_edge as f64));
+            }
+        }
+        points.push(corners[corners.len() - 1]);
+        points
+    }
+
+    /// Adds a small deterministic jitter, like a shaky hand.
+    fn jittered(points: Vec<na::Vector2<f64>>, amount: f64) -> Vec<na::Vector2<f64>> {
+        points
+            .into_iter()
+            .enumerate()
+            .map(|(i, p)| p + na::vector![(i as f64 * 1.7).sin(), (i as f64 * 2.3).cos()] * amount)
+            .collect()
+    }
+
+    #[test]
+    fn recognize_line() {
+        let points = jittered(
+            polyline_points(&[na::vector![0.0, 0.0], na::vector![100.0, 50.0]], 30),
+            0.5,
+        );
+        let Some(Shape::Line(line)) = recognize_shape(&points) else {
+            panic!("not recognized as line");
+        };
+        assert!((line.start - points[0]).magnitude() < 1e-6);
+        assert!((line.end - points[points.len() - 1]).magnitude() < 1e-6);
+    }
+
+    #[test]
+    fn recognize_arrow() {
+        let points = polyline_points(
+            &[
+                na::vector![0.0, 0.0],
+                na::vector![100.0, 0.0],
+                na::vector![85.0, -10.0],
+            ],
+            20,
+        );
+        let Some(Shape::Arrow(arrow)) = recognize_shape(&points) else {
+            panic!("not recognized as arrow");
+        };
+        assert!((arrow.tip - na::vector![100.0, 0.0]).magnitude() < 2.0);
+    }
+
+    #[test]
+    fn recognize_ellipse() {
+        let points = jittered(
+            ellipse_points(na::vector![50.0, 20.0], na::vector![40.0, 20.0], 80),
+            0.5,
+        );
+        let Some(Shape::Ellipse(ellipse)) = recognize_shape(&points) else {
+            panic!("not recognized as ellipse");
+        };
+        let bounds = ellipse.bounds();
+        assert!((bounds.center().coords - na::vector![50.0, 20.0]).magnitude() < 2.0);
+        assert!((bounds.half_extents() - na::vector![40.0, 20.0]).magnitude() < 3.0);
+    }
+
+    #[test]
+    fn recognize_rectangle() {
+        let points = jittered(
+            polyline_points(
+                &[
+                    na::vector![30.0, 0.0],
+                    na::vector![100.0, 0.0],
+                    na::vector![100.0, 50.0],
+                    na::vector![0.0, 50.0],
+                    na::vector![0.0, 0.0],
+                    na::vector![30.0, 0.0],
+                ],
+                20,
+            ),
+            0.5,
+        );
+        let Some(Shape::Rectangle(rectangle)) = recognize_shape(&points) else {
+            panic!("not recognized as rectangle");
+        };
+        let bounds = rectangle.bounds();
+        assert!((bounds.mins.coords - na::vector![0.0, 0.0]).magnitude() < 3.0);
+        assert!((bounds.maxs.coords - na::vector![100.0, 50.0]).magnitude() < 3.0);
+    }
+
+    #[test]
+    fn recognize_triangle() {
+        let points = polyline_points(
+            &[
+                na::vector![0.0, 0.0],
+                na::vector![100.0, 0.0],
+                na::vector![50.0, 80.0],
+                na::vector![0.0, 0.0],
+            ],
+            20,
+        );
+        let Some(Shape::Polygon(polygon)) = recognize_shape(&points) else {
+            panic!("not recognized as polygon");
+        };
+        assert_eq!(polygon.path.len() + 1, 3);
+    }
+
+    #[test]
+    fn reject_scribble() {
+        let points = (0..100)
+            .map(|i| {
+                let t = i as f64 * 0.3;
+                na::vector![t * 10.0, (t * 3.0).sin() * 30.0]
+            })
+            .collect::<Vec<na::Vector2<f64>>>();
+        assert!(recognize_shape(&points).is_none());
+    }
+
+    #[test]
+    fn reject_too_small() {
+        let points = vec![na::vector![0.0, 0.0], na::vector![1.0, 1.0]];
+        assert!(recognize_shape(&points).is_none());
+    }
+}
@@ -84,10 +84,10 @@ impl Arrow {
     const TIP_LINES_DEFAULT_LENGTH: f64 = 10.0;
 
     /// The angle for `rline` and `lline` to the stem of the arrow.
-    const TIP_LINES_STEM_OBTUSE_ANGLE: f64 = (13.0 / 16.0) * std::f64::consts::PI;
+    pub(crate) const TIP_LINES_STEM_OBTUSE_ANGLE: f64 = (13.0 / 16.0) * std::f64::consts::PI;
 
     /// The default direction vector (the stem) if the stem has length 0.
-    const DEFAULT_DIRECTION_VECTOR: na::Vector2<f64> = na::Vector2::new(1.0, 0.0);
+    pub(crate) const DEFAULT_DIRECTION_VECTOR: na::Vector2<f64> = na::Vector2::new(1.0, 0.0);
 
     /// Creating a new arrow with the given start and tip vectors.
     pub fn new(start: na::Vector2<f64>, tip: na::Vector2<f64>) -> Self {
@@ -181,7 +181,7 @@ impl Arrow {
     /// Compute the length of the tip lines.
     ///
     /// Optionally add the stroke width to adjust the length of the line.
-    fn compute_tip_lines_length(stroke_width: Option<f64>) -> f64 {
+    pub(crate) fn compute_tip_lines_length(stroke_width: Option<f64>) -> f64 {
         let factor = stroke_width.unwrap_or(0.0);
         Self::TIP_LINES_DEFAULT_LENGTH * (1.0 + 0.18 * factor)
     }
@@ -0,0 +1,91 @@
+// Imports
+use crate::penpath::Element;
+use crate::style::PressureCurve;
+use crate::Color;
+use serde::{Deserialize, Serialize};
+
+/// Options for pen paths that can be drawn in a calligraphy style, with a broad-edged nib.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename = "calligraphy_options")]
+pub struct CalligraphyOptions {
+    /// Stroke width, the length of the broad edge of the nib.
+    #[serde(rename = "stroke_width", with = "crate::serialize::f64_dp3")]
+    pub stroke_width: f64,
+    /// Stroke color. When set to None, the stroke is not drawn.
+    #[serde(rename = "stroke_color")]
+    pub stroke_color: Option<Color>,
+    /// The angle of the broad edge of the nib in radians.
+    ///
+    /// Measured from the positive x-axis towards the positive y-axis, which points downwards.
+    #[serde(rename = "nib_angle", with = "crate::serialize::f64_dp3")]
+    pub nib_angle: f64,
+    /// The thickness of the nib, relative to the stroke width.
+    #[serde(rename = "thin_width_ratio", with = "crate::serialize::f64_dp3")]
+    pub thin_width_ratio: f64,
+    /// Pressure curve.
+    #[serde(rename = "pressure_curve")]
+    pub pressure_curve: PressureCurve,
+    /// Whether the pen tilt and azimuth adjust the nib, when they are available.
+    ///
+    /// The nib broadens with the tilt and its edge is perpendicular to the direction the pen is tilted towards.
+    #[serde(rename = "follow_tilt")]
+    pub follow_tilt: bool,
+}
+
+impl Default for CalligraphyOptions {
+    fn default() -> Self {
+        Self {
+            stroke_width: 8.0,
+            stroke_color: Some(Color::BLACK),
+            nib_angle: Self::NIB_ANGLE_DEFAULT,
+            thin_width_ratio: Self::THIN_WIDTH_RATIO_DEFAULT,
+            pressure_curve: PressureCurve::default(),
+            follow_tilt: true,
+        }
+    }
+}
+
+impl CalligraphyOptions {
+    /// The default nib angle, with the broad edge going from the lower left to the upper right.
+    pub const NIB_ANGLE_DEFAULT: f64 = -std::f64::consts::FRAC_PI_4;
+    /// The default thickness of the nib, relative to the stroke width.
+    pub const THIN_WIDTH_RATIO_DEFAULT: f64 = 0.1;
+    /// Minimum thickness of the nib, relative to the stroke width.
+    pub const THIN_WIDTH_RATIO_MIN: f64 = 0.01;
+    /// Maximum thickness of the nib, relative to the stroke width.
+    pub const THIN_WIDTH_RATIO_MAX: f64 = 1.0;
+    /// How much the width is reduced when the pen is held perpendicular, compared to fully tilted.
+    const TILT_WIDTH_WEIGHT: f64 = 0.5;
+    /// The minimum tilt in radians for the azimuth to determine the nib angle.
+    const AZIMUTH_TILT_THRESHOLD: f64 = 0.2;
+
+    /// The width and angle of the nib for the element.
+    pub(super) fn nib_for_element(&self, element: &Element) -> (f64, f64) {
+        let mut width = self
+            .pressure_curve
+            .apply(self.stroke_width, element.pressure);
+        let mut angle = self.nib_angle;
+
+        if self.follow_tilt {
+            if let Some(tilt) = element.tilt {
+                width *= 1.0 - Self::TILT_WIDTH_WEIGHT * (1.0 - tilt / std::f64::consts::FRAC_PI_2);
+            }
+            // the azimuth is unreliable when the pen is held almost perpendicular
+            if let (Some(tilt), Some(azimuth)) = (element.tilt, element.azimuth) {
+                if tilt >= Self::AZIMUTH_TILT_THRESHOLD {
+                    angle = azimuth + std::f64::consts::FRAC_PI_2;
+                }
+            }
+        }
+
+        (width, angle)
+    }
+
+    /// The thickness of the nib.
+    pub(super) fn thin_width(&self) -> f64 {
+        self.stroke_width
+            * self
+                .thin_width_ratio
+                .clamp(Self::THIN_WIDTH_RATIO_MIN, Self::THIN_WIDTH_RATIO_MAX)
+    }
+}
@@ -0,0 +1,153 @@
+// Modules
+mod calligraphyoptions;
+
+// Re-exports
+pub use calligraphyoptions::CalligraphyOptions;
+
+// Imports
+use super::Composer;
+use crate::ext::Vector2Ext;
+use crate::penpath::{Element, Segment};
+use crate::shapes::{Line, Shapeable};
+use crate::PenPath;
+use p2d::bounding_volume::{Aabb, BoundingVolume};
+
+impl Composer<CalligraphyOptions> for Line {
+    fn composed_bounds(&self, options: &CalligraphyOptions) -> Aabb {
+        self.bounds()
+            .loosened((options.stroke_width + options.thin_width()) * 0.5)
+    }
+
+    fn draw_composed(&self, cx: &mut impl piet::RenderContext, options: &CalligraphyOptions) {
+        let mut options = options.clone();
+        options.pressure_curve = crate::style::PressureCurve::Const;
+
+        PenPath::new_w_segments(
+            Element::new(self.start, Element::PRESSURE_DEFAULT),
+            [Segment::LineTo {
+                end: Element::new(self.end, Element::PRESSURE_DEFAULT),
+            }],
+        )
+        .draw_composed(cx, &options);
+    }
+}
+
+impl Composer<CalligraphyOptions> for PenPath {
+    fn composed_bounds(&self, options: &CalligraphyOptions) -> Aabb {
+        self.bounds()
+            .loosened((options.stroke_width + options.thin_width()) * 0.5)
+    }
+
+    fn draw_composed(&self, cx: &mut impl piet::RenderContext, options: &CalligraphyOptions) {
+        let Some(color) = options.stroke_color else {
+            return;
+        };
+        let thin_width = options.thin_width();
+        let mut bez_path = kurbo::BezPath::new();
+        let mut prev = self.start;
+
+        extend_w_nib_stamp(&mut bez_path, &self.start, thin_width, options);
+
+        for seg in self.segments.iter() {
+            let elements = seg.subdivided_elements(&prev);
+
+            for (start, end) in elements.iter().zip(elements.iter().skip(1)) {
+                extend_w_nib_sweep(&mut bez_path, start, end, thin_width, options);
+                extend_w_nib_stamp(&mut bez_path, end, thin_width, options);
+            }
+
+            prev = seg.end();
+        }
+
+        cx.save().unwrap();
+        // All subpaths have the same orientation, so the non-zero fill rule draws their union.
+        cx.fill(bez_path, &Into::<piet::Color>::into(color));
+        cx.restore().unwrap();
+    }
+}
+
+/// Adds the area the broad edge of the nib covers when moving from `start` to `end`,
+/// as a quadrilateral and a thin quadrilateral along the path for when the movement is parallel to the edge.
+fn extend_w_nib_sweep(
+    bez_path: &mut kurbo::BezPath,
+    start: &Element,
+    end: &Element,
+    thin_width: f64,
+    options: &CalligraphyOptions,
+) {
+    let (start_width, start_angle) = options.nib_for_element(start);
+    let (end_width, end_angle) = options.nib_for_element(end);
+    let start_half = na::vector![start_angle.cos(), start_angle.sin()] * start_width * 0.5;
+    let end_half = na::vector![end_angle.cos(), end_angle.sin()] * end_width * 0.5;
+
+    extend_w_polygon(
+        bez_path,
+        &[
+            start.pos - start_half,
+            start.pos + start_half,
+            end.pos + end_half,
+            end.pos - end_half,
+        ],
+    );
+
+    let direction = end.pos - start.pos;
+    if direction.magnitude_squared() > 0.0 {
+        let thin_half = direction.orth_unit() * thin_width * 0.5;
+
+        extend_w_polygon(
+            bez_path,
+            &[
+                start.pos - thin_half,
+                start.pos + thin_half,
+                end.pos + thin_half,
+                end.pos - thin_half,
+            ],
+        );
+    }
+}
+
+/// Adds the shape of the nib at the element, which covers the joins between the sweeps.
+fn extend_w_nib_stamp(
+    bez_path: &mut kurbo::BezPath,
+    element: &Element,
+    thin_width: f64,
+    options: &CalligraphyOptions,
+) {
+    let (width, angle) = options.nib_for_element(element);
+    let edge = na::vector![angle.cos(), angle.sin()];
+    let half = edge * width * 0.5;
+    let thin_half = edge.orth_unit() * thin_width * 0.5;
+
+    extend_w_polygon(
+        bez_path,
+        &[
+            element.pos - half - thin_half,
+            element.pos + half - thin_half,
+            element.pos + half + thin_half,
+            element.pos - half + thin_half,
+        ],
+    );
+}
+
+/// Adds the polygon as closed subpath, with the vertices reordered to a positive orientation.
+fn extend_w_polygon(bez_path: &mut kurbo::BezPath, vertices: &[na::Vector2<f64>]) {
+    let signed_area = vertices
+        .iter()
+        .zip(vertices.iter().cycle().skip(1))
+        .map(|(a, b)| a[0] * b[1] - b[0] * a[1])
+        .sum::<f64>();
+    let mut vertices = vertices.to_vec();
+    if signed_area < 0.0 {
+        vertices.reverse();
+    }
+
+    let mut vertices = vertices.into_iter();
+    let Some(first) = vertices.next() else {
+        return;
+    };
+    bez_path.move_to(first.to_kurbo_point());
+    for vertex in vertices {
+        bez_path.line_to(vertex.to_kurbo_point());
+    }
+    bez_path.close_path();
+}
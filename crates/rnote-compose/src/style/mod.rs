@@ -1,8 +1,14 @@
 // Modules
+/// The calligraphy module for calligraphy styles
+pub mod calligraphy;
 /// Composer
 mod composer;
 /// Draw indicators
 pub mod indicators;
+/// The pencil module for pencil styles
+pub mod pencil;
+/// Pressure curves
+mod pressurecurve;
 /// The rough module for rough styles
 pub mod rough;
 /// The smooth module for smooth styles
@@ -11,16 +17,18 @@ pub mod smooth;
 pub mod textured;
 
 // Re-exports
+use self::calligraphy::CalligraphyOptions;
+use self::pencil::PencilOptions;
 use self::rough::RoughOptions;
 use self::smooth::SmoothOptions;
 use self::textured::TexturedOptions;
+pub use pressurecurve::{CustomPressureCurve, PressureCurve, PressureCurvePoint};
 
 // Imports
 use crate::shapes::{
-    Arrow, CubicBezier, Ellipse, Line, Polygon, Polyline, QuadraticBezier, Rectangle,
+    Arrow, CubicBezier, Ellipse, Line, Polygon, Polyline, QuadraticBezier, Rectangle, Shapeable,
 };
 use crate::{Color, PenPath, Shape};
-use anyhow::Context;
 pub use composer::Composer;
 use serde::{Deserialize, Serialize};
 
@@ -37,6 +45,16 @@ pub enum Style {
     /// A textured style
     #[serde(rename = "textured")]
     Textured(TexturedOptions),
+    /// A calligraphy style
+    ///
+    /// Only meant for brush strokes, shapes fall back to their bounds and are not drawn.
+    #[serde(rename = "calligraphy")]
+    Calligraphy(CalligraphyOptions),
+    /// A pencil style
+    ///
+    /// Only meant for brush strokes, shapes fall back to their bounds and are not drawn.
+    #[serde(rename = "pencil")]
+    Pencil(PencilOptions),
 }
 
 impl Default for Style {
@@ -52,6 +70,8 @@ impl Style {
             Style::Smooth(options) => options.stroke_width,
             Style::Rough(options) => options.stroke_width,
             Style::Textured(options) => options.stroke_width,
+            Style::Calligraphy(options) => options.stroke_width,
+            Style::Pencil(options) => options.stroke_width,
         }
     }
 
@@ -61,15 +81,19 @@ impl Style {
             Style::Smooth(options) => options.stroke_width = stroke_width,
             Style::Rough(options) => options.stroke_width = stroke_width,
             Style::Textured(options) => options.stroke_width = stroke_width,
+            Style::Calligraphy(options) => options.stroke_width = stroke_width,
+            Style::Pencil(options) => options.stroke_width = stroke_width,
         }
     }
 
     /// The margins for bounds which contain the shape.
     pub fn bounds_margin(&self) -> f64 {
         match self {
-            Style::Smooth(options) => options.stroke_width,
+            Style::Smooth(options) => options.stroke_width.max(options.arrowheads_bounds_margin()),
             Style::Rough(options) => options.stroke_width + RoughOptions::ROUGH_BOUNDS_MARGIN,
             Style::Textured(options) => options.stroke_width,
+            Style::Calligraphy(options) => options.stroke_width,
+            Style::Pencil(options) => options.stroke_width,
         }
     }
 
@@ -79,6 +103,8 @@ impl Style {
             Style::Smooth(_) => {}
             Style::Rough(options) => options.advance_seed(),
             Style::Textured(options) => options.advance_seed(),
+            Style::Calligraphy(_) => {}
+            Style::Pencil(options) => options.advance_seed(),
         }
     }
 
@@ -88,6 +114,8 @@ impl Style {
             Style::Smooth(options) => options.stroke_color,
             Style::Rough(options) => options.stroke_color,
             Style::Textured(options) => options.stroke_color,
+            Style::Calligraphy(options) => options.stroke_color,
+            Style::Pencil(options) => options.stroke_color,
         }
     }
 
@@ -97,6 +125,8 @@ impl Style {
             Style::Smooth(options) => options.fill_color,
             Style::Rough(options) => options.fill_color,
             Style::Textured(_) => None,
+            Style::Calligraphy(_) => None,
+            Style::Pencil(_) => None,
         }
     }
 
@@ -106,6 +136,8 @@ impl Style {
             Style::Smooth(options) => options.stroke_color = Some(color),
             Style::Rough(options) => options.stroke_color = Some(color),
             Style::Textured(options) => options.stroke_color = Some(color),
+            Style::Calligraphy(options) => options.stroke_color = Some(color),
+            Style::Pencil(options) => options.stroke_color = Some(color),
         };
     }
 
@@ -115,6 +147,8 @@ impl Style {
             Style::Smooth(options) => options.fill_color = Some(color),
             Style::Rough(options) => options.fill_color = Some(color),
             Style::Textured(_) => {}
+            Style::Calligraphy(_) => {}
+            Style::Pencil(_) => {}
         };
     }
 }
@@ -125,6 +159,8 @@ impl Composer<Style> for Line {
             Style::Smooth(options) => self.composed_bounds(options),
             Style::Rough(options) => self.composed_bounds(options),
             Style::Textured(options) => self.composed_bounds(options),
+            Style::Calligraphy(options) => self.composed_bounds(options),
+            Style::Pencil(options) => self.composed_bounds(options),
         }
     }
 
@@ -133,6 +169,8 @@ impl Composer<Style> for Line {
             Style::Smooth(options) => self.draw_composed(cx, options),
             Style::Rough(options) => self.draw_composed(cx, options),
             Style::Textured(options) => self.draw_composed(cx, options),
+            Style::Calligraphy(options) => self.draw_composed(cx, options),
+            Style::Pencil(options) => self.draw_composed(cx, options),
         }
     }
 }
@@ -143,6 +181,8 @@ impl Composer<Style> for Arrow {
             Style::Smooth(options) => self.composed_bounds(options),
             Style::Rough(options) => self.composed_bounds(options),
             Style::Textured(_options) => unimplemented!(),
+            Style::Calligraphy(_options) => self.bounds(),
+            Style::Pencil(_options) => self.bounds(),
         }
     }
 
@@ -151,6 +191,8 @@ impl Composer<Style> for Arrow {
             Style::Smooth(options) => self.draw_composed(cx, options),
             Style::Rough(options) => self.draw_composed(cx, options),
             Style::Textured(_options) => unimplemented!(),
+            Style::Calligraphy(_options) => {}
+            Style::Pencil(_options) => {}
         }
     }
 }
@@ -161,6 +203,8 @@ impl Composer<Style> for Rectangle {
             Style::Smooth(options) => self.composed_bounds(options),
             Style::Rough(options) => self.composed_bounds(options),
             Style::Textured(_options) => unimplemented!(),
+            Style::Calligraphy(_options) => self.bounds(),
+            Style::Pencil(_options) => self.bounds(),
         }
     }
 
@@ -169,6 +213,8 @@ impl Composer<Style> for Rectangle {
             Style::Smooth(options) => self.draw_composed(cx, options),
             Style::Rough(options) => self.draw_composed(cx, options),
             Style::Textured(_options) => unimplemented!(),
+            Style::Calligraphy(_options) => {}
+            Style::Pencil(_options) => {}
         }
     }
 }
@@ -179,6 +225,8 @@ impl Composer<Style> for Ellipse {
             Style::Smooth(options) => self.composed_bounds(options),
             Style::Rough(options) => self.composed_bounds(options),
             Style::Textured(_options) => unimplemented!(),
+            Style::Calligraphy(_options) => self.bounds(),
+            Style::Pencil(_options) => self.bounds(),
         }
     }
 
@@ -187,6 +235,8 @@ impl Composer<Style> for Ellipse {
             Style::Smooth(options) => self.draw_composed(cx, options),
             Style::Rough(options) => self.draw_composed(cx, options),
             Style::Textured(_options) => unimplemented!(),
+            Style::Calligraphy(_options) => {}
+            Style::Pencil(_options) => {}
         }
     }
 }
@@ -197,6 +247,8 @@ impl Composer<Style> for QuadraticBezier {
             Style::Smooth(options) => self.composed_bounds(options),
             Style::Rough(options) => self.composed_bounds(options),
             Style::Textured(_options) => unimplemented!(),
+            Style::Calligraphy(_options) => self.bounds(),
+            Style::Pencil(_options) => self.bounds(),
         }
     }
 
@@ -205,6 +257,8 @@ impl Composer<Style> for QuadraticBezier {
             Style::Smooth(options) => self.draw_composed(cx, options),
             Style::Rough(options) => self.draw_composed(cx, options),
             Style::Textured(_options) => unimplemented!(),
+            Style::Calligraphy(_options) => {}
+            Style::Pencil(_options) => {}
         }
     }
 }
@@ -215,6 +269,8 @@ impl Composer<Style> for CubicBezier {
             Style::Smooth(options) => self.composed_bounds(options),
             Style::Rough(options) => self.composed_bounds(options),
             Style::Textured(_options) => unimplemented!(),
+            Style::Calligraphy(_options) => self.bounds(),
+            Style::Pencil(_options) => self.bounds(),
         }
     }
 
@@ -223,6 +279,8 @@ impl Composer<Style> for CubicBezier {
             Style::Smooth(options) => self.draw_composed(cx, options),
             Style::Rough(options) => self.draw_composed(cx, options),
             Style::Textured(_options) => unimplemented!(),
+            Style::Calligraphy(_options) => {}
+            Style::Pencil(_options) => {}
         }
     }
 }
@@ -233,6 +291,8 @@ impl Composer<Style> for Polyline {
             Style::Smooth(options) => self.composed_bounds(options),
             Style::Rough(options) => self.composed_bounds(options),
             Style::Textured(_options) => unimplemented!(),
+            Style::Calligraphy(_options) => self.bounds(),
+            Style::Pencil(_options) => self.bounds(),
         }
     }
 
@@ -241,6 +301,8 @@ impl Composer<Style> for Polyline {
             Style::Smooth(options) => self.draw_composed(cx, options),
             Style::Rough(options) => self.draw_composed(cx, options),
             Style::Textured(_options) => unimplemented!(),
+            Style::Calligraphy(_options) => {}
+            Style::Pencil(_options) => {}
         }
     }
 }
@@ -251,6 +313,8 @@ impl Composer<Style> for Polygon {
             Style::Smooth(options) => self.composed_bounds(options),
             Style::Rough(options) => self.composed_bounds(options),
             Style::Textured(_options) => unimplemented!(),
+            Style::Calligraphy(_options) => self.bounds(),
+            Style::Pencil(_options) => self.bounds(),
         }
     }
 
@@ -259,6 +323,8 @@ impl Composer<Style> for Polygon {
             Style::Smooth(options) => self.draw_composed(cx, options),
             Style::Rough(options) => self.draw_composed(cx, options),
             Style::Textured(_options) => unimplemented!(),
+            Style::Calligraphy(_options) => {}
+            Style::Pencil(_options) => {}
         }
     }
 }
@@ -269,6 +335,8 @@ impl Composer<Style> for PenPath {
             Style::Smooth(options) => self.composed_bounds(options),
             Style::Rough(_) => unimplemented!(),
             Style::Textured(options) => self.composed_bounds(options),
+            Style::Calligraphy(options) => self.composed_bounds(options),
+            Style::Pencil(options) => self.composed_bounds(options),
         }
     }
 
@@ -277,6 +345,8 @@ impl Composer<Style> for PenPath {
             Style::Smooth(options) => self.draw_composed(cx, options),
             Style::Rough(_) => unimplemented!(),
             Style::Textured(options) => self.draw_composed(cx, options),
+            Style::Calligraphy(options) => self.draw_composed(cx, options),
+            Style::Pencil(options) => self.draw_composed(cx, options),
         }
     }
 }
@@ -308,60 +378,3 @@ impl Composer<Style> for Shape {
         }
     }
 }
-
-/// The pressure curve used by some styles.
-#[derive(
-    Debug, Clone, Copy, Serialize, Deserialize, num_derive::FromPrimitive, num_derive::ToPrimitive,
-)]
-#[serde(rename = "pressure_curve")]
-pub enum PressureCurve {
-    /// Constant.
-    #[serde(rename = "const")]
-    Const = 0,
-    /// Linear.
-    #[serde(rename = "linear")]
-    Linear,
-    /// Square root.
-    #[serde(rename = "sqrt")]
-    Sqrt,
-    /// Cubic root.
-    #[serde(rename = "cbrt")]
-    Cbrt,
-    /// Quadratic polynomial.
-    #[serde(rename = "pow2")]
-    Pow2,
-    /// Cubic polynomial.
-    #[serde(rename = "pow3")]
-    Pow3,
-}
-
-impl Default for PressureCurve {
-    fn default() -> Self {
-        Self::Linear
-    }
-}
-
-impl PressureCurve {
-    /// Apply the pressure curve to a width and the given pressure.
-    ///
-    /// Expects pressure to be between range [0.0 - 1.0].
-    pub fn apply(&self, width: f64, pressure: f64) -> f64 {
-        match self {
-            Self::Const => width,
-            Self::Linear => width * pressure,
-            Self::Sqrt => width * pressure.sqrt(),
-            Self::Cbrt => width * pressure.cbrt(),
-            Self::Pow2 => width * pressure.powi(2),
-            Self::Pow3 => width * pressure.powi(3),
-        }
-    }
-}
-
-impl TryFrom<u32> for PressureCurve {
-    type Error = anyhow::Error;
-
-    fn try_from(value: u32) -> Result<Self, Self::Error> {
-        num_traits::FromPrimitive::from_u32(value)
-            .with_context(|| format!("PressureCurve try_from::<u32>() for value {value} failed"))
-    }
-}
@@ -0,0 +1,218 @@
+// Modules
+mod penciloptions;
+
+// Re-exports
+pub use penciloptions::PencilOptions;
+
+// Imports
+use super::Composer;
+use crate::penpath::{Element, Segment};
+use crate::shapes::{Line, Shapeable};
+use crate::PenPath;
+use p2d::bounding_volume::{Aabb, BoundingVolume};
+use rand::Rng;
+use std::collections::BTreeMap;
+
+impl Composer<PencilOptions> for Line {
+    fn composed_bounds(&self, options: &PencilOptions) -> Aabb {
+        self.bounds()
+            .loosened(options.stroke_width * 0.5 + options.grain_cell_size())
+    }
+
+    fn draw_composed(&self, cx: &mut impl piet::RenderContext, options: &PencilOptions) {
+        let mut options = options.clone();
+        options.pressure_curve = crate::style::PressureCurve::Const;
+
+        PenPath::new_w_segments(
+            Element::new(self.start, Element::PRESSURE_DEFAULT),
+            [Segment::LineTo {
+                end: Element::new(self.end, Element::PRESSURE_DEFAULT),
+            }],
+        )
+        .draw_composed(cx, &options);
+    }
+}
+
+impl Composer<PencilOptions> for PenPath {
+    fn composed_bounds(&self, options: &PencilOptions) -> Aabb {
+        self.bounds()
+            .loosened(options.stroke_width * 0.5 + options.grain_cell_size())
+    }
+
+    /// The stroke is drawn as cells on a grid that is aligned to the document, like the grain of a paper.
+    /// Where the path overlaps itself, a cell keeps its highest opacity, so opacity only builds up with repeated strokes.
+    fn draw_composed(&self, cx: &mut impl piet::RenderContext, options: &PencilOptions) {
+        let Some(color) = options.stroke_color else {
+            return;
+        };
+        let grain = Grain::new(options);
+        let mut cells = BTreeMap::<(i64, i64), f64>::new();
+        let mut prev = self.start;
+
+        add_cells_for_stamp(&mut cells, &self.start, &grain, options);
+
+        for seg in self.segments.iter() {
+            let elements = seg.subdivided_elements(&prev);
+
+            for (start, end) in elements.iter().zip(elements.iter().skip(1)) {
+                add_cells_for_sweep(&mut cells, start, end, &grain, options);
+            }
+
+            prev = seg.end();
+        }
+
+        // Cells with the same opacity are drawn in a single path, to keep the number of draw calls low.
+        let levels = PencilOptions::OPACITY_LEVELS;
+        let mut level_paths = vec![kurbo::BezPath::new(); levels as usize];
+        for ((i, j), alpha) in cells {
+            let level = (alpha.clamp(0.0, 1.0) * levels as f64).round() as u32;
+            if level == 0 {
+                continue;
+            }
+            let rect = kurbo::Rect::new(
+                i as f64 * grain.cell_size,
+                j as f64 * grain.cell_size,
+                (i + 1) as f64 * grain.cell_size,
+                (j + 1) as f64 * grain.cell_size,
+            );
+            level_paths[level as usize - 1].extend(kurbo::Shape::path_elements(&rect, 0.1));
+        }
+
+        cx.save().unwrap();
+        for (i, bez_path) in level_paths.into_iter().enumerate() {
+            if bez_path.elements().is_empty() {
+                continue;
+            }
+            let mut level_color = color;
+            level_color.a *= (i + 1) as f64 / levels as f64;
+
+            cx.fill(bez_path, &Into::<piet::Color>::into(level_color));
+        }
+        cx.restore().unwrap();
+    }
+}
+
+/// The deterministic grain of the paper.
+struct Grain {
+    key: u64,
+    cell_size: f64,
+}
+
+impl Grain {
+    fn new(options: &PencilOptions) -> Self {
+        Self {
+            key: crate::utils::new_rng_default_pcg64(options.seed).gen(),
+            cell_size: options.grain_cell_size(),
+        }
+    }
+
+    /// The height of the paper at the cell, between range [0.0 - 1.0]. A mix of a coarse and a fine structure.
+    fn height(&self, i: i64, j: i64) -> f64 {
+        let scale = PencilOptions::GRAIN_COARSE_SCALE;
+        let (x, y) = (i as f64 / scale, j as f64 / scale);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (smoothstep(x - x0), smoothstep(y - y0));
+        let (x0, y0) = (x0 as i64, y0 as i64);
+        let coarse_key = self.key.rotate_left(32);
+
+        let top = lerp(
+            hash_unit(coarse_key, x0, y0),
+            hash_unit(coarse_key, x0 + 1, y0),
+            tx,
+        );
+        let bottom = lerp(
+            hash_unit(coarse_key, x0, y0 + 1),
+            hash_unit(coarse_key, x0 + 1, y0 + 1),
+            tx,
+        );
+        let coarse = lerp(top, bottom, ty);
+
+        0.5 * coarse + 0.5 * hash_unit(self.key, i, j)
+    }
+}
+
+/// Adds the cells covered by a circle at the element.
+fn add_cells_for_stamp(
+    cells: &mut BTreeMap<(i64, i64), f64>,
+    element: &Element,
+    grain: &Grain,
+    options: &PencilOptions,
+) {
+    add_cells_for_sweep(cells, element, element, grain, options);
+}
+
+/// Adds the cells covered by the area swept by a circle from `start` to `end`, with the radius following the pressure.
+fn add_cells_for_sweep(
+    cells: &mut BTreeMap<(i64, i64), f64>,
+    start: &Element,
+    end: &Element,
+    grain: &Grain,
+    options: &PencilOptions,
+) {
+    let start_radius = options
+        .pressure_curve
+        .apply(options.stroke_width, start.pressure)
+        * 0.5;
+    let end_radius = options
+        .pressure_curve
+        .apply(options.stroke_width, end.pressure)
+        * 0.5;
+    let max_radius = start_radius.max(end_radius) + grain.cell_size;
+    let mins = start.pos.inf(&end.pos).add_scalar(-max_radius) / grain.cell_size;
+    let maxs = start.pos.sup(&end.pos).add_scalar(max_radius) / grain.cell_size;
+    let direction = end.pos - start.pos;
+    let len_squared = direction.magnitude_squared();
+
+    for i in (mins[0].floor() as i64)..=(maxs[0].ceil() as i64) {
+        for j in (mins[1].floor() as i64)..=(maxs[1].ceil() as i64) {
+            let center = na::vector![i as f64 + 0.5, j as f64 + 0.5] * grain.cell_size;
+            let t = if len_squared > 0.0 {
+                ((center - start.pos).dot(&direction) / len_squared).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let radius = lerp(start_radius, end_radius, t);
+            let dist = (center - (start.pos + direction * t)).magnitude();
+            // soft edges, over the width of a cell
+            let coverage = ((radius - dist) / grain.cell_size + 0.5).clamp(0.0, 1.0);
+            if coverage <= 0.0 {
+                continue;
+            }
+
+            let pressure = lerp(start.pressure, end.pressure, t);
+            // more pressure pushes the graphite deeper into the valleys of the paper.
+            let grain_strength = options
+                .grain
+                .clamp(PencilOptions::GRAIN_MIN, PencilOptions::GRAIN_MAX);
+            let grain_factor = (1.0 - grain_strength)
+                + grain_strength * grain.height(i, j).powf(1.0 - 0.5 * pressure);
+            let opacity = options
+                .opacity
+                .clamp(PencilOptions::OPACITY_MIN, PencilOptions::OPACITY_MAX);
+            let alpha = opacity * pressure.sqrt() * grain_factor * coverage;
+
+            let cell = cells.entry((i, j)).or_insert(0.0);
+            *cell = cell.max(alpha);
+        }
+    }
+}
+
+fn lerp(a: f64, b: f64, t: f64) -> f64 {
+    a + (b - a) * t
+}
+
+fn smoothstep(t: f64) -> f64 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// A deterministic hash of the key and cell, mapped to the range [0.0 - 1.0).
+fn hash_unit(key: u64, i: i64, j: i64) -> f64 {
+    // splitmix64 finalizer
+    let mut z = key
+        ^ (i as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15)
+        ^ (j as u64).wrapping_mul(0xc2b2_ae3d_27d4_eb4f);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^= z >> 31;
+    (z >> 11) as f64 / (1_u64 << 53) as f64
+}
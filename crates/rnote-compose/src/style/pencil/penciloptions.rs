@@ -0,0 +1,74 @@
+// Imports
+use crate::style::PressureCurve;
+use crate::Color;
+use serde::{Deserialize, Serialize};
+
+/// Options for pen paths that can be drawn in a pencil style, with graphite grain on paper.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename = "pencil_options")]
+pub struct PencilOptions {
+    /// An optional seed to generate a reproducible grain.
+    #[serde(rename = "seed")]
+    pub seed: Option<u64>,
+    /// Stroke width.
+    #[serde(rename = "stroke_width", with = "crate::serialize::f64_dp3")]
+    pub stroke_width: f64,
+    /// Stroke color. When set to None, the stroke is not drawn.
+    #[serde(rename = "stroke_color")]
+    pub stroke_color: Option<Color>,
+    /// The opacity at full pressure. The valid range is [0.0, 1.0].
+    #[serde(rename = "opacity", with = "crate::serialize::f64_dp3")]
+    pub opacity: f64,
+    /// How strongly the grain of the paper shows. The valid range is [0.0, 1.0].
+    #[serde(rename = "grain", with = "crate::serialize::f64_dp3")]
+    pub grain: f64,
+    /// Pressure curve for the stroke width.
+    #[serde(rename = "pressure_curve")]
+    pub pressure_curve: PressureCurve,
+}
+
+impl Default for PencilOptions {
+    fn default() -> Self {
+        Self {
+            seed: None,
+            stroke_width: 2.0,
+            stroke_color: Some(Color::BLACK),
+            opacity: Self::OPACITY_DEFAULT,
+            grain: Self::GRAIN_DEFAULT,
+            pressure_curve: PressureCurve::Const,
+        }
+    }
+}
+
+impl PencilOptions {
+    /// The default opacity.
+    pub const OPACITY_DEFAULT: f64 = 0.8;
+    /// Minimum opacity.
+    pub const OPACITY_MIN: f64 = 0.05;
+    /// Maximum opacity.
+    pub const OPACITY_MAX: f64 = 1.0;
+    /// The default grain.
+    pub const GRAIN_DEFAULT: f64 = 0.6;
+    /// Minimum grain.
+    pub const GRAIN_MIN: f64 = 0.0;
+    /// Maximum grain.
+    pub const GRAIN_MAX: f64 = 1.0;
+    /// The size of the grain cells, relative to the stroke width.
+    pub(super) const GRAIN_CELL_SIZE_RATIO: f64 = 0.125;
+    /// The minimum size of the grain cells.
+    pub(super) const GRAIN_CELL_SIZE_MIN: f64 = 0.5;
+    /// The amount of grain cells that make up the coarse structure of the paper.
+    pub(super) const GRAIN_COARSE_SCALE: f64 = 4.0;
+    /// The number of distinct opacity levels the grain cells are drawn with.
+    pub(super) const OPACITY_LEVELS: u32 = 32;
+
+    /// Advances the seed.
+    pub fn advance_seed(&mut self) {
+        self.seed = self.seed.map(crate::utils::seed_advance)
+    }
+
+    /// The size of the grain cells.
+    pub(super) fn grain_cell_size(&self) -> f64 {
+        (self.stroke_width * Self::GRAIN_CELL_SIZE_RATIO).max(Self::GRAIN_CELL_SIZE_MIN)
+    }
+}
@@ -0,0 +1,330 @@
+// Imports
+use anyhow::Context;
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// The pressure curve used by some styles.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename = "pressure_curve")]
+pub enum PressureCurve {
+    /// Constant.
+    #[serde(rename = "const")]
+    Const,
+    /// Linear.
+    #[serde(rename = "linear")]
+    Linear,
+    /// Square root.
+    #[serde(rename = "sqrt")]
+    Sqrt,
+    /// Cubic root.
+    #[serde(rename = "cbrt")]
+    Cbrt,
+    /// Quadratic polynomial.
+    #[serde(rename = "pow2")]
+    Pow2,
+    /// Cubic polynomial.
+    #[serde(rename = "pow3")]
+    Pow3,
+    /// Defined by control points.
+    #[serde(rename = "custom")]
+    Custom(CustomPressureCurve),
+}
+
+impl Default for PressureCurve {
+    fn default() -> Self {
+        Self::Linear
+    }
+}
+
+impl PressureCurve {
+    /// Apply the pressure curve to a width and the given pressure.
+    ///
+    /// Expects pressure to be between range [0.0 - 1.0].
+    pub fn apply(&self, width: f64, pressure: f64) -> f64 {
+        match self {
+            Self::Const => width,
+            Self::Linear => width * pressure,
+            Self::Sqrt => width * pressure.sqrt(),
+            Self::Cbrt => width * pressure.cbrt(),
+            Self::Pow2 => width * pressure.powi(2),
+            Self::Pow3 => width * pressure.powi(3),
+            Self::Custom(custom) => width * custom.factor(pressure),
+        }
+    }
+
+    /// The index of the pressure curve variant.
+    pub fn to_u32(&self) -> u32 {
+        match self {
+            Self::Const => 0,
+            Self::Linear => 1,
+            Self::Sqrt => 2,
+            Self::Cbrt => 3,
+            Self::Pow2 => 4,
+            Self::Pow3 => 5,
+            Self::Custom(_) => 6,
+        }
+    }
+}
+
+impl TryFrom<u32> for PressureCurve {
+    type Error = anyhow::Error;
+
+    /// Custom pressure curves are created with their default control points.
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Some(Self::Const),
+            1 => Some(Self::Linear),
+            2 => Some(Self::Sqrt),
+            3 => Some(Self::Cbrt),
+            4 => Some(Self::Pow2),
+            5 => Some(Self::Pow3),
+            6 => Some(Self::Custom(CustomPressureCurve::default())),
+            _ => None,
+        }
+        .with_context(|| format!("PressureCurve try_from::<u32>() for value {value} failed"))
+    }
+}
+
+/// A control point of a custom pressure curve.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename = "pressure_curve_point")]
+pub struct PressureCurvePoint {
+    /// The pressure, between range [0.0 - 1.0].
+    #[serde(rename = "pressure", with = "crate::serialize::f64_dp3")]
+    pub pressure: f64,
+    /// The factor the width is multiplied with at the pressure, between range [0.0 - 1.0].
+    #[serde(rename = "factor", with = "crate::serialize::f64_dp3")]
+    pub factor: f64,
+}
+
+impl Default for PressureCurvePoint {
+    fn default() -> Self {
+        Self {
+            pressure: 0.0,
+            factor: 0.0,
+        }
+    }
+}
+
+impl PressureCurvePoint {
+    /// A new control point.
+    pub fn new(pressure: f64, factor: f64) -> Self {
+        Self { pressure, factor }
+    }
+}
+
+/// A pressure curve defined by control points, interpolated with a monotone cubic spline.
+///
+/// The control points are kept sorted by their pressure and with non-decreasing factors,
+/// so that a higher pressure never results in a thinner stroke.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename = "custom_pressure_curve")]
+pub struct CustomPressureCurve {
+    #[serde(rename = "points", deserialize_with = "deserialize_points")]
+    points: Vec<PressureCurvePoint>,
+    /// The minimum factor the width is multiplied with, between range [0.0 - 1.0].
+    #[serde(rename = "min_width", with = "crate::serialize::f64_dp3")]
+    pub min_width: f64,
+    /// The maximum factor the width is multiplied with, between range [0.0 - 1.0].
+    #[serde(rename = "max_width", with = "crate::serialize::f64_dp3")]
+    pub max_width: f64,
+}
+
+impl Default for CustomPressureCurve {
+    fn default() -> Self {
+        Self {
+            points: vec![
+                PressureCurvePoint::new(0.0, 0.0),
+                PressureCurvePoint::new(1.0, 1.0),
+            ],
+            min_width: 0.0,
+            max_width: 1.0,
+        }
+    }
+}
+
+impl CustomPressureCurve {
+    /// A new custom pressure curve from the control points.
+    ///
+    /// The points are clamped, sorted and made monotone. Points with the same pressure are merged.
+    pub fn new(points: Vec<PressureCurvePoint>, min_width: f64, max_width: f64) -> Self {
+        Self {
+            points: sanitize_points(points),
+            min_width,
+            max_width,
+        }
+    }
+
+    /// The control points.
+    pub fn points(&self) -> &[PressureCurvePoint] {
+        &self.points
+    }
+
+    /// Replace the control points.
+    ///
+    /// The points are clamped, sorted and made monotone. Points with the same pressure are merged.
+    pub fn set_points(&mut self, points: Vec<PressureCurvePoint>) {
+        self.points = sanitize_points(points);
+    }
+
+    /// The factor the width is multiplied with at the given pressure, clamped to the min and max width.
+    pub fn factor(&self, pressure: f64) -> f64 {
+        let min_width = self.min_width.clamp(0.0, 1.0);
+        let max_width = self.max_width.clamp(min_width, 1.0);
+
+        self.interpolate(pressure.clamp(0.0, 1.0))
+            .clamp(min_width, max_width)
+    }
+
+    /// Monotone cubic hermite interpolation of the control points, with the tangents of Fritsch and Butland.
+    fn interpolate(&self, pressure: f64) -> f64 {
+        let points = &self.points;
+        let (Some(first), Some(last)) = (points.first(), points.last()) else {
+            return pressure;
+        };
+        if pressure <= first.pressure {
+            return first.factor;
+        }
+        if pressure >= last.pressure {
+            return last.factor;
+        }
+
+        // the index of the segment that contains the pressure.
+        // Always valid, because the pressure is inside the range of the points.
+        let i = points.partition_point(|p| p.pressure <= pressure) - 1;
+        let secant = |k: usize| {
+            (points[k + 1].factor - points[k].factor)
+                / (points[k + 1].pressure - points[k].pressure)
+        };
+        let tangent = |k: usize| {
+            if k == 0 {
+                return secant(0);
+            }
+            if k == points.len() - 1 {
+                return secant(k - 1);
+            }
+            let (d0, d1) = (secant(k - 1), secant(k));
+            if d0 <= 0.0 || d1 <= 0.0 {
+                return 0.0;
+            }
+            let h0 = points[k].pressure - points[k - 1].pressure;
+            let h1 = points[k + 1].pressure - points[k].pressure;
+            let (w0, w1) = (2.0 * h1 + h0, h1 + 2.0 * h0);
+            (w0 + w1) / (w0 / d0 + w1 / d1)
+        };
+
+        let (p0, p1) = (points[i], points[i + 1]);
+        let h = p1.pressure - p0.pressure;
+        let t = (pressure - p0.pressure) / h;
+        let (t2, t3) = (t * t, t * t * t);
+
+        (2.0 * t3 - 3.0 * t2 + 1.0) * p0.factor
+            + (t3 - 2.0 * t2 + t) * h * tangent(i)
+            + (-2.0 * t3 + 3.0 * t2) * p1.factor
+            + (t3 - t2) * h * tangent(i + 1)
+    }
+}
+
+fn sanitize_points(mut points: Vec<PressureCurvePoint>) -> Vec<PressureCurvePoint> {
+    points.retain(|p| p.pressure.is_finite() && p.factor.is_finite());
+    points.iter_mut().for_each(|p| {
+        p.pressure = p.pressure.clamp(0.0, 1.0);
+        p.factor = p.factor.clamp(0.0, 1.0);
+    });
+    points.sort_by(|a, b| a.pressure.total_cmp(&b.pressure));
+    // keep the last of the points with the same pressure
+    points.reverse();
+    points.dedup_by(|a, b| a.pressure == b.pressure);
+    points.reverse();
+
+    let mut max_factor = 0.0_f64;
+    points.iter_mut().for_each(|p| {
+        max_factor = max_factor.max(p.factor);
+        p.factor = max_factor;
+    });
+
+    if points.is_empty() {
+        return CustomPressureCurve::default().points;
+    }
+    points
+}
+
+fn deserialize_points<'de, D: Deserializer<'de>>(
+    d: D,
+) -> Result<Vec<PressureCurvePoint>, D::Error> {
+    Vec::<PressureCurvePoint>::deserialize(d).map(sanitize_points)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(points: &[(f64, f64)]) -> Vec<PressureCurvePoint> {
+        points
+            .iter()
+            .map(|&(pressure, factor)| PressureCurvePoint::new(pressure, factor))
+            .collect()
+    }
+
+    #[test]
+    fn sanitize_clamps_sorts_and_merges() {
+        let sanitized = sanitize_points(points(&[
+            (1.5, 0.8),
+            (0.5, 0.2),
+            (-0.3, -1.0),
+            (0.5, 0.4),
+            (f64::NAN, 0.5),
+        ]));
+        assert_eq!(sanitized, points(&[(0.0, 0.0), (0.5, 0.4), (1.0, 0.8)]));
+    }
+
+    #[test]
+    fn sanitize_makes_factors_monotone() {
+        let sanitized = sanitize_points(points(&[(0.0, 0.5), (0.5, 0.2), (1.0, 0.9)]));
+        assert_eq!(sanitized, points(&[(0.0, 0.5), (0.5, 0.5), (1.0, 0.9)]));
+    }
+
+    #[test]
+    fn sanitize_empty_gives_default() {
+        assert_eq!(
+            sanitize_points(vec![]),
+            CustomPressureCurve::default().points
+        );
+    }
+
+    #[test]
+    fn interpolate_passes_through_points() {
+        let curve = CustomPressureCurve::new(
+            points(&[(0.2, 0.1), (0.5, 0.6), (0.8, 0.7), (1.0, 1.0)]),
+            0.0,
+            1.0,
+        );
+        for point in curve.points() {
+            assert!((curve.interpolate(point.pressure) - point.factor).abs() < 1e-9);
+        }
+        // Constant outside of the range of the points
+        assert_eq!(curve.interpolate(0.0), 0.1);
+    }
+
+    #[test]
+    fn interpolate_is_monotone() {
+        let curve = CustomPressureCurve::new(
+            points(&[(0.0, 0.0), (0.3, 0.7), (0.4, 0.7), (0.6, 0.75), (1.0, 1.0)]),
+            0.0,
+            1.0,
+        );
+        let factors = (0..=100)
+            .map(|i| curve.interpolate(i as f64 / 100.0))
+            .collect::<Vec<f64>>();
+        assert!(factors.windows(2).all(|w| w[1] >= w[0] - 1e-12));
+        // Flat between points with equal factors
+        assert!((curve.interpolate(0.35) - 0.7).abs() < 1e-9);
+    }
+
+    #[test]
+    fn factor_is_clamped_to_widths() {
+        let curve = CustomPressureCurve::new(points(&[(0.0, 0.0), (1.0, 1.0)]), 0.2, 0.6);
+        assert_eq!(curve.factor(0.0), 0.2);
+        assert_eq!(curve.factor(1.0), 0.6);
+        assert!((curve.factor(0.4) - 0.4).abs() < 1e-9);
+    }
+}
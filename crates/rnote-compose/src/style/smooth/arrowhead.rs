@@ -0,0 +1,153 @@
+// Imports
+use crate::ext::Vector2Ext;
+use crate::shapes::Arrow;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+/// The arrowhead at an end of an open shape.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    num_derive::FromPrimitive,
+    num_derive::ToPrimitive,
+)]
+#[serde(rename = "arrowhead")]
+pub enum Arrowhead {
+    /// No arrowhead.
+    #[serde(rename = "none")]
+    None = 0,
+    /// Two lines, like the tip of an [Arrow].
+    #[serde(rename = "open")]
+    Open,
+    /// A filled triangle.
+    #[serde(rename = "filled")]
+    Filled,
+    /// A filled circle.
+    #[serde(rename = "circle")]
+    Circle,
+    /// A filled diamond.
+    #[serde(rename = "diamond")]
+    Diamond,
+    /// A bar perpendicular to the end.
+    #[serde(rename = "bar")]
+    Bar,
+}
+
+impl Default for Arrowhead {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+impl TryFrom<u32> for Arrowhead {
+    type Error = anyhow::Error;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        num_traits::FromPrimitive::from_u32(value)
+            .with_context(|| format!("Arrowhead try_from::<u32>() for value {value} failed"))
+    }
+}
+
+impl Arrowhead {
+    /// The radius of the circle, relative to the length of the arrowhead.
+    const CIRCLE_RADIUS_RATIO: f64 = 0.3;
+    /// The width of the diamond, relative to the length of the arrowhead.
+    const DIAMOND_WIDTH_RATIO: f64 = 0.5;
+    /// The length of the bar, relative to the length of the arrowhead.
+    const BAR_LENGTH_RATIO: f64 = 1.0;
+
+    /// The length of the arrowhead for the stroke width, the same as the tip of an [Arrow].
+    pub fn length(stroke_width: f64) -> f64 {
+        Arrow::compute_tip_lines_length(Some(stroke_width))
+    }
+
+    /// The margin the arrowhead can extend beyond the end of the shape.
+    pub fn bounds_margin(&self, stroke_width: f64) -> f64 {
+        match self {
+            Self::None => 0.0,
+            // includes the miter of the joins
+            _ => Self::length(stroke_width) + stroke_width,
+        }
+    }
+
+    /// Draws the arrowhead at the end of a shape.
+    ///
+    /// `direction` points away from the shape, in the direction the arrowhead points to.
+    pub fn draw(
+        &self,
+        cx: &mut impl piet::RenderContext,
+        end: na::Vector2<f64>,
+        direction: na::Vector2<f64>,
+        stroke_width: f64,
+        color: piet::Color,
+    ) {
+        let direction = if direction.magnitude() > 0.0 {
+            direction.normalize()
+        } else {
+            Arrow::DEFAULT_DIRECTION_VECTOR
+        };
+        let orth = direction.orth_unit();
+        let length = Self::length(stroke_width);
+        let side = |angle: f64| na::Rotation2::new(angle) * direction * length + end;
+
+        match self {
+            Self::None => {}
+            Self::Open => {
+                let mut bez_path = kurbo::BezPath::new();
+                bez_path.move_to(side(Arrow::TIP_LINES_STEM_OBTUSE_ANGLE).to_kurbo_point());
+                bez_path.line_to(end.to_kurbo_point());
+                bez_path.line_to(side(-Arrow::TIP_LINES_STEM_OBTUSE_ANGLE).to_kurbo_point());
+
+                cx.stroke(bez_path, &color, stroke_width);
+            }
+            Self::Filled => {
+                let mut bez_path = kurbo::BezPath::new();
+                bez_path.move_to(side(Arrow::TIP_LINES_STEM_OBTUSE_ANGLE).to_kurbo_point());
+                bez_path.line_to(end.to_kurbo_point());
+                bez_path.line_to(side(-Arrow::TIP_LINES_STEM_OBTUSE_ANGLE).to_kurbo_point());
+                bez_path.close_path();
+
+                cx.fill(&bez_path, &color);
+                cx.stroke(bez_path, &color, stroke_width);
+            }
+            Self::Circle => {
+                cx.fill(
+                    kurbo::Circle::new(
+                        end.to_kurbo_point(),
+                        length * Self::CIRCLE_RADIUS_RATIO + stroke_width * 0.5,
+                    ),
+                    &color,
+                );
+            }
+            Self::Diamond => {
+                let half_width = orth * length * Self::DIAMOND_WIDTH_RATIO * 0.5;
+                let mut bez_path = kurbo::BezPath::new();
+                bez_path.move_to(end.to_kurbo_point());
+                bez_path.line_to((end - direction * length * 0.5 + half_width).to_kurbo_point());
+                bez_path.line_to((end - direction * length).to_kurbo_point());
+                bez_path.line_to((end - direction * length * 0.5 - half_width).to_kurbo_point());
+                bez_path.close_path();
+
+                cx.fill(&bez_path, &color);
+                cx.stroke(bez_path, &color, stroke_width);
+            }
+            Self::Bar => {
+                let half_bar = orth * length * Self::BAR_LENGTH_RATIO * 0.5;
+
+                cx.stroke(
+                    kurbo::Line::new(
+                        (end + half_bar).to_kurbo_point(),
+                        (end - half_bar).to_kurbo_point(),
+                    ),
+                    &color,
+                    stroke_width,
+                );
+            }
+        }
+    }
+}
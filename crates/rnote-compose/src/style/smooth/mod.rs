@@ -1,23 +1,26 @@
 // Modules
+mod arrowhead;
 mod smoothoptions;
 
 // Re-exports
+pub use arrowhead::Arrowhead;
 pub use smoothoptions::SmoothOptions;
 
 // Imports
 use super::Composer;
 use crate::ext::Vector2Ext;
-use crate::penpath::{self, Segment};
+use crate::penpath::{self, Element, Segment};
 use crate::shapes::{
     Arrow, CubicBezier, Ellipse, Line, Polygon, Polyline, QuadraticBezier, Rectangle, Shapeable,
 };
-use crate::PenPath;
+use crate::{Color, PenPath};
 use kurbo::Shape;
 use p2d::bounding_volume::{Aabb, BoundingVolume};
 
 impl Composer<SmoothOptions> for Line {
     fn composed_bounds(&self, options: &SmoothOptions) -> Aabb {
-        self.bounds().loosened(options.stroke_width * 0.5)
+        self.bounds()
+            .loosened((options.stroke_width * 0.5).max(options.arrowheads_bounds_margin()))
     }
 
     fn draw_composed(&self, cx: &mut impl piet::RenderContext, options: &SmoothOptions) {
@@ -26,7 +29,19 @@ impl Composer<SmoothOptions> for Line {
 
         if let Some(stroke_color) = options.stroke_color {
             let stroke_brush = cx.solid_brush(stroke_color.into());
-            cx.stroke(line, &stroke_brush, options.stroke_width);
+            cx.stroke_styled(
+                line,
+                &stroke_brush,
+                options.stroke_width,
+                &options.dashed_stroke_style(piet::StrokeStyle::new()),
+            );
+            draw_arrowheads(
+                cx,
+                (self.start, self.start - self.end),
+                (self.end, self.end - self.start),
+                stroke_color,
+                options,
+            );
         }
         cx.restore().unwrap();
     }
@@ -35,19 +50,53 @@ impl Composer<SmoothOptions> for Line {
 impl Composer<SmoothOptions> for Arrow {
     fn composed_bounds(&self, options: &SmoothOptions) -> Aabb {
         self.internal_compute_bounds(Some(options.stroke_width))
-            .loosened(options.stroke_width)
+            .loosened(options.stroke_width.max(options.arrowheads_bounds_margin()))
     }
 
     fn draw_composed(&self, cx: &mut impl piet::RenderContext, options: &SmoothOptions) {
         cx.save().unwrap();
 
         if let Some(stroke_color) = options.stroke_color {
-            let arrow = self.to_kurbo(Some(options.stroke_width));
-            cx.stroke(
-                arrow,
-                &Into::<piet::Color>::into(stroke_color),
-                options.stroke_width,
-            );
+            // The tip of the arrow is its end arrowhead, which can be replaced by another one
+            let end_arrowhead = match options.end_arrowhead {
+                Arrowhead::None => Arrowhead::Open,
+                arrowhead => arrowhead,
+            };
+
+            if !options.is_dashed()
+                && options.start_arrowhead == Arrowhead::None
+                && end_arrowhead == Arrowhead::Open
+            {
+                let arrow = self.to_kurbo(Some(options.stroke_width));
+                cx.stroke(
+                    arrow,
+                    &Into::<piet::Color>::into(stroke_color),
+                    options.stroke_width,
+                );
+            } else {
+                let stem = kurbo::Line::new(self.start.to_kurbo_point(), self.tip.to_kurbo_point());
+
+                cx.stroke_styled(
+                    stem,
+                    &Into::<piet::Color>::into(stroke_color),
+                    options.stroke_width,
+                    &options.dashed_stroke_style(piet::StrokeStyle::new()),
+                );
+                options.start_arrowhead.draw(
+                    cx,
+                    self.start,
+                    self.start - self.tip,
+                    options.stroke_width,
+                    stroke_color.into(),
+                );
+                end_arrowhead.draw(
+                    cx,
+                    self.tip,
+                    self.tip - self.start,
+                    options.stroke_width,
+                    stroke_color.into(),
+                );
+            }
         }
 
         cx.restore().unwrap();
@@ -70,7 +119,12 @@ impl Composer<SmoothOptions> for Rectangle {
 
         if let Some(stroke_color) = options.stroke_color {
             let stroke_brush = cx.solid_brush(stroke_color.into());
-            cx.stroke(shape, &stroke_brush, options.stroke_width);
+            cx.stroke_styled(
+                shape,
+                &stroke_brush,
+                options.stroke_width,
+                &options.dashed_stroke_style(piet::StrokeStyle::new()),
+            );
         }
         cx.restore().unwrap();
     }
@@ -92,7 +146,12 @@ impl Composer<SmoothOptions> for Ellipse {
 
         if let Some(stroke_color) = options.stroke_color {
             let stroke_brush = cx.solid_brush(stroke_color.into());
-            cx.stroke(ellipse, &stroke_brush, options.stroke_width);
+            cx.stroke_styled(
+                ellipse,
+                &stroke_brush,
+                options.stroke_width,
+                &options.dashed_stroke_style(piet::StrokeStyle::new()),
+            );
         }
         cx.restore().unwrap();
     }
@@ -100,7 +159,8 @@ impl Composer<SmoothOptions> for Ellipse {
 
 impl Composer<SmoothOptions> for QuadraticBezier {
     fn composed_bounds(&self, options: &SmoothOptions) -> Aabb {
-        self.bounds().loosened(options.stroke_width * 0.5)
+        self.bounds()
+            .loosened((options.stroke_width * 0.5).max(options.arrowheads_bounds_margin()))
     }
 
     fn draw_composed(&self, cx: &mut impl piet::RenderContext, options: &SmoothOptions) {
@@ -114,7 +174,25 @@ impl Composer<SmoothOptions> for QuadraticBezier {
 
         if let Some(stroke_color) = options.stroke_color {
             let stroke_brush = cx.solid_brush(stroke_color.into());
-            cx.stroke(quadbez, &stroke_brush, options.stroke_width);
+            cx.stroke_styled(
+                quadbez,
+                &stroke_brush,
+                options.stroke_width,
+                &options.dashed_stroke_style(piet::StrokeStyle::new()),
+            );
+            draw_arrowheads(
+                cx,
+                (
+                    self.start,
+                    direction_away_from(self.start, [self.cp, self.end]),
+                ),
+                (
+                    self.end,
+                    direction_away_from(self.end, [self.cp, self.start]),
+                ),
+                stroke_color,
+                options,
+            );
         }
         cx.restore().unwrap();
     }
@@ -122,7 +200,8 @@ impl Composer<SmoothOptions> for QuadraticBezier {
 
 impl Composer<SmoothOptions> for CubicBezier {
     fn composed_bounds(&self, options: &SmoothOptions) -> Aabb {
-        self.bounds().loosened(options.stroke_width * 0.5)
+        self.bounds()
+            .loosened((options.stroke_width * 0.5).max(options.arrowheads_bounds_margin()))
     }
 
     fn draw_composed(&self, cx: &mut impl piet::RenderContext, options: &SmoothOptions) {
@@ -136,7 +215,25 @@ impl Composer<SmoothOptions> for CubicBezier {
 
         if let Some(stroke_color) = options.stroke_color {
             let stroke_brush = cx.solid_brush(stroke_color.into());
-            cx.stroke(cubbez, &stroke_brush, options.stroke_width);
+            cx.stroke_styled(
+                cubbez,
+                &stroke_brush,
+                options.stroke_width,
+                &options.dashed_stroke_style(piet::StrokeStyle::new()),
+            );
+            draw_arrowheads(
+                cx,
+                (
+                    self.start,
+                    direction_away_from(self.start, [self.cp1, self.cp2, self.end]),
+                ),
+                (
+                    self.end,
+                    direction_away_from(self.end, [self.cp2, self.cp1, self.start]),
+                ),
+                stroke_color,
+                options,
+            );
         }
         cx.restore().unwrap();
     }
@@ -144,7 +241,8 @@ impl Composer<SmoothOptions> for CubicBezier {
 
 impl Composer<SmoothOptions> for Polyline {
     fn composed_bounds(&self, options: &SmoothOptions) -> Aabb {
-        self.bounds().loosened(options.stroke_width * 0.5)
+        self.bounds()
+            .loosened((options.stroke_width * 0.5).max(options.arrowheads_bounds_margin()))
     }
 
     fn draw_composed(&self, cx: &mut impl piet::RenderContext, options: &SmoothOptions) {
@@ -165,9 +263,33 @@ impl Composer<SmoothOptions> for Polyline {
                 self.outline_path(),
                 &Into::<piet::Color>::into(color),
                 options.stroke_width,
-                &piet::StrokeStyle::default()
-                    .line_cap(piet::LineCap::Butt)
-                    .line_join(piet::LineJoin::Bevel),
+                &options.dashed_stroke_style(
+                    piet::StrokeStyle::default()
+                        .line_cap(piet::LineCap::Butt)
+                        .line_join(piet::LineJoin::Bevel),
+                ),
+            );
+
+            let end = self.path.last().copied().unwrap_or(self.start);
+            draw_arrowheads(
+                cx,
+                (
+                    self.start,
+                    direction_away_from(self.start, self.path.iter().copied()),
+                ),
+                (
+                    end,
+                    direction_away_from(
+                        end,
+                        self.path
+                            .iter()
+                            .rev()
+                            .copied()
+                            .chain(std::iter::once(self.start)),
+                    ),
+                ),
+                color,
+                options,
             );
         }
     }
@@ -201,9 +323,11 @@ impl Composer<SmoothOptions> for Polygon {
                 &outline_path,
                 &Into::<piet::Color>::into(color),
                 options.stroke_width,
-                &piet::StrokeStyle::default()
-                    .line_cap(piet::LineCap::Butt)
-                    .line_join(piet::LineJoin::Bevel),
+                &options.dashed_stroke_style(
+                    piet::StrokeStyle::default()
+                        .line_cap(piet::LineCap::Butt)
+                        .line_join(piet::LineJoin::Bevel),
+                ),
             );
         }
     }
@@ -218,6 +342,12 @@ impl Composer<SmoothOptions> for PenPath {
         let Some(color) = options.stroke_color else {
             return;
         };
+        if let Some((dash_pattern, dash_offset)) = options.absolute_dash_pattern() {
+            cx.save().unwrap();
+            draw_penpath_dashed(cx, self, &dash_pattern, dash_offset, color, options);
+            cx.restore().unwrap();
+            return;
+        }
         let mut single_pos = true;
         let mut prev = self.start;
 
@@ -361,6 +491,174 @@ impl Composer<SmoothOptions> for crate::Shape {
     }
 }
 
+/// Draws the arrowheads of the options at the start and end of an open shape,
+/// each given as position and direction pointing away from the shape.
+fn draw_arrowheads(
+    cx: &mut impl piet::RenderContext,
+    (start, start_direction): (na::Vector2<f64>, na::Vector2<f64>),
+    (end, end_direction): (na::Vector2<f64>, na::Vector2<f64>),
+    color: Color,
+    options: &SmoothOptions,
+) {
+    options.start_arrowhead.draw(
+        cx,
+        start,
+        start_direction,
+        options.stroke_width,
+        color.into(),
+    );
+    options
+        .end_arrowhead
+        .draw(cx, end, end_direction, options.stroke_width, color.into());
+}
+
+/// The direction from the first of the points which differs from the end, to the end.
+fn direction_away_from(
+    end: na::Vector2<f64>,
+    points: impl IntoIterator<Item = na::Vector2<f64>>,
+) -> na::Vector2<f64> {
+    points
+        .into_iter()
+        .map(|p| end - p)
+        .find(|direction| direction.magnitude() > 0.0)
+        .unwrap_or_else(na::Vector2::zeros)
+}
+
+/// Draws the pen path with the dash pattern, with each dash filled as a separate outline of variable width.
+fn draw_penpath_dashed(
+    cx: &mut impl piet::RenderContext,
+    penpath: &PenPath,
+    dash_pattern: &[f64],
+    dash_offset: f64,
+    color: crate::Color,
+    options: &SmoothOptions,
+) {
+    let mut dasher = Dasher::new(dash_pattern, dash_offset);
+    // The elements of the current dash
+    let mut dash = vec![];
+    if dasher.is_on() {
+        dash.push(penpath.start);
+    }
+    let mut prev = penpath.start;
+
+    for seg in penpath.segments.iter() {
+        let elements = seg.subdivided_elements(&prev);
+
+        for (start, end) in elements.iter().zip(elements.iter().skip(1)) {
+            let len = (end.pos - start.pos).magnitude();
+            let mut consumed = 0.0;
+
+            loop {
+                let step = dasher.remaining.min(len - consumed);
+                consumed += step;
+                dasher.remaining -= step;
+
+                if dasher.remaining > 0.0 {
+                    // The line ends inside the current interval
+                    if dasher.is_on() {
+                        dash.push(*end);
+                    }
+                    break;
+                }
+
+                // The current interval ends on the line
+                let element = if len > 0.0 {
+                    start.lerp(end, consumed / len)
+                } else {
+                    *end
+                };
+                if dasher.is_on() {
+                    dash.push(element);
+                    fill_dash(cx, &dash, color, options);
+                    dash.clear();
+                }
+                dasher.next_interval();
+                if dasher.is_on() {
+                    dash.push(element);
+                }
+            }
+        }
+
+        prev = seg.end();
+    }
+
+    if dasher.is_on() {
+        fill_dash(cx, &dash, color, options);
+    }
+}
+
+/// Fills a single dash. Dashes without a length are filled as dots.
+fn fill_dash(
+    cx: &mut impl piet::RenderContext,
+    dash: &[Element],
+    color: crate::Color,
+    options: &SmoothOptions,
+) {
+    let (Some(first), Some(last)) = (dash.first(), dash.last()) else {
+        return;
+    };
+    let lines = dash
+        .iter()
+        .zip(dash.iter().skip(1))
+        .map(|(start, end)| Line {
+            start: start.pos,
+            end: end.pos,
+        })
+        .collect::<Vec<Line>>();
+    let start_width = options
+        .pressure_curve
+        .apply(options.stroke_width, first.pressure);
+    let end_width = options
+        .pressure_curve
+        .apply(options.stroke_width, last.pressure);
+
+    let bez_path = compose_lines_variable_width(&lines, start_width, end_width, options);
+    if bez_path.elements().is_empty() {
+        cx.fill(
+            kurbo::Circle::new(first.pos.to_kurbo_point(), start_width * 0.5),
+            &Into::<piet::Color>::into(color),
+        );
+    } else {
+        cx.fill(bez_path, &Into::<piet::Color>::into(color));
+    }
+}
+
+/// Walks through the intervals of a dash pattern, which alternate between dashes and gaps.
+struct Dasher<'a> {
+    dash_pattern: &'a [f64],
+    index: usize,
+    /// The remaining length of the current interval.
+    remaining: f64,
+}
+
+impl<'a> Dasher<'a> {
+    /// Expects a dash pattern with an even number of non-negative lengths, which add up to more than zero.
+    fn new(dash_pattern: &'a [f64], dash_offset: f64) -> Self {
+        let mut offset = dash_offset.rem_euclid(dash_pattern.iter().sum::<f64>());
+        let mut index = 0;
+        while offset > dash_pattern[index] {
+            offset -= dash_pattern[index];
+            index = (index + 1) % dash_pattern.len();
+        }
+
+        Self {
+            dash_pattern,
+            index,
+            remaining: dash_pattern[index] - offset,
+        }
+    }
+
+    /// Whether the current interval is a dash.
+    fn is_on(&self) -> bool {
+        self.index % 2 == 0
+    }
+
+    fn next_interval(&mut self) {
+        self.index = (self.index + 1) % self.dash_pattern.len();
+        self.remaining = self.dash_pattern[self.index];
+    }
+}
+
 /// Composes lines with variable width. Must be drawn with only a fill.
 fn compose_lines_variable_width(
     lines: &[Line],
@@ -1,4 +1,6 @@
 // Imports
+use super::Arrowhead;
+use crate::penpath::{Element, Segment};
 use crate::style::PressureCurve;
 use crate::Color;
 use serde::{Deserialize, Serialize};
@@ -19,6 +21,19 @@ pub struct SmoothOptions {
     /// Pressure curve.
     #[serde(rename = "pressure_curve")]
     pub pressure_curve: PressureCurve,
+    /// The lengths of the alternating dashes and gaps, relative to the stroke width.
+    /// When empty, the stroke is solid.
+    #[serde(rename = "dash_array")]
+    pub dash_array: Vec<f64>,
+    /// The distance into the dash pattern at which the stroke starts, relative to the stroke width.
+    #[serde(rename = "dash_offset", with = "crate::serialize::f64_dp3")]
+    pub dash_offset: f64,
+    /// The arrowhead at the start of lines, curves and polylines.
+    #[serde(rename = "start_arrowhead")]
+    pub start_arrowhead: Arrowhead,
+    /// The arrowhead at the end of lines, curves and polylines.
+    #[serde(rename = "end_arrowhead")]
+    pub end_arrowhead: Arrowhead,
 }
 
 impl Default for SmoothOptions {
@@ -28,6 +43,88 @@ impl Default for SmoothOptions {
             stroke_color: Some(Color::BLACK),
             fill_color: None,
             pressure_curve: PressureCurve::default(),
+            dash_array: vec![],
+            dash_offset: 0.0,
+            start_arrowhead: Arrowhead::None,
+            end_arrowhead: Arrowhead::None,
         }
     }
 }
+
+impl SmoothOptions {
+    /// A dash array for dashed strokes.
+    pub const DASH_ARRAY_DASHED: [f64; 2] = [2.0, 3.0];
+    /// A dash array for dotted strokes.
+    pub const DASH_ARRAY_DOTTED: [f64; 2] = [0.0, 2.0];
+    /// A dash array for strokes with alternating dashes and dots.
+    pub const DASH_ARRAY_DASH_DOTTED: [f64; 4] = [2.0, 3.0, 0.0, 3.0];
+
+    /// Whether the stroke is drawn with a dash pattern.
+    ///
+    /// Dash arrays with negative or non-finite lengths, or which add up to zero, are drawn solid.
+    pub fn is_dashed(&self) -> bool {
+        self.dash_array.iter().all(|l| l.is_finite() && *l >= 0.0)
+            && self.dash_array.iter().sum::<f64>() > 0.0
+    }
+
+    /// The dash pattern and offset in absolute lengths, or None when the stroke is solid.
+    ///
+    /// Like in Svg, a dash array with an odd number of lengths is repeated to get an even number.
+    pub fn absolute_dash_pattern(&self) -> Option<(Vec<f64>, f64)> {
+        if !self.is_dashed() || self.stroke_width.is_nan() || self.stroke_width <= 0.0 {
+            return None;
+        }
+        let mut dash_pattern = self
+            .dash_array
+            .iter()
+            .map(|l| l * self.stroke_width)
+            .collect::<Vec<f64>>();
+        if dash_pattern.len() % 2 != 0 {
+            dash_pattern.extend_from_within(..);
+        }
+        let dash_offset = if self.dash_offset.is_finite() {
+            self.dash_offset * self.stroke_width
+        } else {
+            0.0
+        };
+
+        Some((dash_pattern, dash_offset))
+    }
+
+    /// The margin the arrowheads can extend beyond the ends of a shape.
+    pub fn arrowheads_bounds_margin(&self) -> f64 {
+        self.start_arrowhead
+            .bounds_margin(self.stroke_width)
+            .max(self.end_arrowhead.bounds_margin(self.stroke_width))
+    }
+
+    /// Advances the dash offset by the length of the segment starting at `prev`,
+    /// so that the dash pattern continues when a path is drawn segment by segment.
+    pub fn advance_dash_offset(&mut self, prev: &Element, segment: &Segment) {
+        if !self.is_dashed() || self.stroke_width.is_nan() || self.stroke_width <= 0.0 {
+            return;
+        }
+        let elements = segment.subdivided_elements(prev);
+        let len = elements
+            .iter()
+            .zip(elements.iter().skip(1))
+            .map(|(start, end)| (end.pos - start.pos).magnitude())
+            .sum::<f64>();
+        self.dash_offset += len / self.stroke_width;
+    }
+
+    /// The stroke style with the dash pattern applied.
+    ///
+    /// Dashes get round caps, so that zero length dashes are drawn as dots.
+    pub(super) fn dashed_stroke_style(
+        &self,
+        mut stroke_style: piet::StrokeStyle,
+    ) -> piet::StrokeStyle {
+        if let Some((dash_pattern, dash_offset)) = self.absolute_dash_pattern() {
+            stroke_style.set_dash_pattern(dash_pattern);
+            stroke_style.set_dash_offset(dash_offset);
+            stroke_style.set_line_cap(piet::LineCap::Round);
+        }
+        stroke_style
+    }
+}
@@ -1,4 +1,6 @@
 // Imports
+use super::BackgroundImage;
+use crate::fileformats::xoppformat;
 use crate::render;
 use anyhow::Context;
 use p2d::bounding_volume::Aabb;
@@ -32,6 +34,14 @@ pub enum PatternStyle {
     IsometricGrid,
     #[serde(rename = "isometric_dots")]
     IsometricDots,
+    #[serde(rename = "music_staves")]
+    MusicStaves,
+    #[serde(rename = "cornell_notes")]
+    CornellNotes,
+    #[serde(rename = "hexagonal_grid")]
+    HexagonalGrid,
+    #[serde(rename = "engineering_paper")]
+    EngineeringPaper,
 }
 
 impl Default for PatternStyle {
@@ -50,6 +60,38 @@ impl TryFrom<u32> for PatternStyle {
     }
 }
 
+impl PatternStyle {
+    /// The equivalent Xournal++ background style.
+    ///
+    /// Styles without an exact equivalent are mapped to the closest one.
+    pub(crate) fn to_xopp_style(self) -> xoppformat::XoppBackgroundSolidStyle {
+        match self {
+            Self::None | Self::HexagonalGrid => xoppformat::XoppBackgroundSolidStyle::Plain,
+            Self::Lines => xoppformat::XoppBackgroundSolidStyle::Ruled,
+            Self::Grid | Self::EngineeringPaper => xoppformat::XoppBackgroundSolidStyle::Graph,
+            Self::Dots => xoppformat::XoppBackgroundSolidStyle::Dotted,
+            Self::IsometricGrid => xoppformat::XoppBackgroundSolidStyle::IsometricGraph,
+            Self::IsometricDots => xoppformat::XoppBackgroundSolidStyle::IsometricDotted,
+            Self::MusicStaves => xoppformat::XoppBackgroundSolidStyle::Staves,
+            Self::CornellNotes => xoppformat::XoppBackgroundSolidStyle::Lined,
+        }
+    }
+
+    /// The equivalent pattern style of a Xournal++ background style.
+    pub(crate) fn from_xopp_style(style: &xoppformat::XoppBackgroundSolidStyle) -> Self {
+        match style {
+            xoppformat::XoppBackgroundSolidStyle::Plain => Self::None,
+            xoppformat::XoppBackgroundSolidStyle::Lined
+            | xoppformat::XoppBackgroundSolidStyle::Ruled => Self::Lines,
+            xoppformat::XoppBackgroundSolidStyle::Staves => Self::MusicStaves,
+            xoppformat::XoppBackgroundSolidStyle::Graph => Self::Grid,
+            xoppformat::XoppBackgroundSolidStyle::Dotted => Self::Dots,
+            xoppformat::XoppBackgroundSolidStyle::IsometricDotted => Self::IsometricDots,
+            xoppformat::XoppBackgroundSolidStyle::IsometricGraph => Self::IsometricGrid,
+        }
+    }
+}
+
 /// 3_f64.sqrt()
 const SQRT_THREE: f64 = 1.7320508075688772;
 /// 3_f64.sqrt() / 2_f64
@@ -331,7 +373,237 @@ fn gen_iso_dots_pattern(
     group.into()
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+/// Fill the bounds with the pattern, which is repeated in tiles of the given size, aligned to the origin.
+fn gen_pattern_rect(
+    bounds: Aabb,
+    pattern_id: &str,
+    tile_size: na::Vector2<f64>,
+    pattern_content: element::Group,
+) -> svg::node::element::Element {
+    let pattern = element::Definitions::new().add(
+        element::Pattern::new()
+            .set("id", pattern_id)
+            .set("x", 0_f64)
+            .set("y", 0_f64)
+            .set("width", tile_size[0])
+            .set("height", tile_size[1])
+            .set("patternUnits", "userSpaceOnUse")
+            .set("patternContentUnits", "userSpaceOnUse")
+            .add(pattern_content),
+    );
+
+    let mut rect = element::Rectangle::new().set("fill", format!("url(#{pattern_id})"));
+    rect.assign("x", format!("{}px", bounds.mins[0]));
+    rect.assign("y", format!("{}px", bounds.mins[1]));
+    rect.assign("width", format!("{}px", bounds.extents()[0]));
+    rect.assign("height", format!("{}px", bounds.extents()[1]));
+
+    let group = element::Group::new().add(pattern).add(rect);
+    group.into()
+}
+
+fn gen_line(
+    start: na::Vector2<f64>,
+    end: na::Vector2<f64>,
+    color: Color,
+    line_width: f64,
+) -> element::Line {
+    element::Line::new()
+        .set("stroke-width", line_width)
+        .set("stroke", color.to_css_color_attr())
+        .set("x1", start[0])
+        .set("y1", start[1])
+        .set("x2", end[0])
+        .set("y2", end[1])
+}
+
+/// The distance between the lines of a music staff, for the given distance between consecutive staves.
+fn calc_music_staff_line_distance(staff_spacing: f64) -> f64 {
+    staff_spacing / MUSIC_STAFF_PERIOD_LINE_DISTANCES
+}
+
+/// The number of line distances from one staff to the next. Four for the staff itself, and four for the spacing.
+const MUSIC_STAFF_PERIOD_LINE_DISTANCES: f64 = 8.0;
+
+fn gen_music_staves_pattern(
+    bounds: Aabb,
+    staff_spacing: f64,
+    color: Color,
+    line_width: f64,
+) -> svg::node::element::Element {
+    // staff_spacing: the distance between the first lines of two consecutive staves
+
+    let pattern_id = rnote_compose::utils::svg_random_id_prefix() + "_bg_music_staves_pattern";
+    let line_distance = calc_music_staff_line_distance(staff_spacing);
+    let line_offset = line_width * 0.5;
+
+    let mut content = element::Group::new();
+    for i in 0..5 {
+        let y = line_offset + line_distance * i as f64;
+        content = content.add(gen_line(
+            na::vector![0.0, y],
+            na::vector![bounds.extents()[0], y],
+            color,
+            line_width,
+        ));
+    }
+
+    gen_pattern_rect(
+        bounds,
+        &pattern_id,
+        na::vector![bounds.extents()[0], staff_spacing],
+        content,
+    )
+}
+
+/// The width of the cue column of Cornell notes, relative to the page width.
+const CORNELL_CUE_COLUMN_WIDTH_FACTOR: f64 = 0.3;
+/// The height of the summary area of Cornell notes, relative to the page height.
+const CORNELL_SUMMARY_HEIGHT_FACTOR: f64 = 0.2;
+
+fn gen_cornell_notes_pattern(
+    bounds: Aabb,
+    page_size: na::Vector2<f64>,
+    line_spacing: f64,
+    color: Color,
+    line_width: f64,
+    separator_width: f64,
+) -> svg::node::element::Element {
+    // The pattern is repeated for every page.
+    // The note area is ruled, left of it is the cue column, below both is the summary area.
+
+    let pattern_id = rnote_compose::utils::svg_random_id_prefix() + "_bg_cornell_notes_pattern";
+    let cue_column_width = page_size[0] * CORNELL_CUE_COLUMN_WIDTH_FACTOR;
+    let summary_top = page_size[1] * (1.0 - CORNELL_SUMMARY_HEIGHT_FACTOR);
+    let line_offset = line_width * 0.5;
+
+    let mut content = element::Group::new();
+    if line_spacing > 0.0 {
+        let mut y = line_spacing + line_offset;
+        while y < summary_top {
+            content = content.add(gen_line(
+                na::vector![0.0, y],
+                na::vector![page_size[0], y],
+                color,
+                line_width,
+            ));
+            y += line_spacing;
+        }
+    }
+    content = content
+        .add(gen_line(
+            na::vector![cue_column_width, 0.0],
+            na::vector![cue_column_width, summary_top],
+            color,
+            separator_width,
+        ))
+        .add(gen_line(
+            na::vector![0.0, summary_top],
+            na::vector![page_size[0], summary_top],
+            color,
+            separator_width,
+        ));
+
+    gen_pattern_rect(bounds, &pattern_id, page_size, content)
+}
+
+fn calc_hex_grid_pattern_size(side_length: f64) -> na::Vector2<f64> {
+    na::vector![side_length * SQRT_THREE, side_length * 3.0]
+}
+
+fn gen_hex_grid_pattern(
+    bounds: Aabb,
+    side_length: f64,
+    color: Color,
+    line_width: f64,
+) -> svg::node::element::Element {
+    // side_length: side length of the regular hexagons, which are pointing upwards.
+    // The pattern contains one full hexagon and the vertical edge between the two hexagons of the next row,
+    // all other edges are part of the neighbouring patterns.
+
+    let pattern_id = rnote_compose::utils::svg_random_id_prefix() + "_bg_hex_grid_pattern";
+    let pattern_size = calc_hex_grid_pattern_size(side_length);
+    let (w, s) = (pattern_size[0], side_length);
+
+    let hexagon_path = element::path::Data::new()
+        .move_to((w * 0.5, 0.0))
+        .line_to((w, s * 0.5))
+        .line_to((w, s * 1.5))
+        .line_to((w * 0.5, s * 2.0))
+        .line_to((0.0, s * 1.5))
+        .line_to((0.0, s * 0.5))
+        .close()
+        .move_to((w * 0.5, s * 2.0))
+        .line_to((w * 0.5, s * 3.0));
+
+    let content = element::Group::new().add(
+        element::Path::new()
+            .set("stroke-width", line_width)
+            .set("stroke", color.to_css_color_attr())
+            .set("fill", "none")
+            .set("d", hexagon_path),
+    );
+
+    gen_pattern_rect(bounds, &pattern_id, pattern_size, content)
+}
+
+/// The number of minor grid cells between two major grid lines of engineering paper.
+const ENGINEERING_PAPER_MAJOR_INTERVAL: u32 = 5;
+
+fn gen_engineering_paper_pattern(
+    bounds: Aabb,
+    row_spacing: f64,
+    column_spacing: f64,
+    minor_color: Color,
+    major_color: Color,
+    minor_line_width: f64,
+    major_line_width: f64,
+) -> svg::node::element::Element {
+    let pattern_id = rnote_compose::utils::svg_random_id_prefix() + "_bg_engineering_paper_pattern";
+    let interval = ENGINEERING_PAPER_MAJOR_INTERVAL;
+    let pattern_size = na::vector![
+        column_spacing * interval as f64,
+        row_spacing * interval as f64
+    ];
+    let minor_offset = minor_line_width * 0.5;
+    let major_offset = major_line_width * 0.5;
+
+    let mut content = element::Group::new();
+    for i in 1..interval {
+        let x = minor_offset + column_spacing * i as f64;
+        let y = minor_offset + row_spacing * i as f64;
+        content = content
+            .add(gen_line(
+                na::vector![x, 0.0],
+                na::vector![x, pattern_size[1]],
+                minor_color,
+                minor_line_width,
+            ))
+            .add(gen_line(
+                na::vector![0.0, y],
+                na::vector![pattern_size[0], y],
+                minor_color,
+                minor_line_width,
+            ));
+    }
+    content = content
+        .add(gen_line(
+            na::vector![major_offset, 0.0],
+            na::vector![major_offset, pattern_size[1]],
+            major_color,
+            major_line_width,
+        ))
+        .add(gen_line(
+            na::vector![0.0, major_offset],
+            na::vector![pattern_size[0], major_offset],
+            major_color,
+            major_line_width,
+        ));
+
+    gen_pattern_rect(bounds, &pattern_id, pattern_size, content)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default, rename = "background")]
 pub struct Background {
     #[serde(rename = "color")]
@@ -345,6 +617,12 @@ pub struct Background {
     pub pattern_size: na::Vector2<f64>,
     #[serde(rename = "pattern_color")]
     pub pattern_color: Color,
+    /// The color of the major grid lines of engineering paper.
+    #[serde(rename = "pattern_major_color")]
+    pub pattern_major_color: Color,
+    /// An image that is drawn on every page, between the background color and the pattern.
+    #[serde(rename = "image")]
+    pub image: Option<BackgroundImage>,
 }
 
 impl Default for Background {
@@ -354,12 +632,16 @@ impl Default for Background {
             pattern: PatternStyle::default(),
             pattern_size: Self::PATTERN_SIZE_DEFAULT,
             pattern_color: Self::PATTERN_COLOR_DEFAULT,
+            pattern_major_color: Self::PATTERN_MAJOR_COLOR_DEFAULT,
+            image: None,
         }
     }
 }
 
 impl Background {
     const LINE_WIDTH: f64 = 0.5;
+    const SEPARATOR_LINE_WIDTH: f64 = 1.5;
+    const MAJOR_LINE_WIDTH: f64 = 1.0;
     const DOTS_WIDTH: f64 = 1.5;
     const HEXAGON_HEIGHT: f64 = 2.0;
 
@@ -372,9 +654,22 @@ impl Background {
         b: 1.0,
         a: 1.0,
     };
+    const PATTERN_MAJOR_COLOR_DEFAULT: Color = Color {
+        r: 0.55,
+        g: 0.75,
+        b: 0.55,
+        a: 1.0,
+    };
+
+    /// Calculates the tile size as multiple of pattern_size with max size TITLE_MAX_SIZE.
+    ///
+    /// Patterns that are laid out per page, like Cornell notes, and backgrounds with an image
+    /// have tiles the size of the format.
+    pub(crate) fn tile_size(&self, format_size: na::Vector2<f64>) -> na::Vector2<f64> {
+        if self.image.is_some() {
+            return format_size;
+        }
 
-    /// Calculates the tile size as multiple of pattern_size with max size TITLE_MAX_SIZE
-    pub(crate) fn tile_size(&self) -> na::Vector2<f64> {
         let pattern_size = match self.pattern {
             PatternStyle::None => {
                 na::vector![Self::TILE_MAX_SIZE, Self::TILE_MAX_SIZE]
@@ -388,6 +683,14 @@ impl Background {
                     self.pattern_size[1]
                 ]
             }
+            PatternStyle::MusicStaves => {
+                na::vector![Self::TILE_MAX_SIZE, self.pattern_size[1]]
+            }
+            PatternStyle::CornellNotes => return format_size,
+            PatternStyle::HexagonalGrid => calc_hex_grid_pattern_size(self.pattern_size[1]),
+            PatternStyle::EngineeringPaper => {
+                self.pattern_size * ENGINEERING_PAPER_MAJOR_INTERVAL as f64
+            }
             _ => self.pattern_size,
         };
 
@@ -409,25 +712,29 @@ impl Background {
     }
 
     /// Generate the background svg, without Xml header or Svg root.
+    ///
+    /// The format size is needed for patterns that are laid out per page.
     pub(crate) fn gen_svg(
         &self,
         bounds: Aabb,
+        format_size: na::Vector2<f64>,
         with_pattern: bool,
         optimize_printing: bool,
     ) -> Result<render::Svg, anyhow::Error> {
-        let (color, pattern_color) = if optimize_printing {
+        let (color, pattern_color, pattern_major_color) = if optimize_printing {
             if self.color.luma() > 0.5 {
                 // original background color is bright, don't invert pattern color
-                (Color::WHITE, self.pattern_color)
+                (Color::WHITE, self.pattern_color, self.pattern_major_color)
             } else {
                 // original background color is dark, invert pattern color
                 (
                     Color::WHITE,
                     self.pattern_color.to_inverted_brightness_color(),
+                    self.pattern_major_color.to_inverted_brightness_color(),
                 )
             }
         } else {
-            (self.color, self.pattern_color)
+            (self.color, self.pattern_color, self.pattern_major_color)
         };
 
         // background color
@@ -440,6 +747,11 @@ impl Background {
         let mut svg_group = element::Group::new();
         svg_group = svg_group.add(color_rect);
 
+        if let Some(image) = &self.image {
+            let image_svg = image.gen_svg(bounds, format_size)?;
+            svg_group = svg_group.add(svg::node::Blob::new(image_svg.svg_data));
+        }
+
         if with_pattern {
             match self.pattern {
                 PatternStyle::None => {}
@@ -485,6 +797,43 @@ impl Background {
                         Self::HEXAGON_HEIGHT,
                     ));
                 }
+                PatternStyle::MusicStaves => {
+                    svg_group = svg_group.add(gen_music_staves_pattern(
+                        bounds,
+                        self.pattern_size[1],
+                        pattern_color,
+                        Self::LINE_WIDTH,
+                    ));
+                }
+                PatternStyle::CornellNotes => {
+                    svg_group = svg_group.add(gen_cornell_notes_pattern(
+                        bounds,
+                        format_size,
+                        self.pattern_size[1],
+                        pattern_color,
+                        Self::LINE_WIDTH,
+                        Self::SEPARATOR_LINE_WIDTH,
+                    ));
+                }
+                PatternStyle::HexagonalGrid => {
+                    svg_group = svg_group.add(gen_hex_grid_pattern(
+                        bounds,
+                        self.pattern_size[1],
+                        pattern_color,
+                        Self::LINE_WIDTH,
+                    ));
+                }
+                PatternStyle::EngineeringPaper => {
+                    svg_group = svg_group.add(gen_engineering_paper_pattern(
+                        bounds,
+                        self.pattern_size[1],
+                        self.pattern_size[0],
+                        pattern_color,
+                        pattern_major_color,
+                        Self::LINE_WIDTH,
+                        Self::MAJOR_LINE_WIDTH,
+                    ));
+                }
             }
         }
 
@@ -494,9 +843,13 @@ impl Background {
         Ok(render::Svg { svg_data, bounds })
     }
 
-    pub(crate) fn gen_tile_image(&self, image_scale: f64) -> Result<render::Image, anyhow::Error> {
-        let tile_bounds = Aabb::new(na::point![0.0, 0.0], self.tile_size().into());
-        self.gen_svg(tile_bounds, true, false)?
+    pub(crate) fn gen_tile_image(
+        &self,
+        format_size: na::Vector2<f64>,
+        image_scale: f64,
+    ) -> Result<render::Image, anyhow::Error> {
+        let tile_bounds = Aabb::new(na::point![0.0, 0.0], self.tile_size(format_size).into());
+        self.gen_svg(tile_bounds, format_size, true, false)?
             .gen_image(image_scale)
     }
 
@@ -504,11 +857,33 @@ impl Background {
         &self,
         cx: &cairo::Context,
         bounds: Aabb,
+        format_size: na::Vector2<f64>,
         with_pattern: bool,
         optimize_printing: bool,
     ) -> anyhow::Result<()> {
-        let mut background_svg = self.gen_svg(bounds, with_pattern, optimize_printing)?;
+        let mut background_svg =
+            self.gen_svg(bounds, format_size, with_pattern, optimize_printing)?;
         background_svg.wrap_svg_root(Some(bounds), Some(bounds), false);
         background_svg.draw_to_cairo(cx)
     }
 }
+
+/// A background that overrides the document background for a single page.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename = "page_background")]
+pub struct PageBackground {
+    /// The index of the page, starting at 0 for the page at the origin.
+    #[serde(rename = "page")]
+    pub page: u32,
+    #[serde(rename = "background")]
+    pub background: Background,
+}
+
+impl Default for PageBackground {
+    fn default() -> Self {
+        Self {
+            page: 0,
+            background: Background::default(),
+        }
+    }
+}
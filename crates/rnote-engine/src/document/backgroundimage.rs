@@ -0,0 +1,316 @@
+// Imports
+use crate::render;
+use crate::strokes::resize::ImageSizeOption;
+use crate::strokes::{BitmapImage, VectorImage};
+use crate::Drawable;
+use p2d::bounding_volume::{Aabb, BoundingVolume};
+use rnote_compose::ext::AabbExt;
+use rnote_compose::shapes::{Rectangle, Shapeable};
+use rnote_compose::SplitOrder;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+#[derive(
+    Debug,
+    Eq,
+    PartialEq,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    num_derive::FromPrimitive,
+    num_derive::ToPrimitive,
+)]
+#[serde(rename = "background_image_mode")]
+pub enum BackgroundImageMode {
+    /// The image is repeated in its intrinsic size, starting at the upper left corner of every page.
+    #[serde(rename = "tiled")]
+    Tiled = 0,
+    /// The image is stretched to fill every page.
+    #[serde(rename = "stretched")]
+    Stretched,
+    /// The image is placed in its intrinsic size in the center of every page.
+    #[serde(rename = "centered")]
+    Centered,
+}
+
+impl Default for BackgroundImageMode {
+    fn default() -> Self {
+        Self::Stretched
+    }
+}
+
+impl TryFrom<u32> for BackgroundImageMode {
+    type Error = anyhow::Error;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        num_traits::FromPrimitive::from_u32(value).ok_or_else(|| {
+            anyhow::anyhow!(
+                "BackgroundImageMode try_from::<u32>() for value {} failed",
+                value
+            )
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename = "background_image_content")]
+pub enum BackgroundImageContent {
+    #[serde(rename = "bitmap")]
+    Bitmap(BitmapImage),
+    #[serde(rename = "vector")]
+    Vector(VectorImage),
+}
+
+impl Default for BackgroundImageContent {
+    fn default() -> Self {
+        Self::Bitmap(BitmapImage::default())
+    }
+}
+
+/// An image that is drawn on every page of the background.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename = "background_image")]
+pub struct BackgroundImage {
+    #[serde(rename = "content")]
+    pub content: BackgroundImageContent,
+    #[serde(rename = "mode")]
+    pub mode: BackgroundImageMode,
+}
+
+impl Default for BackgroundImage {
+    fn default() -> Self {
+        Self {
+            content: BackgroundImageContent::default(),
+            mode: BackgroundImageMode::default(),
+        }
+    }
+}
+
+impl BackgroundImage {
+    /// Create a new background image from encoded bitmap image bytes.
+    pub fn from_image_bytes(bytes: &[u8], mode: BackgroundImageMode) -> anyhow::Result<Self> {
+        let image = BitmapImage::from_image_bytes(
+            bytes,
+            na::Vector2::zeros(),
+            ImageSizeOption::RespectOriginalSize,
+        )?;
+        Ok(Self {
+            content: BackgroundImageContent::Bitmap(image),
+            mode,
+        })
+    }
+
+    /// Create a new background image from a Svg string.
+    pub fn from_svg_str(svg_data: &str, mode: BackgroundImageMode) -> anyhow::Result<Self> {
+        let image = VectorImage::from_svg_str(
+            svg_data,
+            na::Vector2::zeros(),
+            ImageSizeOption::RespectOriginalSize,
+        )?;
+        Ok(Self {
+            content: BackgroundImageContent::Vector(image),
+            mode,
+        })
+    }
+
+    /// Create a new background image from the bytes of a file, choosing the format from the file extension.
+    ///
+    /// Files with the `svg` extension are expected to be UTF-8 encoded Svg's,
+    /// all others to be encoded bitmap images (Png/Jpeg).
+    pub fn from_file_bytes(
+        bytes: Vec<u8>,
+        file_ext: Option<&str>,
+        mode: BackgroundImageMode,
+    ) -> anyhow::Result<Self> {
+        if file_ext.is_some_and(|ext| ext.eq_ignore_ascii_case("svg")) {
+            Self::from_svg_str(&String::from_utf8(bytes)?, mode)
+        } else {
+            Self::from_image_bytes(&bytes, mode)
+        }
+    }
+
+    /// Load the background image from a file.
+    pub fn load_from_file(
+        path: impl AsRef<Path>,
+        mode: BackgroundImageMode,
+    ) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)?;
+        Self::from_file_bytes(bytes, path.extension().and_then(|ext| ext.to_str()), mode)
+    }
+
+    /// Encode the image as Png, rasterizing it in its intrinsic size if it is a Svg.
+    pub fn to_png_bytes(&self) -> anyhow::Result<Vec<u8>> {
+        let image = match &self.content {
+            BackgroundImageContent::Bitmap(bitmapimage) => bitmapimage.image.clone(),
+            BackgroundImageContent::Vector(vectorimage) => render::Image::gen_with_cairo(
+                |cx| vectorimage.draw_to_cairo(cx, 1.0),
+                vectorimage.rectangle.bounds(),
+                1.0,
+            )?,
+        };
+        image.into_encoded_bytes(image::ImageFormat::Png, None)
+    }
+
+    /// The intrinsic size of the image.
+    pub fn intrinsic_size(&self) -> na::Vector2<f64> {
+        match &self.content {
+            BackgroundImageContent::Bitmap(bitmapimage) => na::vector![
+                f64::from(bitmapimage.image.pixel_width),
+                f64::from(bitmapimage.image.pixel_height)
+            ],
+            BackgroundImageContent::Vector(vectorimage) => vectorimage.intrinsic_size,
+        }
+    }
+
+    /// The rectangles the image is placed in on a page with the given bounds.
+    fn placements_for_page(&self, page_bounds: Aabb) -> Vec<Aabb> {
+        let intrinsic_size = self.intrinsic_size();
+
+        match self.mode {
+            BackgroundImageMode::Stretched => vec![page_bounds],
+            _ if intrinsic_size[0] <= 0.0 || intrinsic_size[1] <= 0.0 => vec![],
+            BackgroundImageMode::Centered => {
+                let mins = page_bounds.center() - intrinsic_size * 0.5;
+                vec![Aabb::new(mins, mins + intrinsic_size)]
+            }
+            BackgroundImageMode::Tiled => {
+                let mut tiles = Vec::new();
+                let mut y = page_bounds.mins[1];
+
+                while y < page_bounds.maxs[1] {
+                    let mut x = page_bounds.mins[0];
+
+                    while x < page_bounds.maxs[0] {
+                        tiles.push(Aabb::new(
+                            na::point![x, y],
+                            na::point![x + intrinsic_size[0], y + intrinsic_size[1]],
+                        ));
+                        x += intrinsic_size[0];
+                    }
+
+                    y += intrinsic_size[1];
+                }
+
+                tiles
+            }
+        }
+    }
+
+    /// Draws the image on all pages that intersect the bounds, clipped to the bounds.
+    ///
+    /// Pages are origin-aligned and have the given format size.
+    pub(crate) fn draw_to_cairo(
+        &self,
+        cx: &cairo::Context,
+        bounds: Aabb,
+        format_size: na::Vector2<f64>,
+    ) -> anyhow::Result<()> {
+        if format_size[0] <= 0.0 || format_size[1] <= 0.0 {
+            return Ok(());
+        }
+
+        cx.save()?;
+        cx.rectangle(
+            bounds.mins[0],
+            bounds.mins[1],
+            bounds.extents()[0],
+            bounds.extents()[1],
+        );
+        cx.clip();
+
+        let mut content = self.content.clone();
+
+        for page_bounds in bounds.split_extended_origin_aligned(format_size, SplitOrder::default())
+        {
+            cx.save()?;
+            cx.rectangle(
+                page_bounds.mins[0],
+                page_bounds.mins[1],
+                page_bounds.extents()[0],
+                page_bounds.extents()[1],
+            );
+            cx.clip();
+
+            for placement in self
+                .placements_for_page(page_bounds)
+                .into_iter()
+                .filter(|placement| placement.intersects(&bounds))
+            {
+                let rectangle = Rectangle::from_p2d_aabb(placement);
+
+                match &mut content {
+                    BackgroundImageContent::Bitmap(bitmapimage) => {
+                        bitmapimage.rectangle = rectangle;
+                        bitmapimage.draw_to_cairo(cx, 1.0)?;
+                    }
+                    BackgroundImageContent::Vector(vectorimage) => {
+                        vectorimage.rectangle = rectangle;
+                        vectorimage.draw_to_cairo(cx, 1.0)?;
+                    }
+                }
+            }
+
+            cx.restore()?;
+        }
+
+        cx.restore()?;
+        Ok(())
+    }
+
+    /// Generate the Svg of the image on all pages that intersect the bounds.
+    pub(crate) fn gen_svg(
+        &self,
+        bounds: Aabb,
+        format_size: na::Vector2<f64>,
+    ) -> anyhow::Result<render::Svg> {
+        render::Svg::gen_with_cairo(|cx| self.draw_to_cairo(cx, bounds, format_size), bounds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use std::io::Cursor;
+
+    #[test]
+    fn bitmap_file_bytes() {
+        let mut bytes = Cursor::new(Vec::new());
+        image::DynamicImage::ImageRgba8(image::RgbaImage::new(4, 3))
+            .write_to(&mut bytes, image::ImageFormat::Png)
+            .unwrap();
+        let image = BackgroundImage::from_file_bytes(
+            bytes.into_inner(),
+            Some("png"),
+            BackgroundImageMode::Tiled,
+        )
+        .unwrap();
+
+        assert!(matches!(image.content, BackgroundImageContent::Bitmap(_)));
+        assert_relative_eq!(image.intrinsic_size(), na::vector![4.0, 3.0]);
+    }
+
+    #[test]
+    fn svg_file_bytes() {
+        let svg_data = r#"<svg xmlns="http://www.w3.org/2000/svg" width="40" height="30"><rect width="40" height="30" fill="red"/></svg>"#;
+        let image = BackgroundImage::from_file_bytes(
+            svg_data.as_bytes().to_vec(),
+            Some("SVG"),
+            BackgroundImageMode::Tiled,
+        )
+        .unwrap();
+
+        assert!(matches!(image.content, BackgroundImageContent::Vector(_)));
+        assert_relative_eq!(image.intrinsic_size(), na::vector![40.0, 30.0]);
+
+        // Text that only mentions Svg is not loaded as Svg without the extension
+        assert!(BackgroundImage::from_file_bytes(
+            svg_data.as_bytes().to_vec(),
+            Some("png"),
+            BackgroundImageMode::Tiled
+        )
+        .is_err());
+    }
+}
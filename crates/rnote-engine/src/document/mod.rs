@@ -1,9 +1,11 @@
 // Modules
 pub mod background;
+pub mod backgroundimage;
 pub mod format;
 
 // Re-exports
-pub use background::Background;
+pub use background::{Background, PageBackground};
+pub use backgroundimage::{BackgroundImage, BackgroundImageMode};
 pub use format::Format;
 
 // Imports
@@ -125,8 +127,11 @@ impl Default for Document {
 }
 
 impl CloneConfig for Document {
+    /// Clones the document without the background image, which is part of the document content.
     fn clone_config(&self) -> Self {
-        self.clone()
+        let mut document = self.clone();
+        document.background.image = None;
+        document
     }
 }
 
@@ -249,6 +254,32 @@ impl Document {
         widget_flags
     }
 
+    /// Whether the layout has pages that are stacked vertically, which can have their own background.
+    pub fn has_pages(&self) -> bool {
+        self.layout.is_fixed_width()
+    }
+
+    /// The bounds of the page with the given index, starting at 0 for the page at the origin.
+    pub(crate) fn page_bounds(&self, page: u32) -> Aabb {
+        let format_size = self.format.size();
+        let mins = na::point![
+            (self.x / format_size[0]).floor() * format_size[0],
+            page as f64 * format_size[1]
+        ];
+        Aabb::new(mins, mins + format_size)
+    }
+
+    /// The index of the page that contains the position.
+    ///
+    /// Returns None if the layout has no pages or the position is above the first page.
+    pub(crate) fn page_index_for_pos(&self, pos: na::Vector2<f64>) -> Option<u32> {
+        let format_height = self.format.height();
+        if !self.has_pages() || format_height <= 0.0 || pos[1] < 0.0 {
+            return None;
+        }
+        Some((pos[1] / format_height).floor() as u32)
+    }
+
     /// Adds a page when in fixed-size layout.
     ///
     /// Returns false when not in fixed-size layout.
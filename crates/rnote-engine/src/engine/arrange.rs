@@ -0,0 +1,282 @@
+// Imports
+use crate::store::StrokeKey;
+use crate::{Engine, WidgetFlags};
+use p2d::bounding_volume::{Aabb, BoundingVolume};
+use std::time::Instant;
+
+/// How the selected strokes are aligned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alignment {
+    /// Align the left edges.
+    Left,
+    /// Align the right edges.
+    Right,
+    /// Align the top edges.
+    Top,
+    /// Align the bottom edges.
+    Bottom,
+    /// Align the horizontal centers.
+    CenterHorizontal,
+    /// Align the vertical centers.
+    CenterVertical,
+}
+
+impl std::str::FromStr for Alignment {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "left" => Ok(Self::Left),
+            "right" => Ok(Self::Right),
+            "top" => Ok(Self::Top),
+            "bottom" => Ok(Self::Bottom),
+            "center-horizontal" => Ok(Self::CenterHorizontal),
+            "center-vertical" => Ok(Self::CenterVertical),
+            s => Err(anyhow::anyhow!(
+                "Alignment from_string failed, invalid name: {s}"
+            )),
+        }
+    }
+}
+
+/// How the selected strokes are distributed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Distribution {
+    /// Equal horizontal gaps between the strokes.
+    Horizontal,
+    /// Equal vertical gaps between the strokes.
+    Vertical,
+}
+
+impl std::str::FromStr for Distribution {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "horizontal" => Ok(Self::Horizontal),
+            "vertical" => Ok(Self::Vertical),
+            s => Err(anyhow::anyhow!(
+                "Distribution from_string failed, invalid name: {s}"
+            )),
+        }
+    }
+}
+
+impl Distribution {
+    /// The index of the axis the strokes are distributed along.
+    fn axis(&self) -> usize {
+        match self {
+            Self::Horizontal => 0,
+            Self::Vertical => 1,
+        }
+    }
+}
+
+impl Engine {
+    /// Align the selected strokes to the bounds of the selection.
+    ///
+    /// Strokes in the same group are moved together.
+    pub fn align_selection(&mut self, alignment: Alignment) -> WidgetFlags {
+        let units = self.selection_units();
+        if units.len() < 2 {
+            return WidgetFlags::default();
+        }
+        let selection_bounds = units
+            .iter()
+            .map(|(_, bounds)| *bounds)
+            .reduce(|acc, bounds| acc.merged(&bounds))
+            .unwrap();
+
+        let mut moved_keys = Vec::new();
+        for (keys, bounds) in units.iter() {
+            let offset = match alignment {
+                Alignment::Left => na::vector![selection_bounds.mins[0] - bounds.mins[0], 0.0],
+                Alignment::Right => na::vector![selection_bounds.maxs[0] - bounds.maxs[0], 0.0],
+                Alignment::Top => na::vector![0.0, selection_bounds.mins[1] - bounds.mins[1]],
+                Alignment::Bottom => na::vector![0.0, selection_bounds.maxs[1] - bounds.maxs[1]],
+                Alignment::CenterHorizontal => {
+                    na::vector![selection_bounds.center()[0] - bounds.center()[0], 0.0]
+                }
+                Alignment::CenterVertical => {
+                    na::vector![0.0, selection_bounds.center()[1] - bounds.center()[1]]
+                }
+            };
+            if self.translate_arranged_strokes(keys, offset) {
+                moved_keys.extend_from_slice(keys);
+            }
+        }
+        self.finish_arrange(&moved_keys)
+    }
+
+    /// Distribute the selected strokes with equal gaps between them,
+    /// keeping the first and the last stroke along the axis in place.
+    ///
+    /// Strokes in the same group are moved together.
+    pub fn distribute_selection(&mut self, distribution: Distribution) -> WidgetFlags {
+        let axis = distribution.axis();
+        let mut units = self.selection_units();
+        if units.len() < 3 {
+            return WidgetFlags::default();
+        }
+        units.sort_by(|(_, first), (_, second)| {
+            first.center()[axis].total_cmp(&second.center()[axis])
+        });
+        let span = units.last().unwrap().1.maxs[axis] - units.first().unwrap().1.mins[axis];
+        let occupied = units
+            .iter()
+            .map(|(_, bounds)| bounds.extents()[axis])
+            .sum::<f64>();
+        let gap = (span - occupied) / (units.len() - 1) as f64;
+
+        let mut pos = units.first().unwrap().1.maxs[axis] + gap;
+        let mut moved_keys = Vec::new();
+        for (keys, bounds) in units[1..units.len() - 1].iter() {
+            let mut offset = na::Vector2::<f64>::zeros();
+            offset[axis] = pos - bounds.mins[axis];
+            if self.translate_arranged_strokes(keys, offset) {
+                moved_keys.extend_from_slice(keys);
+            }
+            pos += bounds.extents()[axis] + gap;
+        }
+        self.finish_arrange(&moved_keys)
+    }
+
+    /// The selected strokes, bundled into units that are arranged as a whole, together with their bounds.
+    ///
+    /// Strokes that share an outermost group form a single unit, all other strokes form a unit of their own.
+    fn selection_units(&self) -> Vec<(Vec<StrokeKey>, Aabb)> {
+        let mut units: Vec<(Option<u32>, Vec<StrokeKey>, Aabb)> = Vec::new();
+
+        for key in self.store.selection_keys_as_rendered() {
+            let Some(bounds) = self.store.strokes_bounds(&[key]).pop() else {
+                continue;
+            };
+            let group = self.store.outermost_group(key);
+            match units
+                .iter_mut()
+                .find(|(unit_group, ..)| group.is_some() && *unit_group == group)
+            {
+                Some((_, keys, unit_bounds)) => {
+                    keys.push(key);
+                    unit_bounds.merge(&bounds);
+                }
+                None => units.push((group, vec![key], bounds)),
+            }
+        }
+
+        units
+            .into_iter()
+            .map(|(_, keys, bounds)| (keys, bounds))
+            .collect()
+    }
+
+    /// Translate the strokes of a unit. Returns true if they were moved.
+    fn translate_arranged_strokes(&mut self, keys: &[StrokeKey], offset: na::Vector2<f64>) -> bool {
+        // Tolerates rounding errors of the computed offsets, so that already arranged strokes are not moved again
+        if offset.amax() < 1e-6 {
+            return false;
+        }
+        self.store.translate_strokes(keys, offset);
+        self.store.translate_strokes_images(keys, offset);
+        true
+    }
+
+    /// Finish an arrange operation that moved the given strokes as a single history entry.
+    ///
+    /// Nothing is recorded when no strokes were moved.
+    fn finish_arrange(&mut self, moved_keys: &[StrokeKey]) -> WidgetFlags {
+        if moved_keys.is_empty() {
+            return WidgetFlags::default();
+        }
+        self.store.update_geometry_for_strokes(moved_keys);
+        let mut widget_flags = self.current_pen_update_state()
+            | self.record(Instant::now())
+            | self.update_content_rendering_current_viewport();
+        widget_flags.store_modified = true;
+        widget_flags
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strokes::{BrushStroke, Stroke};
+    use approx::assert_relative_eq;
+    use rnote_compose::penpath::{Element, Segment};
+    use rnote_compose::{PenPath, Style};
+
+    /// An engine with the given line strokes selected, together with their keys.
+    fn engine_w_selected_lines(
+        lines: &[(na::Vector2<f64>, na::Vector2<f64>)],
+    ) -> (Engine, Vec<StrokeKey>) {
+        let mut engine = Engine::default();
+        let keys = lines
+            .iter()
+            .map(|&(start, end)| {
+                let mut path = PenPath::new(Element::new(start, 0.5));
+                path.segments.push(Segment::LineTo {
+                    end: Element::new(end, 0.5),
+                });
+                engine.store.insert_stroke(
+                    Stroke::BrushStroke(BrushStroke::from_penpath(path, Style::default())),
+                    None,
+                )
+            })
+            .collect::<Vec<StrokeKey>>();
+        engine.store.set_selected_keys(&keys, true);
+        let _ = engine.record(Instant::now());
+        (engine, keys)
+    }
+
+    #[test]
+    fn align() {
+        let (mut engine, keys) = engine_w_selected_lines(&[
+            (na::vector![0.0, 0.0], na::vector![10.0, 10.0]),
+            (na::vector![50.0, 100.0], na::vector![80.0, 110.0]),
+        ]);
+        let bounds_before = engine.store.strokes_bounds(&keys);
+
+        assert!(engine.align_selection(Alignment::Left).store_modified);
+        let bounds = engine.store.strokes_bounds(&keys);
+        assert_relative_eq!(bounds[0].mins[0], bounds_before[0].mins[0]);
+        assert_relative_eq!(bounds[1].mins[0], bounds_before[0].mins[0]);
+        assert_relative_eq!(bounds[1].mins[1], bounds_before[1].mins[1]);
+
+        // Already aligned strokes are not moved and no history entry is recorded
+        assert!(!engine.align_selection(Alignment::Left).store_modified);
+        let _ = engine.undo(Instant::now());
+        let bounds = engine.store.strokes_bounds(&keys);
+        assert_relative_eq!(bounds[1].mins[0], bounds_before[1].mins[0]);
+    }
+
+    #[test]
+    fn distribute() {
+        let (mut engine, keys) = engine_w_selected_lines(&[
+            (na::vector![0.0, 0.0], na::vector![10.0, 0.0]),
+            (na::vector![15.0, 0.0], na::vector![25.0, 0.0]),
+            (na::vector![100.0, 0.0], na::vector![110.0, 0.0]),
+        ]);
+        let bounds_before = engine.store.strokes_bounds(&keys);
+
+        assert!(
+            engine
+                .distribute_selection(Distribution::Horizontal)
+                .store_modified
+        );
+        let bounds = engine.store.strokes_bounds(&keys);
+        assert_relative_eq!(bounds[0].mins[0], bounds_before[0].mins[0]);
+        assert_relative_eq!(bounds[2].mins[0], bounds_before[2].mins[0]);
+        assert_relative_eq!(
+            bounds[1].mins[0] - bounds[0].maxs[0],
+            bounds[2].mins[0] - bounds[1].maxs[0],
+            epsilon = 1e-9
+        );
+
+        // Evenly distributed strokes are not moved and no history entry is recorded
+        assert!(
+            !engine
+                .distribute_selection(Distribution::Horizontal)
+                .store_modified
+        );
+    }
+}
@@ -1,14 +1,20 @@
 // Imports
 use super::{Engine, EngineConfig, StrokeContent};
-use crate::fileformats::rnoteformat::RnoteFile;
+use crate::document::{Background, BackgroundImage};
 use crate::fileformats::{xoppformat, FileFormatSaver};
+use crate::recognition;
+use crate::store::chrono_comp::StrokeLayer;
+use crate::strokes::{Stroke, TextStroke};
 use crate::CloneConfig;
 use anyhow::Context;
 use futures::channel::oneshot;
+use p2d::bounding_volume::Aabb;
 use rayon::prelude::*;
+use rnote_compose::shapes::Shapeable;
 use rnote_compose::transform::Transformable;
 use rnote_compose::SplitOrder;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
 use std::sync::Arc;
 use tracing::error;
 
@@ -35,6 +41,12 @@ pub enum DocExportFormat {
     Pdf,
     #[serde(rename = "xopp")]
     Xopp,
+    /// The text of all text strokes, with inline Markdown markup.
+    #[serde(rename = "markdown")]
+    Markdown,
+    /// The text of all text strokes.
+    #[serde(rename = "plain_text")]
+    PlainText,
 }
 
 impl Default for DocExportFormat {
@@ -63,6 +75,8 @@ impl DocExportFormat {
             DocExportFormat::Svg => String::from("svg"),
             DocExportFormat::Pdf => String::from("pdf"),
             DocExportFormat::Xopp => String::from("xopp"),
+            DocExportFormat::Markdown => String::from("md"),
+            DocExportFormat::PlainText => String::from("txt"),
         }
     }
 }
@@ -327,22 +341,7 @@ impl Engine {
         &self,
         file_name: String,
     ) -> oneshot::Receiver<anyhow::Result<Vec<u8>>> {
-        let (oneshot_sender, oneshot_receiver) = oneshot::channel::<anyhow::Result<Vec<u8>>>();
-        let engine_snapshot = self.take_snapshot();
-        rayon::spawn(move || {
-            let result = || -> anyhow::Result<Vec<u8>> {
-                let rnote_file = RnoteFile {
-                    engine_snapshot: ijson::to_value(&engine_snapshot)?,
-                };
-                rnote_file.save_as_bytes(&file_name)
-            };
-            if oneshot_sender.send(result()).is_err() {
-                error!(
-                    "Sending result to receiver failed while saving document as rnote bytes. Receiver already dropped."
-                );
-            }
-        });
-        oneshot_receiver
+        self.take_snapshot().save_as_rnote_bytes(file_name)
     }
 
     /// Extract the current engine configuration.
@@ -358,17 +357,28 @@ impl Engine {
         }
     }
 
+    /// The bounds and backgrounds of the pages with a background override that intersect the given bounds.
+    fn page_backgrounds_for_content(&self, bounds: Aabb) -> Vec<(Aabb, Background)> {
+        self.page_backgrounds_in_bounds(bounds)
+            .into_iter()
+            .map(|(_, page_bounds, background)| (page_bounds, background))
+            .collect()
+    }
+
     pub fn extract_document_content(&self) -> StrokeContent {
+        let bounds = self
+            .bounds_w_content_extended()
+            .unwrap_or(self.document.bounds());
+
         StrokeContent::default()
             .with_strokes(
                 self.store
                     .get_strokes_arc(&self.store.stroke_keys_as_rendered()),
             )
-            .with_bounds(Some(
-                self.bounds_w_content_extended()
-                    .unwrap_or(self.document.bounds()),
-            ))
-            .with_background(Some(self.document.background))
+            .with_bounds(Some(bounds))
+            .with_background(Some(self.document.background.clone()))
+            .with_format_size(Some(self.document.format.size()))
+            .with_page_backgrounds(self.page_backgrounds_for_content(bounds))
     }
 
     pub fn extract_pages_content(&self, page_order: SplitOrder) -> Vec<StrokeContent> {
@@ -384,7 +394,67 @@ impl Engine {
                         ),
                     )
                     .with_bounds(Some(bounds))
-                    .with_background(Some(self.document.background))
+                    .with_background(Some(self.document.background.clone()))
+                    .with_format_size(Some(self.document.format.size()))
+                    .with_page_backgrounds(self.page_backgrounds_for_content(bounds))
+            })
+            .collect()
+    }
+
+    /// Extract the bounds and the strokes together with their layers for each page with content.
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn extract_pages_content_w_layers(
+        &self,
+        page_order: SplitOrder,
+    ) -> Vec<(Aabb, Vec<(Arc<Stroke>, StrokeLayer)>)> {
+        self.pages_bounds_w_content(page_order)
+            .into_iter()
+            .map(|bounds| {
+                let strokes = self
+                    .store
+                    .stroke_keys_as_rendered_intersecting_bounds(bounds)
+                    .into_iter()
+                    .filter_map(|key| {
+                        Some((
+                            self.store.get_stroke_arc(key)?,
+                            self.store.stroke_layer(key)?,
+                        ))
+                    })
+                    .collect();
+                (bounds, strokes)
+            })
+            .collect()
+    }
+
+    /// Extract the text strokes of the document for each page, in reading order.
+    ///
+    /// Pages are ordered by the given page order, the text strokes on a page from top to bottom, then left to right.
+    /// Text strokes that span multiple pages are only extracted for the first page.
+    pub(crate) fn extract_pages_textstrokes(&self, page_order: SplitOrder) -> Vec<Vec<TextStroke>> {
+        let mut extracted_keys = HashSet::new();
+
+        self.pages_bounds_w_content(page_order)
+            .into_iter()
+            .map(|bounds| {
+                let mut textstrokes = self
+                    .store
+                    .stroke_keys_as_rendered_intersecting_bounds(bounds)
+                    .into_iter()
+                    .filter_map(|key| match self.store.get_stroke_ref(key)? {
+                        Stroke::TextStroke(textstroke) if extracted_keys.insert(key) => {
+                            Some(textstroke.clone())
+                        }
+                        _ => None,
+                    })
+                    .collect::<Vec<TextStroke>>();
+                textstrokes.sort_by(|first, second| {
+                    let first_mins = first.bounds().mins;
+                    let second_mins = second.bounds().mins;
+                    first_mins[1]
+                        .total_cmp(&second_mins[1])
+                        .then(first_mins[0].total_cmp(&second_mins[0]))
+                });
+                textstrokes
             })
             .collect()
     }
@@ -397,7 +467,8 @@ impl Engine {
         Some(
             StrokeContent::default()
                 .with_strokes(self.store.get_strokes_arc(&selection_keys))
-                .with_background(Some(self.document.background)),
+                .with_background(Some(self.document.background.clone()))
+                .with_format_size(Some(self.document.format.size())),
         )
     }
 
@@ -428,6 +499,12 @@ impl Engine {
             DocExportFormat::Xopp => {
                 self.export_doc_as_xopp_bytes(title, doc_export_prefs_override)
             }
+            DocExportFormat::Markdown => {
+                self.export_doc_text_as_bytes(true, doc_export_prefs_override)
+            }
+            DocExportFormat::PlainText => {
+                self.export_doc_text_as_bytes(false, doc_export_prefs_override)
+            }
         }
     }
 
@@ -482,6 +559,7 @@ impl Engine {
             doc_export_prefs_override.unwrap_or(self.export_prefs.doc_export_prefs);
         let pages_content = self.extract_pages_content(doc_export_prefs.page_order);
         let format_size = self.document.format.size();
+        let handwriting_recognizer = self.handwriting_recognizer();
 
         rayon::spawn(move || {
             let result = || -> anyhow::Result<Vec<u8>> {
@@ -518,6 +596,20 @@ impl Engine {
                             DocExportPrefs::MARGIN,
                             Engine::STROKE_EXPORT_IMAGE_SCALE,
                         )?;
+                        if let Some(handwriting_recognizer) = &handwriting_recognizer {
+                            let recognized = recognition::recognize_brushstrokes(
+                                handwriting_recognizer.as_ref(),
+                                page_content.strokes.iter().filter_map(|stroke| {
+                                    match stroke.as_ref() {
+                                        Stroke::BrushStroke(brushstroke) => {
+                                            Some(brushstroke.clone())
+                                        }
+                                        _ => None,
+                                    }
+                                }),
+                            );
+                            recognition::draw_recognized_text_to_cairo(&cairo_cx, &recognized)?;
+                        }
                         cairo_cx.show_page().map_err(|e| {
                             anyhow::anyhow!(
                                 "Showing page failed while exporting page {i} as pdf, Err: {e:?}"
@@ -545,6 +637,55 @@ impl Engine {
         oneshot_receiver
     }
 
+    /// Export the text of all text strokes in the document in reading order, either as Markdown or as plain text.
+    fn export_doc_text_as_bytes(
+        &self,
+        markdown: bool,
+        doc_export_prefs_override: Option<DocExportPrefs>,
+    ) -> oneshot::Receiver<Result<Vec<u8>, anyhow::Error>> {
+        let (oneshot_sender, oneshot_receiver) = oneshot::channel::<anyhow::Result<Vec<u8>>>();
+        let doc_export_prefs =
+            doc_export_prefs_override.unwrap_or(self.export_prefs.doc_export_prefs);
+        let pages_textstrokes = self.extract_pages_textstrokes(doc_export_prefs.page_order);
+
+        rayon::spawn(move || {
+            let result = || -> anyhow::Result<Vec<u8>> {
+                let pages_text = pages_textstrokes
+                    .into_iter()
+                    .filter_map(|textstrokes| {
+                        let page_text = textstrokes
+                            .into_iter()
+                            .map(|textstroke| {
+                                if markdown {
+                                    textstroke.to_markdown()
+                                } else {
+                                    textstroke.text
+                                }
+                            })
+                            .filter(|text| !text.trim().is_empty())
+                            .collect::<Vec<String>>()
+                            .join("\n\n");
+                        (!page_text.is_empty()).then_some(page_text)
+                    })
+                    .collect::<Vec<String>>();
+
+                // Pages are separated by a thematic break in Markdown
+                let separator = if markdown { "\n\n---\n\n" } else { "\n\n" };
+                let mut text = pages_text.join(separator);
+                if !text.is_empty() {
+                    text.push('\n');
+                }
+                Ok(text.into_bytes())
+            };
+
+            if oneshot_sender.send(result()).is_err() {
+                error!("Sending result to receiver failed while exporting document text. Receiver already dropped.");
+            }
+        });
+
+        oneshot_receiver
+    }
+
     /// Export the document as a Xournal++ .xopp file.
     fn export_doc_as_xopp_bytes(
         &self,
@@ -554,101 +695,94 @@ impl Engine {
         let (oneshot_sender, oneshot_receiver) = oneshot::channel::<anyhow::Result<Vec<u8>>>();
         let doc_export_prefs =
             doc_export_prefs_override.unwrap_or(self.export_prefs.doc_export_prefs);
-        let pages_content = self.extract_pages_content(doc_export_prefs.page_order);
+        let pages_content = self.extract_pages_content_w_layers(doc_export_prefs.page_order);
+        let pages_background = pages_content
+            .iter()
+            .map(|(page_bounds, _)| self.xopp_page_background(*page_bounds))
+            .collect::<Vec<(Background, Option<String>)>>();
+        let user_layers = self.store.user_layers().to_vec();
         let document = self.document.clone();
 
         rayon::spawn(move || {
             let result = || -> anyhow::Result<Vec<u8>> {
-                // Only one background for all pages
-                let xopp_background = xoppformat::XoppBackground {
-                    name: None,
-                    bg_type: xoppformat::XoppBackgroundType::Solid {
-                        color: crate::utils::xoppcolor_from_color(document.background.color),
-                        style: xoppformat::XoppBackgroundSolidStyle::Plain,
-                    },
-                };
-
                 // xopp spec needs at least one page in vec,
                 // but it is fine because pages_bounds_w_content() always produces at least one.
                 let pages = pages_content
                     .into_iter()
-                    .filter_map(|page_content| {
-                        let page_bounds = page_content.bounds()?;
-                        // Translate strokes to to page mins and convert to XoppStrokStyle
-                        let xopp_strokestyles = page_content
-                            .strokes
-                            .into_iter()
-                            .filter_map(|mut stroke| {
-                                let mut stroke = Arc::make_mut(&mut stroke).clone();
-                                stroke.translate(-page_bounds.mins.coords);
-                                stroke.into_xopp(document.format.dpi())
-                            })
-                            .collect::<Vec<xoppformat::XoppStrokeType>>();
-
-                        // Extract the strokes
-                        let xopp_strokes = xopp_strokestyles
-                            .iter()
-                            .filter_map(|stroke| {
-                                if let xoppformat::XoppStrokeType::XoppStroke(xoppstroke) = stroke {
-                                    Some(xoppstroke.clone())
-                                } else {
-                                    None
-                                }
-                            })
-                            .collect::<Vec<xoppformat::XoppStroke>>();
-
-                        // Extract the texts
-                        let xopp_texts = xopp_strokestyles
-                            .iter()
-                            .filter_map(|stroke| {
-                                if let xoppformat::XoppStrokeType::XoppText(xopptext) = stroke {
-                                    Some(xopptext.clone())
-                                } else {
-                                    None
-                                }
-                            })
-                            .collect::<Vec<xoppformat::XoppText>>();
-
-                        // Extract the images
-                        let xopp_images = xopp_strokestyles
-                            .iter()
-                            .filter_map(|stroke| {
-                                if let xoppformat::XoppStrokeType::XoppImage(xoppstroke) = stroke {
-                                    Some(xoppstroke.clone())
-                                } else {
-                                    None
-                                }
-                            })
-                            .collect::<Vec<xoppformat::XoppImage>>();
-
-                        // In Rnote images are always rendered below strokes and text.
-                        // To match this behaviour accurately, images are separated into another layer.
-                        let image_layer = xoppformat::XoppLayer {
-                            name: None,
-                            strokes: vec![],
-                            texts: vec![],
-                            images: xopp_images,
+                    .zip(pages_background)
+                    .map(|((page_bounds, page_strokes), page_background)| {
+                        let (page_background, attachment) = page_background;
+                        // Xopp pixmap backgrounds reference an image file that is attached next to the xopp file
+                        // and are always stretched to the page.
+                        // Xopp background styles only have approximate equivalents for some patterns
+                        let bg_type = match attachment {
+                            Some(filename) => xoppformat::XoppBackgroundType::Pixmap {
+                                domain: xoppformat::XoppBackgroundPixmapDomain::Attach,
+                                filename,
+                            },
+                            None => xoppformat::XoppBackgroundType::Solid {
+                                color: crate::utils::xoppcolor_from_color(page_background.color),
+                                style: page_background.pattern.to_xopp_style(),
+                            },
                         };
-
-                        let strokes_layer = xoppformat::XoppLayer {
+                        let xopp_background = xoppformat::XoppBackground {
                             name: None,
-                            strokes: xopp_strokes,
-                            texts: xopp_texts,
-                            images: vec![],
+                            bg_type,
                         };
 
+                        // One xopp layer for each distinct stroke layer. The map is ordered the same way the layers
+                        // are rendered, and Xournal++ renders its layers bottom to top in the order they appear.
+                        let mut layers = BTreeMap::<StrokeLayer, xoppformat::XoppLayer>::new();
+
+                        for (stroke, stroke_layer) in page_strokes.into_iter() {
+                            // Translate strokes to to page mins and convert to XoppStrokStyle
+                            let mut stroke = stroke.as_ref().clone();
+                            stroke.translate(-page_bounds.mins.coords);
+                            let Some(xopp_stroke) = stroke.into_xopp(document.format.dpi()) else {
+                                continue;
+                            };
+                            let xopp_layer = layers.entry(stroke_layer).or_insert_with(|| {
+                                let user_layer_name = match stroke_layer {
+                                    StrokeLayer::UserLayer(i) => user_layers
+                                        .get(i as usize)
+                                        .map(|user_layer| user_layer.name.as_str()),
+                                    _ => None,
+                                };
+                                let name = crate::utils::xopp_layer_name_from_stroke_layer(
+                                    stroke_layer,
+                                    user_layer_name,
+                                );
+                                xoppformat::XoppLayer {
+                                    name: Some(name),
+                                    ..Default::default()
+                                }
+                            });
+
+                            match xopp_stroke {
+                                xoppformat::XoppStrokeType::XoppStroke(xoppstroke) => {
+                                    xopp_layer.strokes.push(xoppstroke)
+                                }
+                                xoppformat::XoppStrokeType::XoppText(xopptext) => {
+                                    xopp_layer.texts.push(xopptext)
+                                }
+                                xoppformat::XoppStrokeType::XoppImage(xoppimage) => {
+                                    xopp_layer.images.push(xoppimage)
+                                }
+                            }
+                        }
+
                         let page_dimensions = crate::utils::convert_coord_dpi(
                             page_bounds.extents(),
                             document.format.dpi(),
                             xoppformat::XoppFile::DPI,
                         );
 
-                        Some(xoppformat::XoppPage {
+                        xoppformat::XoppPage {
                             width: page_dimensions[0],
                             height: page_dimensions[1],
-                            background: xopp_background.clone(),
-                            layers: vec![image_layer, strokes_layer],
-                        })
+                            background: xopp_background,
+                            layers: layers.into_values().collect(),
+                        }
                     })
                     .collect::<Vec<xoppformat::XoppPage>>();
 
@@ -677,6 +811,73 @@ impl Engine {
         oneshot_receiver
     }
 
+    /// Export the background images that are attached to the Xournal++ .xopp file of the document.
+    ///
+    /// Returns the file names and the Png bytes of the images,
+    /// which are expected to be written next to the xopp file with the given file name.
+    pub fn export_doc_xopp_attachments(
+        &self,
+        xopp_file_name: &str,
+        doc_export_prefs_override: Option<DocExportPrefs>,
+    ) -> oneshot::Receiver<Result<Vec<(String, Vec<u8>)>, anyhow::Error>> {
+        let (oneshot_sender, oneshot_receiver) =
+            oneshot::channel::<anyhow::Result<Vec<(String, Vec<u8>)>>>();
+        let doc_export_prefs =
+            doc_export_prefs_override.unwrap_or(self.export_prefs.doc_export_prefs);
+        let mut attachments = Vec::<(String, BackgroundImage)>::new();
+        for page_bounds in self.pages_bounds_w_content(doc_export_prefs.page_order) {
+            let (background, attachment) = self.xopp_page_background(page_bounds);
+            if let (Some(image), Some(filename)) = (background.image, attachment) {
+                if !attachments.iter().any(|(name, _)| *name == filename) {
+                    attachments.push((filename, image));
+                }
+            }
+        }
+        let xopp_file_name = xopp_file_name.to_string();
+
+        rayon::spawn(move || {
+            let result = || -> anyhow::Result<Vec<(String, Vec<u8>)>> {
+                attachments
+                    .into_iter()
+                    .map(|(filename, image)| {
+                        Ok((
+                            format!("{xopp_file_name}.{filename}"),
+                            image.to_png_bytes()?,
+                        ))
+                    })
+                    .collect()
+            };
+
+            if oneshot_sender.send(result()).is_err() {
+                error!(
+                    "Sending result to receiver failed while exporting xopp attachments. Receiver already dropped."
+                );
+            }
+        });
+
+        oneshot_receiver
+    }
+
+    /// The background of the page with the given bounds for exporting it to a Xournal++ .xopp file,
+    /// together with the name of the attached image file if the background has an image.
+    ///
+    /// Pages without a background override share the attached image of the document background.
+    fn xopp_page_background(&self, page_bounds: Aabb) -> (Background, Option<String>) {
+        let page = self
+            .document
+            .page_index_for_pos(page_bounds.center().coords);
+        let background = page
+            .map(|page| self.page_background(page))
+            .unwrap_or_else(|| self.document.background.clone());
+        let override_page =
+            page.filter(|&page| self.store.page_background_override(page).is_some());
+        let attachment = background.image.as_ref().map(|_| match override_page {
+            Some(page) => format!("bg_{}.png", page + 1),
+            None => String::from("bg.png"),
+        });
+        (background, attachment)
+    }
+
     /// Export the document pages.
     pub fn export_doc_pages(
         &self,
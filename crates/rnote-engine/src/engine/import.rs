@@ -20,6 +20,7 @@ use tracing::error;
 #[derive(
     Debug, Clone, Copy, Serialize, Deserialize, num_derive::FromPrimitive, num_derive::ToPrimitive,
 )]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
 #[serde(rename = "pdf_import_pages_type")]
 pub enum PdfImportPagesType {
     #[serde(rename = "bitmap")]
@@ -50,6 +51,7 @@ impl TryFrom<u32> for PdfImportPagesType {
 #[derive(
     Debug, Clone, Copy, Serialize, Deserialize, num_derive::FromPrimitive, num_derive::ToPrimitive,
 )]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
 #[serde(rename = "pdf_import_page_spacing")]
 pub enum PdfImportPageSpacing {
     #[serde(rename = "continuous")]
@@ -0,0 +1,110 @@
+// Imports
+use crate::store::UserLayer;
+use crate::{Engine, WidgetFlags};
+use std::time::Instant;
+
+impl Engine {
+    /// The user layers, ordered from bottom to top.
+    pub fn user_layers(&self) -> &[UserLayer] {
+        self.store.user_layers()
+    }
+
+    /// The index of the active user layer, which new strokes are inserted into.
+    pub fn active_user_layer(&self) -> u32 {
+        self.store.active_user_layer()
+    }
+
+    /// Set the active user layer.
+    ///
+    /// The current selection is kept, but new strokes are inserted into the new active layer.
+    pub fn set_active_user_layer(&mut self, index: u32) -> WidgetFlags {
+        let mut widget_flags = WidgetFlags::default();
+        if self.store.set_active_user_layer(index) {
+            widget_flags.refresh_ui = true;
+        }
+        widget_flags
+    }
+
+    /// Add a new user layer on top of the existing ones and make it the active layer.
+    ///
+    /// When `name` is None, a default name is assigned.
+    pub fn add_user_layer(&mut self, name: Option<String>) -> WidgetFlags {
+        let index = self.store.add_user_layer(name);
+        self.store.set_active_user_layer(index);
+        let mut widget_flags = self.record(Instant::now());
+        widget_flags.refresh_ui = true;
+        widget_flags
+    }
+
+    /// Add the user layers of another document, mapping layers that are equal to an existing one to it.
+    ///
+    /// Returns the new index for each of the given layers, to remap the strokes of the other document with
+    /// [PageContent::remap_user_layers](crate::engine::PageContent::remap_user_layers).
+    /// The change is recorded together with the next operation, for example when replacing the pages.
+    pub fn import_user_layers(&mut self, user_layers: &[UserLayer]) -> Vec<u32> {
+        self.store.import_user_layers(user_layers)
+    }
+
+    /// Rename the user layer.
+    pub fn rename_user_layer(&mut self, index: u32, name: String) -> WidgetFlags {
+        let mut widget_flags = WidgetFlags::default();
+        if self.store.rename_user_layer(index, name) {
+            widget_flags |= self.record(Instant::now());
+            widget_flags.refresh_ui = true;
+        }
+        widget_flags
+    }
+
+    /// Show or hide the user layer.
+    ///
+    /// Strokes on hidden layers are not rendered, exported or selectable.
+    pub fn set_user_layer_visible(&mut self, index: u32, visible: bool) -> WidgetFlags {
+        let mut widget_flags = WidgetFlags::default();
+        if self.store.set_user_layer_visible(index, visible) {
+            widget_flags |= self.current_pen_update_state()
+                | self.record(Instant::now())
+                | self.update_rendering_current_viewport();
+            widget_flags.refresh_ui = true;
+        }
+        widget_flags
+    }
+
+    /// Lock or unlock the user layer.
+    ///
+    /// Strokes on locked layers can't be selected or erased.
+    pub fn set_user_layer_locked(&mut self, index: u32, locked: bool) -> WidgetFlags {
+        let mut widget_flags = WidgetFlags::default();
+        if self.store.set_user_layer_locked(index, locked) {
+            widget_flags |= self.current_pen_update_state()
+                | self.record(Instant::now())
+                | self.update_rendering_current_viewport();
+            widget_flags.refresh_ui = true;
+        }
+        widget_flags
+    }
+
+    /// Move the user layer from index `from` to index `to`, together with all its strokes.
+    pub fn move_user_layer(&mut self, from: u32, to: u32) -> WidgetFlags {
+        let mut widget_flags = WidgetFlags::default();
+        if self.store.move_user_layer(from, to) {
+            widget_flags |= self.record(Instant::now()) | self.update_rendering_current_viewport();
+            widget_flags.refresh_ui = true;
+        }
+        widget_flags
+    }
+
+    /// Remove the user layer and all strokes on it.
+    ///
+    /// The last remaining user layer can't be removed.
+    pub fn remove_user_layer(&mut self, index: u32) -> WidgetFlags {
+        let mut widget_flags = WidgetFlags::default();
+        if self.store.remove_user_layer(index) {
+            widget_flags |= self.current_pen_update_state()
+                | self.doc_resize_autoexpand()
+                | self.record(Instant::now())
+                | self.update_rendering_current_viewport();
+            widget_flags.refresh_ui = true;
+        }
+        widget_flags
+    }
+}
@@ -1,23 +1,31 @@
 // Modules
+pub mod arrange;
 pub mod export;
 pub mod import;
+pub mod layers;
+pub mod pages;
 pub mod rendering;
 pub mod snapshot;
 pub mod strokecontent;
+pub mod template;
 pub mod visual_debug;
 
 // Re-exports
+pub use arrange::{Alignment, Distribution};
 pub use export::ExportPrefs;
 use futures::channel::mpsc::UnboundedReceiver;
 use futures::StreamExt;
 pub use import::ImportPrefs;
+pub use pages::PageContent;
 pub use snapshot::EngineSnapshot;
 pub use strokecontent::StrokeContent;
+pub use template::TemplateValues;
 
 // Imports
 use crate::document::Layout;
 use crate::pens::{Pen, PenStyle};
 use crate::pens::{PenMode, PensConfig};
+use crate::recognition::HandwritingRecognizer;
 use crate::store::render_comp::{self, RenderCompState};
 use crate::store::StrokeKey;
 use crate::strokes::content::GeneratedContentImages;
@@ -176,6 +184,8 @@ pub struct Engine {
     audioplayer: Option<AudioPlayer>,
     #[serde(skip)]
     visual_debug: bool,
+    #[serde(skip)]
+    handwriting_recognizer: Option<Arc<dyn HandwritingRecognizer>>,
     // the task sender. Must not be modified, only cloned.
     #[serde(skip)]
     tasks_tx: EngineTaskSender,
@@ -184,6 +194,9 @@ pub struct Engine {
     // Background rendering
     #[serde(skip)]
     background_tile_image: Option<render::Image>,
+    /// The tile images of the pages with a background override, together with their page index.
+    #[serde(skip)]
+    page_background_tile_images: Vec<(u32, render::Image)>,
     #[cfg(feature = "ui")]
     #[serde(skip)]
     background_rendernodes: Vec<gtk4::gsk::RenderNode>,
@@ -212,9 +225,11 @@ impl Default for Engine {
 
             audioplayer: None,
             visual_debug: false,
+            handwriting_recognizer: None,
             tasks_tx: EngineTaskSender(tasks_tx),
             tasks_rx: Some(EngineTaskReceiver(tasks_rx)),
             background_tile_image: None,
+            page_background_tile_images: Vec::default(),
             #[cfg(feature = "ui")]
             background_rendernodes: Vec::default(),
             origin_indicator_image: None,
@@ -226,6 +241,8 @@ impl Default for Engine {
 
 impl Engine {
     pub(crate) const STROKE_BOUNDS_INTERSECTION_TOLERANCE: f64 = 1e-3;
+    /// The default tolerance when simplifying brush strokes.
+    pub const SIMPLIFY_TOLERANCE_DEFAULT: f64 = 0.5;
 
     pub fn engine_tasks_tx(&self) -> EngineTaskSender {
         self.tasks_tx.clone()
@@ -307,6 +324,21 @@ impl Engine {
         widget_flags
     }
 
+    /// The handwriting recognizer, if one is set.
+    pub fn handwriting_recognizer(&self) -> Option<Arc<dyn HandwritingRecognizer>> {
+        self.handwriting_recognizer.clone()
+    }
+
+    /// Set the handwriting recognizer.
+    ///
+    /// When set, the recognized text of the brush strokes is embedded as invisible text layer when exporting as Pdf.
+    pub fn set_handwriting_recognizer(
+        &mut self,
+        handwriting_recognizer: Option<Arc<dyn HandwritingRecognizer>>,
+    ) {
+        self.handwriting_recognizer = handwriting_recognizer;
+    }
+
     /// Takes a snapshot of the current state.
     pub fn take_snapshot(&self) -> EngineSnapshot {
         let mut store_history_entry = self.store.create_history_entry();
@@ -323,17 +355,21 @@ impl Engine {
         }
 
         EngineSnapshot {
-            document: self.document.clone_config(),
+            document: self.document.clone(),
             camera: self.camera.clone_config(),
             stroke_components: Arc::clone(&store_history_entry.stroke_components),
             chrono_components: Arc::clone(&store_history_entry.chrono_components),
             chrono_counter: store_history_entry.chrono_counter,
+            group_components: Arc::clone(&store_history_entry.group_components),
+            user_layers: Arc::clone(&store_history_entry.user_layers),
+            active_user_layer: self.store.active_user_layer(),
+            page_backgrounds: Arc::clone(&store_history_entry.page_backgrounds),
         }
     }
 
     /// Imports an engine snapshot. A save file should always be loaded with this method.
     pub fn load_snapshot(&mut self, snapshot: EngineSnapshot) -> WidgetFlags {
-        self.document = snapshot.document.clone_config();
+        self.document = snapshot.document.clone();
         self.camera = snapshot.camera.clone_config();
         let mut widget_flags = self.store.import_from_snapshot(&snapshot)
             | self.doc_resize_autoexpand()
@@ -357,18 +393,43 @@ impl Engine {
 
     /// Undo the latest changes.
     pub fn undo(&mut self, now: Instant) -> WidgetFlags {
-        self.store.undo(now)
-            | self.doc_resize_autoexpand()
+        let page_backgrounds = self.store.page_backgrounds_handle();
+        let doc_height_change = self
+            .store
+            .can_undo()
+            .then(|| self.store.live_doc_height_change())
+            .flatten();
+        let mut widget_flags = self.store.undo(now);
+        if let Some((height_before, _)) = doc_height_change {
+            widget_flags |= self.doc_restore_height_fixed_size(height_before);
+        }
+        widget_flags |= self.doc_resize_autoexpand()
             | self.current_pen_update_state()
-            | self.update_rendering_current_viewport()
+            | self.update_rendering_current_viewport();
+        if !Arc::ptr_eq(&page_backgrounds, &self.store.page_backgrounds_handle()) {
+            widget_flags |= self.background_rendering_regenerate();
+        }
+        widget_flags
     }
 
     /// Redo the latest changes.
     pub fn redo(&mut self, now: Instant) -> WidgetFlags {
-        self.store.redo(now)
-            | self.doc_resize_autoexpand()
+        let page_backgrounds = self.store.page_backgrounds_handle();
+        let redone = self.store.can_redo();
+        let mut widget_flags = self.store.redo(now);
+        let doc_height_change = redone
+            .then(|| self.store.live_doc_height_change())
+            .flatten();
+        if let Some((_, height_after)) = doc_height_change {
+            widget_flags |= self.doc_restore_height_fixed_size(height_after);
+        }
+        widget_flags |= self.doc_resize_autoexpand()
             | self.current_pen_update_state()
-            | self.update_rendering_current_viewport()
+            | self.update_rendering_current_viewport();
+        if !Arc::ptr_eq(&page_backgrounds, &self.store.page_backgrounds_handle()) {
+            widget_flags |= self.background_rendering_regenerate();
+        }
+        widget_flags
     }
 
     pub fn can_undo(&self) -> bool {
@@ -812,8 +873,13 @@ impl Engine {
 
     pub fn select_all_strokes(&mut self) -> WidgetFlags {
         let widget_flags = self.change_pen_style(PenStyle::Selector);
-        self.store
-            .set_selected_keys(&self.store.stroke_keys_as_rendered(), true);
+        let select = self
+            .store
+            .stroke_keys_as_rendered()
+            .into_iter()
+            .filter(|&key| self.store.stroke_editable(key))
+            .collect::<Vec<StrokeKey>>();
+        self.store.set_selected_keys(&select, true);
         widget_flags
             | self.current_pen_update_state()
             | self.doc_resize_autoexpand()
@@ -842,7 +908,10 @@ impl Engine {
             SelectionCollision::Intersects => self
                 .store
                 .stroke_keys_as_rendered_intersecting_bounds(bounds),
-        };
+        }
+        .into_iter()
+        .filter(|&key| self.store.stroke_editable(key))
+        .collect::<Vec<StrokeKey>>();
         self.store.set_selected_keys(&select, true);
         self.doc_resize_autoexpand()
             | self.record(Instant::now())
@@ -892,6 +961,161 @@ impl Engine {
             | self.update_content_rendering_current_viewport()
     }
 
+    /// Bring the selected strokes in front of all other strokes on their layers.
+    pub fn selection_bring_to_front(&mut self) -> WidgetFlags {
+        let changed = self
+            .store
+            .bring_strokes_to_front(&self.store.selection_keys_as_rendered());
+        self.finish_reorder(changed)
+    }
+
+    /// Send the selected strokes behind all other strokes on their layers.
+    pub fn selection_send_to_back(&mut self) -> WidgetFlags {
+        let changed = self
+            .store
+            .send_strokes_to_back(&self.store.selection_keys_as_rendered());
+        self.finish_reorder(changed)
+    }
+
+    /// Raise the selected strokes one step on their layers.
+    pub fn selection_raise(&mut self) -> WidgetFlags {
+        let changed = self
+            .store
+            .raise_strokes(&self.store.selection_keys_as_rendered());
+        self.finish_reorder(changed)
+    }
+
+    /// Lower the selected strokes one step on their layers.
+    pub fn selection_lower(&mut self) -> WidgetFlags {
+        let changed = self
+            .store
+            .lower_strokes(&self.store.selection_keys_as_rendered());
+        self.finish_reorder(changed)
+    }
+
+    fn finish_reorder(&mut self, changed: bool) -> WidgetFlags {
+        let mut widget_flags = WidgetFlags::default();
+        if changed {
+            widget_flags.redraw = true;
+            widget_flags.store_modified = true;
+            widget_flags |= self.record(Instant::now());
+        }
+        widget_flags
+    }
+
+    /// Group the selected strokes, nesting already existing groups inside the new group.
+    pub fn group_selection(&mut self) -> WidgetFlags {
+        let mut widget_flags = WidgetFlags::default();
+        if self
+            .store
+            .group_strokes(&self.store.selection_keys_as_rendered())
+        {
+            widget_flags.store_modified = true;
+            widget_flags |= self.record(Instant::now());
+        }
+        widget_flags
+    }
+
+    /// Ungroup the selected strokes, removing their outermost group.
+    pub fn ungroup_selection(&mut self) -> WidgetFlags {
+        let mut widget_flags = WidgetFlags::default();
+        if self
+            .store
+            .ungroup_strokes(&self.store.selection_keys_as_rendered())
+        {
+            widget_flags.store_modified = true;
+            widget_flags |= self.record(Instant::now());
+        }
+        widget_flags
+    }
+
+    /// Connect the two selected strokes with a connector, styled with the current shaper options.
+    ///
+    /// Does nothing if not exactly two strokes are selected.
+    pub fn connect_selection(&mut self) -> WidgetFlags {
+        let mut widget_flags = WidgetFlags::default();
+        let selection_keys = self.store.selection_keys_as_rendered();
+        let &[start_key, end_key] = selection_keys.as_slice() else {
+            return widget_flags;
+        };
+        let style = self
+            .pens_config
+            .shaper_config
+            .gen_style_for_current_options();
+        if self
+            .store
+            .insert_connector(start_key, end_key, style)
+            .is_none()
+        {
+            return widget_flags;
+        }
+        widget_flags.store_modified = true;
+
+        widget_flags
+            | self.current_pen_update_state()
+            | self.record(Instant::now())
+            | self.update_content_rendering_current_viewport()
+    }
+
+    /// Smooth the selected brush strokes, removing jitter and reducing their segments.
+    pub fn smooth_selection_brushstrokes(&mut self) -> WidgetFlags {
+        self.store
+            .smooth_brushstrokes(&self.store.selection_keys_as_rendered())
+            | self.current_pen_update_state()
+            | self.record(Instant::now())
+            | self.update_content_rendering_current_viewport()
+    }
+
+    /// Simplify the selected brush strokes with the given tolerance.
+    ///
+    /// Returns the number of removed elements.
+    pub fn simplify_selection_brushstrokes(&mut self, tolerance: f64) -> (usize, WidgetFlags) {
+        let (removed, widget_flags) = self
+            .store
+            .simplify_brushstrokes(&self.store.selection_keys_as_rendered(), tolerance);
+        (removed, self.finish_simplify(removed, widget_flags))
+    }
+
+    /// Simplify all brush strokes of the document with the given tolerance.
+    /// Strokes on hidden or locked layers are left untouched.
+    ///
+    /// Returns the number of removed elements.
+    pub fn simplify_doc_brushstrokes(&mut self, tolerance: f64) -> (usize, WidgetFlags) {
+        let keys = self
+            .store
+            .stroke_keys_unordered()
+            .into_iter()
+            .filter(|&key| self.store.stroke_editable(key))
+            .collect::<Vec<StrokeKey>>();
+        let (removed, widget_flags) = self.store.simplify_brushstrokes(&keys, tolerance);
+        (removed, self.finish_simplify(removed, widget_flags))
+    }
+
+    /// Replace the selected brush strokes that are recognized as shapes with shape strokes
+    /// of the same color and width.
+    ///
+    /// Returns the number of replaced strokes.
+    pub fn recognize_shapes_in_selection(&mut self) -> (usize, WidgetFlags) {
+        let (replaced, mut widget_flags) = self
+            .store
+            .recognize_shapes_in_brushstrokes(&self.store.selection_keys_as_rendered());
+        if replaced > 0 {
+            widget_flags |= self.current_pen_update_state()
+                | self.record(Instant::now())
+                | self.update_content_rendering_current_viewport();
+        }
+        (replaced, widget_flags)
+    }
+
+    fn finish_simplify(&mut self, removed: usize, mut widget_flags: WidgetFlags) -> WidgetFlags {
+        if removed > 0 {
+            widget_flags |= self.current_pen_update_state()
+                | self.record(Instant::now())
+                | self.update_content_rendering_current_viewport();
+        }
+        widget_flags
+    }
+
     pub fn text_selection_change_style<F>(&mut self, modify_func: F) -> WidgetFlags
     where
         F: FnOnce(&mut TextStyle),
@@ -1000,3 +1224,80 @@ impl Engine {
         widget_flags
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strokes::{BrushStroke, Stroke};
+    use rnote_compose::penpath::Element;
+    use rnote_compose::style::smooth::SmoothOptions;
+    use rnote_compose::{PenPath, Style};
+
+    fn insert_brushstroke(engine: &mut Engine, points: &[na::Vector2<f64>]) -> StrokeKey {
+        let path =
+            PenPath::try_from_elements(points.iter().map(|&pos| Element::new(pos, 0.5))).unwrap();
+        let style = Style::Smooth(SmoothOptions {
+            stroke_width: 4.0,
+            stroke_color: Some(Color::RED),
+            ..SmoothOptions::default()
+        });
+        engine.store.insert_stroke(
+            Stroke::BrushStroke(BrushStroke::from_penpath(path, style)),
+            None,
+        )
+    }
+
+    #[test]
+    fn recognize_shapes_in_selection() {
+        let mut engine = Engine::default();
+        let corners = [
+            na::vector![30.0, 0.0],
+            na::vector![100.0, 0.0],
+            na::vector![100.0, 50.0],
+            na::vector![0.0, 50.0],
+            na::vector![0.0, 0.0],
+            na::vector![30.0, 0.0],
+        ];
+        let rectangle = corners
+            .windows(2)
+            .flat_map(|w| (0..20).map(move |i| w[0] + (w[1] - w[0]) * (i as f64 / 20.0)))
+            .collect::<Vec<na::Vector2<f64>>>();
+        let scribble = (0..100)
+            .map(|i| {
+                let t = i as f64 * 0.3;
+                na::vector![t * 10.0, 200.0 + (t * 3.0).sin() * 30.0]
+            })
+            .collect::<Vec<na::Vector2<f64>>>();
+        let rectangle_key = insert_brushstroke(&mut engine, &rectangle);
+        let scribble_key = insert_brushstroke(&mut engine, &scribble);
+        engine
+            .store
+            .set_selected_keys(&[rectangle_key, scribble_key], true);
+        let _ = engine.record(Instant::now());
+
+        let (replaced, widget_flags) = engine.recognize_shapes_in_selection();
+        assert_eq!(replaced, 1);
+        assert!(widget_flags.store_modified);
+        let Some(Stroke::ShapeStroke(shapestroke)) = engine.store.get_stroke_ref(rectangle_key)
+        else {
+            panic!("rectangle stroke was not replaced with a shape stroke");
+        };
+        assert_eq!(shapestroke.style.stroke_width(), 4.0);
+        assert_eq!(shapestroke.style.stroke_color(), Some(Color::RED));
+        assert!(matches!(
+            engine.store.get_stroke_ref(scribble_key),
+            Some(Stroke::BrushStroke(_))
+        ));
+
+        // Nothing left to recognize, so no history entry is recorded
+        let (replaced, widget_flags) = engine.recognize_shapes_in_selection();
+        assert_eq!(replaced, 0);
+        assert!(!widget_flags.store_modified);
+
+        let _ = engine.undo(Instant::now());
+        assert!(matches!(
+            engine.store.get_stroke_ref(rectangle_key),
+            Some(Stroke::BrushStroke(_))
+        ));
+    }
+}
@@ -0,0 +1,500 @@
+// Imports
+use crate::document::{Background, Layout};
+use crate::store::chrono_comp::StrokeLayer;
+use crate::store::{GroupComponent, StrokeKey};
+use crate::strokes::Stroke;
+use crate::{Engine, WidgetFlags};
+use p2d::bounding_volume::{Aabb, BoundingVolume};
+use rnote_compose::shapes::Shapeable;
+use rnote_compose::transform::Transformable;
+use rnote_compose::SplitOrder;
+use std::collections::HashMap;
+use std::time::Instant;
+
+/// The content of a single page of the document.
+#[derive(Debug, Clone)]
+pub struct PageContent {
+    /// The bounds of the page.
+    pub bounds: Aabb,
+    /// The strokes on the page, together with the layers they are on.
+    pub strokes: Vec<(Stroke, StrokeLayer)>,
+    /// The keys the strokes had in the document they were extracted from, in the same order as the strokes.
+    ///
+    /// Connectors stay attached to the strokes with these keys when the pages are placed in a document.
+    pub keys: Vec<StrokeKey>,
+    /// The groups of the strokes, in the same order as the strokes.
+    pub group_components: Vec<GroupComponent>,
+    /// Identifies the document the page was extracted from.
+    ///
+    /// The stroke keys and groups of pages with different origins are unrelated, even if they are equal.
+    pub origin: usize,
+    /// The background that overrides the document background for the page, if there is one.
+    pub background: Option<Background>,
+}
+
+impl PageContent {
+    /// Move the strokes on user layers to the layers given by the map, which holds the new index for each
+    /// old index, as returned by [Engine::import_user_layers].
+    pub fn remap_user_layers(&mut self, map: &[u32]) {
+        for (_, layer) in self.strokes.iter_mut() {
+            if let StrokeLayer::UserLayer(index) = *layer {
+                if let Some(&new_index) = map.get(index as usize) {
+                    *layer = StrokeLayer::UserLayer(new_index);
+                }
+            }
+        }
+    }
+}
+
+/// The index of the page that contains the center of the bounds, or else the nearest page.
+pub(crate) fn page_index_for_bounds(pages_bounds: &[Aabb], bounds: Aabb) -> Option<usize> {
+    let center = bounds.center();
+
+    pages_bounds
+        .iter()
+        .map(|page_bounds| {
+            let dx = (page_bounds.mins[0] - center[0])
+                .max(center[0] - page_bounds.maxs[0])
+                .max(0.0);
+            let dy = (page_bounds.mins[1] - center[1])
+                .max(center[1] - page_bounds.maxs[1])
+                .max(0.0);
+            dx * dx + dy * dy
+        })
+        .enumerate()
+        .min_by(|(_, first), (_, second)| first.total_cmp(second))
+        .map(|(i, _)| i)
+}
+
+impl Engine {
+    /// Generate bounds for each page of the document.
+    ///
+    /// In fixed-size layout these are all pages of the document, including empty ones.
+    /// In all other layouts the document has no fixed pages, so only the pages with content are returned.
+    pub fn doc_pages_bounds(&self, split_order: SplitOrder) -> Vec<Aabb> {
+        match self.document.layout {
+            Layout::FixedSize => self.document.pages_bounds(split_order),
+            Layout::ContinuousVertical | Layout::SemiInfinite | Layout::Infinite => {
+                self.pages_bounds_w_content(split_order)
+            }
+        }
+    }
+
+    /// Extract the content of the given pages.
+    ///
+    /// Every stroke that is not trashed is assigned to exactly one page, the one that contains the center of its
+    /// bounds, or else the nearest one. Strokes on hidden layers are included.
+    pub fn extract_pages(&self, pages_bounds: &[Aabb]) -> Vec<PageContent> {
+        let mut pages = pages_bounds
+            .iter()
+            .map(|&bounds| PageContent {
+                bounds,
+                strokes: vec![],
+                keys: vec![],
+                group_components: vec![],
+                origin: 0,
+                background: self
+                    .document
+                    .page_index_for_pos(bounds.center().coords)
+                    .and_then(|page| self.store.page_background_override(page)),
+            })
+            .collect::<Vec<PageContent>>();
+
+        for key in self.store.keys_sorted_chrono() {
+            if self.store.trashed(key).unwrap_or(false) {
+                continue;
+            }
+            let (Some(stroke), Some(layer)) =
+                (self.store.get_stroke_ref(key), self.store.stroke_layer(key))
+            else {
+                continue;
+            };
+            if let Some(i) = page_index_for_bounds(pages_bounds, stroke.bounds()) {
+                pages[i].strokes.push((stroke.clone(), layer));
+                pages[i].keys.push(key);
+            }
+        }
+        for page in pages.iter_mut() {
+            page.group_components = self.store.clone_group_components(&page.keys);
+        }
+
+        pages
+    }
+
+    /// Replace the content of the document with the given pages.
+    ///
+    /// The pages are placed one below the other, starting at the origin, and the strokes and page backgrounds are
+    /// moved along with them. Pages that are higher than the format of the document, for example from a document with
+    /// another format, take up as many pages as needed so that they don't overlap with the following pages.
+    /// In fixed-size layout the document is resized to hold exactly the given pages, including empty ones.
+    pub fn replace_pages(&mut self, pages: Vec<PageContent>) -> WidgetFlags {
+        let mut widget_flags = WidgetFlags::default();
+        let format_height = self.document.format.height();
+        let mut n_pages = 0;
+        // The inserted keys and the map from the previous to the inserted keys, per origin of the pages
+        let mut origins = HashMap::<usize, (Vec<StrokeKey>, HashMap<StrokeKey, StrokeKey>)>::new();
+
+        let all_keys = self.store.stroke_keys_unordered();
+        self.store.set_trashed_keys(&all_keys, true);
+
+        self.store.clear_page_backgrounds();
+
+        for page in pages.into_iter() {
+            self.store
+                .set_page_background_override(n_pages as u32, page.background);
+            let offset = na::vector![0.0, n_pages as f64 * format_height] - page.bounds.mins.coords;
+            let (inserted_keys, key_map) = origins.entry(page.origin).or_default();
+            for (i, (mut stroke, layer)) in page.strokes.into_iter().enumerate() {
+                stroke.translate(offset);
+                let key = self.store.insert_stroke(stroke, Some(layer));
+                if let Some(&previous_key) = page.keys.get(i) {
+                    key_map.insert(previous_key, key);
+                }
+                if let Some(group_comp) = page.group_components.get(i) {
+                    self.store.set_group_component(key, group_comp.clone());
+                }
+                inserted_keys.push(key);
+            }
+            // Tolerates rounding errors of the page bounds
+            let page_height = page.bounds.extents()[1] / format_height - 1e-6;
+            n_pages += (page_height.ceil() as usize).max(1);
+        }
+        self.store.ensure_user_layers_for_strokes();
+
+        for (inserted_keys, key_map) in origins.values() {
+            self.store.update_geometry_for_strokes(inserted_keys);
+            // Connectors stay attached to the strokes of the same origin that were placed together with them
+            self.store.remap_connectors(inserted_keys, key_map);
+            // Groups of different origins are not joined
+            self.store.regroup_with_unused_ids(inserted_keys);
+        }
+
+        if self.document.layout == Layout::FixedSize {
+            self.document.x = 0.0;
+            self.document.y = 0.0;
+            self.document.width = self.document.format.width();
+            self.doc_set_height_fixed_size(n_pages.max(1) as f64 * format_height);
+            widget_flags.resize = true;
+        } else {
+            widget_flags |= self.doc_resize_to_fit_content();
+        }
+
+        widget_flags |= self.current_pen_update_state()
+            | self.record(Instant::now())
+            | self.background_rendering_regenerate()
+            | self.update_rendering_current_viewport();
+        widget_flags.store_modified = true;
+        widget_flags
+    }
+
+    /// The background that overrides the document background for the page (starting at 0), if there is one.
+    pub fn page_background_override(&self, page: u32) -> Option<Background> {
+        self.store.page_background_override(page)
+    }
+
+    /// The background of the page (starting at 0). Either its override or the document background.
+    pub fn page_background(&self, page: u32) -> Background {
+        if !self.document.has_pages() {
+            return self.document.background.clone();
+        }
+        self.store
+            .page_background_override(page)
+            .unwrap_or_else(|| self.document.background.clone())
+    }
+
+    /// The pages, their bounds and backgrounds for the page background overrides that intersect the given bounds.
+    ///
+    /// Empty if the layout has no pages.
+    pub(crate) fn page_backgrounds_in_bounds(&self, bounds: Aabb) -> Vec<(u32, Aabb, Background)> {
+        if !self.document.has_pages() {
+            return vec![];
+        }
+        self.store
+            .page_backgrounds()
+            .iter()
+            .filter_map(|page_background| {
+                let page_bounds = self.document.page_bounds(page_background.page);
+                page_bounds.intersects(&bounds).then_some((
+                    page_background.page,
+                    page_bounds,
+                    page_background.background.clone(),
+                ))
+            })
+            .collect()
+    }
+
+    /// Set the background that overrides the document background for the page (starting at 0),
+    /// or remove it when `background` is None.
+    ///
+    /// Only honored in layouts that have pages.
+    pub fn set_page_background_override(
+        &mut self,
+        page: u32,
+        background: Option<Background>,
+    ) -> WidgetFlags {
+        self.store.set_page_background_override(page, background);
+        let mut widget_flags = self.record(Instant::now()) | self.background_rendering_regenerate();
+        widget_flags.store_modified = true;
+        widget_flags
+    }
+
+    /// The keys of the strokes on each page of the document when in fixed-size layout.
+    ///
+    /// Every stroke that is not trashed is assigned to the page that contains the center of its bounds,
+    /// or else the nearest one.
+    fn keys_per_page_fixed_size(&self) -> Vec<Vec<StrokeKey>> {
+        let pages_bounds = self.document.pages_bounds(SplitOrder::default());
+        let mut keys_per_page = vec![vec![]; pages_bounds.len()];
+
+        for key in self.store.keys_sorted_chrono() {
+            if self.store.trashed(key).unwrap_or(false) {
+                continue;
+            }
+            let Some(stroke) = self.store.get_stroke_ref(key) else {
+                continue;
+            };
+            if let Some(i) = page_index_for_bounds(&pages_bounds, stroke.bounds()) {
+                keys_per_page[i].push(key);
+            }
+        }
+
+        keys_per_page
+    }
+
+    /// Translate the strokes vertically by the given number of pages.
+    fn translate_strokes_by_pages(&mut self, keys: &[StrokeKey], n_pages: f64) {
+        let offset = na::vector![0.0, n_pages * self.document.format.height()];
+        self.store.translate_strokes(keys, offset);
+        self.store.translate_strokes_images(keys, offset);
+        self.store.update_geometry_for_strokes(keys);
+    }
+
+    /// Set the height of the fixed-size document as part of the page operation that is recorded next,
+    /// so that it is restored when the operation is undone or redone.
+    fn doc_set_height_fixed_size(&mut self, height: f64) {
+        self.store
+            .set_doc_height_change(self.document.height, height);
+        self.document.height = height;
+    }
+
+    /// Restore the height of the fixed-size document after a page operation was undone or redone.
+    pub(crate) fn doc_restore_height_fixed_size(&mut self, height: f64) -> WidgetFlags {
+        let mut widget_flags = WidgetFlags::default();
+        if self.document.layout != Layout::FixedSize || self.document.height == height {
+            return widget_flags;
+        }
+        self.document.height = height;
+        widget_flags |= self.background_rendering_regenerate();
+        widget_flags.resize = true;
+        widget_flags
+    }
+
+    /// Finish a page operation as a single history entry.
+    fn finish_page_operation(&mut self) -> WidgetFlags {
+        let mut widget_flags = self.current_pen_update_state()
+            | self.record(Instant::now())
+            | self.background_rendering_regenerate()
+            | self.update_rendering_current_viewport();
+        widget_flags.resize = true;
+        widget_flags.store_modified = true;
+        widget_flags
+    }
+
+    /// Insert a blank page after the given page (starting at 0). The strokes on all following pages are moved down.
+    ///
+    /// Document layout must be set to fixed-size.
+    pub fn doc_insert_page_fixed_size(&mut self, after: usize) -> WidgetFlags {
+        if self.document.layout != Layout::FixedSize {
+            return WidgetFlags::default();
+        }
+        let keys_per_page = self.keys_per_page_fixed_size();
+        if after >= keys_per_page.len() {
+            return WidgetFlags::default();
+        }
+        let following_keys = keys_per_page[after + 1..].concat();
+
+        self.translate_strokes_by_pages(&following_keys, 1.0);
+        self.store.page_backgrounds_insert_page(after as u32 + 1);
+        self.doc_set_height_fixed_size(self.document.height + self.document.format.height());
+        self.finish_page_operation()
+    }
+
+    /// Delete the given page (starting at 0) together with all strokes on it and its background.
+    /// The strokes on all following pages are moved up. The last remaining page can't be deleted.
+    ///
+    /// Document layout must be set to fixed-size.
+    pub fn doc_delete_page_fixed_size(&mut self, page: usize) -> WidgetFlags {
+        if self.document.layout != Layout::FixedSize {
+            return WidgetFlags::default();
+        }
+        let keys_per_page = self.keys_per_page_fixed_size();
+        if page >= keys_per_page.len() || keys_per_page.len() <= 1 {
+            return WidgetFlags::default();
+        }
+        let following_keys = keys_per_page[page + 1..].concat();
+
+        self.store.set_selected_keys(&keys_per_page[page], false);
+        self.store.set_trashed_keys(&keys_per_page[page], true);
+        self.translate_strokes_by_pages(&following_keys, -1.0);
+        self.store.page_backgrounds_remove_page(page as u32);
+        self.doc_set_height_fixed_size(self.document.height - self.document.format.height());
+        self.finish_page_operation()
+    }
+
+    /// Duplicate the given page (starting at 0) together with all strokes on it and its background. The copy is inserted after the page
+    /// and the strokes on all following pages are moved down.
+    ///
+    /// Document layout must be set to fixed-size.
+    pub fn doc_duplicate_page_fixed_size(&mut self, page: usize) -> WidgetFlags {
+        if self.document.layout != Layout::FixedSize {
+            return WidgetFlags::default();
+        }
+        let keys_per_page = self.keys_per_page_fixed_size();
+        if page >= keys_per_page.len() {
+            return WidgetFlags::default();
+        }
+        let following_keys = keys_per_page[page + 1..].concat();
+        let offset = na::vector![0.0, self.document.format.height()];
+        let copies = keys_per_page[page]
+            .iter()
+            .filter_map(|&key| {
+                Some((
+                    key,
+                    self.store.get_stroke_ref(key)?.clone(),
+                    self.store.stroke_layer(key)?,
+                ))
+            })
+            .collect::<Vec<(StrokeKey, Stroke, StrokeLayer)>>();
+        let group_components = self.store.clone_group_components(
+            &copies
+                .iter()
+                .map(|(key, ..)| *key)
+                .collect::<Vec<StrokeKey>>(),
+        );
+
+        self.translate_strokes_by_pages(&following_keys, 1.0);
+        let key_map = copies
+            .into_iter()
+            .zip(group_components)
+            .map(|((key, mut stroke, layer), group_comp)| {
+                stroke.translate(offset);
+                let inserted_key = self.store.insert_stroke(stroke, Some(layer));
+                self.store.set_group_component(inserted_key, group_comp);
+                (key, inserted_key)
+            })
+            .collect::<HashMap<StrokeKey, StrokeKey>>();
+        let inserted_keys = key_map.values().copied().collect::<Vec<StrokeKey>>();
+        self.store.update_geometry_for_strokes(&inserted_keys);
+        // The copied connectors are attached to the copies of the strokes on the page
+        self.store.remap_connectors(&inserted_keys, &key_map);
+        // The copies form groups of their own
+        self.store.regroup_with_unused_ids(&inserted_keys);
+        self.store.page_backgrounds_insert_page(page as u32 + 1);
+        self.store.set_page_background_override(
+            page as u32 + 1,
+            self.store.page_background_override(page as u32),
+        );
+        self.doc_set_height_fixed_size(self.document.height + self.document.format.height());
+        self.finish_page_operation()
+    }
+
+    /// Swap two pages (starting at 0), together with all strokes on them and their backgrounds.
+    ///
+    /// Document layout must be set to fixed-size.
+    pub fn doc_swap_pages_fixed_size(&mut self, first: usize, second: usize) -> WidgetFlags {
+        if self.document.layout != Layout::FixedSize {
+            return WidgetFlags::default();
+        }
+        let keys_per_page = self.keys_per_page_fixed_size();
+        if first >= keys_per_page.len() || second >= keys_per_page.len() || first == second {
+            return WidgetFlags::default();
+        }
+        let distance = second as f64 - first as f64;
+
+        self.translate_strokes_by_pages(&keys_per_page[first], distance);
+        self.translate_strokes_by_pages(&keys_per_page[second], -distance);
+        self.store
+            .page_backgrounds_swap_pages(first as u32, second as u32);
+        self.finish_page_operation()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strokes::BrushStroke;
+    use approx::assert_relative_eq;
+    use rnote_compose::penpath::{Element, Segment};
+    use rnote_compose::{PenPath, Style};
+
+    /// An engine with a fixed-size document of three pages and a stroke in the center of every page.
+    fn engine_w_three_pages() -> (Engine, Vec<StrokeKey>) {
+        let mut engine = Engine::default();
+        engine.document.layout = Layout::FixedSize;
+        engine.document.height = 3.0 * engine.document.format.height();
+        let keys = (0..3)
+            .map(|page| {
+                let center = na::vector![
+                    engine.document.format.width() * 0.5,
+                    (page as f64 + 0.5) * engine.document.format.height()
+                ];
+                let mut path = PenPath::new(Element::new(center, 0.5));
+                path.segments.push(Segment::LineTo {
+                    end: Element::new(center + na::vector![10.0, 10.0], 0.5),
+                });
+                engine.store.insert_stroke(
+                    Stroke::BrushStroke(BrushStroke::from_penpath(path, Style::default())),
+                    None,
+                )
+            })
+            .collect();
+        let _ = engine.record(Instant::now());
+        (engine, keys)
+    }
+
+    fn stroke_page(engine: &Engine, key: StrokeKey) -> usize {
+        let center = engine.store.get_stroke_ref(key).unwrap().bounds().center();
+        (center[1] / engine.document.format.height()) as usize
+    }
+
+    #[test]
+    fn delete_page_undo() {
+        let (mut engine, keys) = engine_w_three_pages();
+        let format_height = engine.document.format.height();
+
+        let _ = engine.doc_delete_page_fixed_size(1);
+        assert_relative_eq!(engine.document.height, 2.0 * format_height);
+        assert!(engine.store.trashed(keys[1]).unwrap());
+        assert_eq!(stroke_page(&engine, keys[2]), 1);
+
+        let _ = engine.undo(Instant::now());
+        assert_relative_eq!(engine.document.height, 3.0 * format_height);
+        assert!(!engine.store.trashed(keys[1]).unwrap());
+        assert_eq!(stroke_page(&engine, keys[2]), 2);
+
+        let _ = engine.redo(Instant::now());
+        assert_relative_eq!(engine.document.height, 2.0 * format_height);
+        assert_eq!(stroke_page(&engine, keys[2]), 1);
+    }
+
+    #[test]
+    fn insert_page_undo() {
+        let (mut engine, keys) = engine_w_three_pages();
+        let format_height = engine.document.format.height();
+
+        let _ = engine.doc_insert_page_fixed_size(0);
+        assert_relative_eq!(engine.document.height, 4.0 * format_height);
+        assert_eq!(stroke_page(&engine, keys[1]), 2);
+
+        let _ = engine.undo(Instant::now());
+        assert_relative_eq!(engine.document.height, 3.0 * format_height);
+        assert_eq!(stroke_page(&engine, keys[1]), 1);
+
+        // Inserting after the last page moves no strokes, but is still undoable
+        let _ = engine.doc_insert_page_fixed_size(2);
+        assert_relative_eq!(engine.document.height, 4.0 * format_height);
+
+        let _ = engine.undo(Instant::now());
+        assert_relative_eq!(engine.document.height, 3.0 * format_height);
+    }
+}
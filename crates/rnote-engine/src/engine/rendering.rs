@@ -15,8 +15,8 @@ impl Engine {
 
         #[cfg(feature = "ui")]
         {
-            use crate::ext::GrapheneRectExt;
-            use gtk4::{graphene, gsk, prelude::*};
+            use crate::ext::{GdkRGBAExt, GrapheneRectExt};
+            use gtk4::{gdk, graphene, gsk, prelude::*};
             use rnote_compose::ext::AabbExt;
             use rnote_compose::SplitOrder;
 
@@ -34,7 +34,9 @@ impl Engine {
                 };
 
                 for split_bounds in viewport.split_extended_origin_aligned(
-                    self.document.background.tile_size(),
+                    self.document
+                        .background
+                        .tile_size(self.document.format.size()),
                     SplitOrder::default(),
                 ) {
                     rendernodes.push(
@@ -47,6 +49,52 @@ impl Engine {
                 }
             }
 
+            for (page, page_bounds, background) in self.page_backgrounds_in_bounds(viewport) {
+                let Some(image) = self
+                    .page_background_tile_images
+                    .iter()
+                    .find_map(|(i, image)| (*i == page).then_some(image))
+                else {
+                    continue;
+                };
+                let Some(clip_bounds) = page_bounds.intersection(&viewport) else {
+                    continue;
+                };
+                let new_texture = match image.to_memtexture() {
+                    Ok(t) => t,
+                    Err(e) => {
+                        error!("Failed to generate memory-texture of page background tile image, Err: {e:?}");
+                        continue;
+                    }
+                };
+                let mut page_rendernodes: Vec<gsk::RenderNode> = vec![gsk::ColorNode::new(
+                    &gdk::RGBA::from_compose_color(background.color),
+                    &graphene::Rect::from_p2d_aabb(clip_bounds),
+                )
+                .upcast()];
+
+                for split_bounds in clip_bounds.split_extended_origin_aligned(
+                    background.tile_size(self.document.format.size()),
+                    SplitOrder::default(),
+                ) {
+                    page_rendernodes.push(
+                        gsk::TextureNode::new(
+                            &new_texture,
+                            &graphene::Rect::from_p2d_aabb(split_bounds),
+                        )
+                        .upcast(),
+                    );
+                }
+
+                rendernodes.push(
+                    gsk::ClipNode::new(
+                        gsk::ContainerNode::new(&page_rendernodes),
+                        &graphene::Rect::from_p2d_aabb(clip_bounds),
+                    )
+                    .upcast(),
+                );
+            }
+
             self.background_rendernodes = rendernodes;
         }
 
@@ -112,6 +160,7 @@ impl Engine {
         let mut widget_flags = WidgetFlags::default();
         self.store.clear_rendering();
         self.background_tile_image.take();
+        self.page_background_tile_images.clear();
         self.origin_indicator_image.take();
         #[cfg(feature = "ui")]
         {
@@ -128,7 +177,13 @@ impl Engine {
         let image_scale = self.camera.image_scale();
         let scale_factor = self.camera.scale_factor();
 
-        match self.document.background.gen_tile_image(image_scale) {
+        let format_size = self.document.format.size();
+
+        match self
+            .document
+            .background
+            .gen_tile_image(format_size, image_scale)
+        {
             Ok(image) => {
                 self.background_tile_image = Some(image);
             }
@@ -138,6 +193,24 @@ impl Engine {
             }
         }
 
+        self.page_background_tile_images = self
+            .store
+            .page_backgrounds()
+            .iter()
+            .filter_map(|page_background| {
+                match page_background
+                    .background
+                    .gen_tile_image(format_size, image_scale)
+                {
+                    Ok(image) => Some((page_background.page, image)),
+                    Err(e) => {
+                        error!("Regenerating page background tile image failed, Err: {e:?}");
+                        None
+                    }
+                }
+            })
+            .collect();
+
         match gen_origin_indicator_image(scale_factor) {
             Ok(image) => {
                 self.origin_indicator_image = Some(image);
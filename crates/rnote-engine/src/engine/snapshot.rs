@@ -1,16 +1,19 @@
 // Imports
 use crate::document::background;
+use crate::document::{BackgroundImage, BackgroundImageMode, PageBackground};
 use crate::engine::import::XoppImportPrefs;
-use crate::fileformats::{rnoteformat, xoppformat, FileFormatLoader};
-use crate::store::{ChronoComponent, StrokeKey};
+use crate::fileformats::{rnoteformat, xoppformat, FileFormatLoader, FileFormatSaver};
+use crate::store::chrono_comp::StrokeLayer;
+use crate::store::{ChronoComponent, GroupComponent, StrokeKey, UserLayer};
 use crate::strokes::Stroke;
 use crate::{Camera, Document, Engine};
 use anyhow::Context;
 use futures::channel::oneshot;
 use serde::{Deserialize, Serialize};
 use slotmap::{HopSlotMap, SecondaryMap};
+use std::path::{Component, Path, PathBuf};
 use std::sync::Arc;
-use tracing::error;
+use tracing::{error, warn};
 
 // An engine snapshot, used when loading/saving the current document from/into a file.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,6 +29,15 @@ pub struct EngineSnapshot {
     pub chrono_components: Arc<SecondaryMap<StrokeKey, Arc<ChronoComponent>>>,
     #[serde(rename = "chrono_counter")]
     pub chrono_counter: u32,
+    #[serde(rename = "group_components")]
+    pub group_components: Arc<SecondaryMap<StrokeKey, Arc<GroupComponent>>>,
+    /// Snapshots from older files don't have user layers, they get the default layers.
+    #[serde(rename = "user_layers")]
+    pub user_layers: Arc<Vec<UserLayer>>,
+    #[serde(rename = "active_user_layer")]
+    pub active_user_layer: u32,
+    #[serde(rename = "page_backgrounds")]
+    pub page_backgrounds: Arc<Vec<PageBackground>>,
 }
 
 impl Default for EngineSnapshot {
@@ -36,6 +48,10 @@ impl Default for EngineSnapshot {
             stroke_components: Arc::new(HopSlotMap::with_key()),
             chrono_components: Arc::new(SecondaryMap::new()),
             chrono_counter: 0,
+            group_components: Arc::new(SecondaryMap::new()),
+            user_layers: crate::store::user_layers::default_user_layers(),
+            active_user_layer: 0,
+            page_backgrounds: Arc::new(Vec::new()),
         }
     }
 }
@@ -63,11 +79,38 @@ impl EngineSnapshot {
 
         snapshot_receiver.await?
     }
+
+    /// Saves the snapshot as the bytes of a .rnote file.
+    pub fn save_as_rnote_bytes(
+        self,
+        file_name: String,
+    ) -> oneshot::Receiver<anyhow::Result<Vec<u8>>> {
+        let (oneshot_sender, oneshot_receiver) = oneshot::channel::<anyhow::Result<Vec<u8>>>();
+        rayon::spawn(move || {
+            let result = || -> anyhow::Result<Vec<u8>> {
+                let rnote_file = rnoteformat::RnoteFile {
+                    engine_snapshot: ijson::to_value(&self)?,
+                };
+                rnote_file.save_as_bytes(&file_name)
+            };
+            if oneshot_sender.send(result()).is_err() {
+                error!(
+                    "Sending result to receiver failed while saving snapshot as rnote bytes. Receiver already dropped."
+                );
+            }
+        });
+        oneshot_receiver
+    }
+
     /// Loads from the bytes of a Xournal++ .xopp file.
     ///
+    /// `xopp_file_path` is needed to load background images that are attached to the file.
+    /// They are only loaded from the directory of the file, background images with absolute paths are ignored.
+    ///
     /// To import this snapshot into the current engine, use [`Engine::load_snapshot()`].
     pub async fn load_from_xopp_bytes(
         bytes: Vec<u8>,
+        xopp_file_path: Option<PathBuf>,
         xopp_import_prefs: XoppImportPrefs,
     ) -> anyhow::Result<Self> {
         let (snapshot_sender, snapshot_receiver) = oneshot::channel::<anyhow::Result<Self>>();
@@ -124,13 +167,49 @@ impl EngineSnapshot {
                     ));
 
                 if let Some(first_page) = xopp_file.xopp_root.pages.first() {
-                    if let xoppformat::XoppBackgroundType::Solid {
-                        color: _color,
-                        style: _style,
-                    } = &first_page.background.bg_type
-                    {
-                        // Xopp background styles are not compatible with Rnotes, so everything is plain for now
-                        engine.document.background.pattern = background::PatternStyle::None;
+                    // Only the background of the first page is used, and styles without equivalent are plain
+                    match &first_page.background.bg_type {
+                        xoppformat::XoppBackgroundType::Solid { color, style } => {
+                            engine.document.background.color =
+                                crate::utils::color_from_xopp(*color);
+                            engine.document.background.pattern =
+                                background::PatternStyle::from_xopp_style(style);
+                        }
+                        xoppformat::XoppBackgroundType::Pixmap {
+                            domain: xoppformat::XoppBackgroundPixmapDomain::Attach,
+                            filename,
+                        } => {
+                            let image = xopp_file_path
+                                .as_deref()
+                                .and_then(|xopp_file_path| {
+                                    xopp_attached_pixmap_path(xopp_file_path, filename)
+                                })
+                                .ok_or_else(|| anyhow::anyhow!("No attached image file found"))
+                                .and_then(|path| {
+                                    BackgroundImage::load_from_file(
+                                        path,
+                                        BackgroundImageMode::Stretched,
+                                    )
+                                });
+                            match image {
+                                Ok(image) => {
+                                    engine.document.background.pattern =
+                                        background::PatternStyle::None;
+                                    engine.document.background.image = Some(image);
+                                }
+                                Err(e) => {
+                                    warn!("Loading pixmap background image '{filename}' of xopp file failed, Err: {e:?}");
+                                }
+                            }
+                        }
+                        xoppformat::XoppBackgroundType::Pixmap {
+                            domain: xoppformat::XoppBackgroundPixmapDomain::Absolute,
+                            filename,
+                        } => {
+                            // Files outside of the directory of the xopp file are never read
+                            warn!("Pixmap background image '{filename}' of xopp file with an absolute path is ignored");
+                        }
+                        _ => {}
                     }
                 }
 
@@ -138,13 +217,42 @@ impl EngineSnapshot {
                 let mut offset = na::Vector2::<f64>::zeros();
 
                 for page in xopp_file.xopp_root.pages.into_iter() {
+                    // Xournal++ layers that were exported by Rnote are mapped back to the layers they were exported from.
+                    // Other layers are mapped to user layers in their order on the page.
+                    let mut page_user_layers = 0;
+
                     for layers in page.layers.into_iter() {
+                        let reserved_layer = layers
+                            .name
+                            .as_deref()
+                            .and_then(crate::utils::stroke_layer_from_xopp_layer_name);
+                        let stroke_layer = match reserved_layer {
+                            Some(reserved_layer) => reserved_layer,
+                            None => {
+                                let (user_layer, name) = match layers
+                                    .name
+                                    .as_deref()
+                                    .and_then(crate::utils::user_layer_from_xopp_layer_name)
+                                {
+                                    Some((user_layer, name)) => (user_layer, name),
+                                    None => (page_user_layers, layers.name.clone()),
+                                };
+                                page_user_layers += 1;
+                                engine.store.ensure_user_layers_len(user_layer as usize + 1);
+                                if let Some(name) = name {
+                                    engine.store.rename_user_layer(user_layer, name);
+                                }
+                                StrokeLayer::UserLayer(user_layer)
+                            }
+                        };
+
                         // import strokes
                         for new_xoppstroke in layers.strokes.into_iter() {
                             match Stroke::from_xoppstroke(
                                 new_xoppstroke,
                                 offset,
                                 xopp_import_prefs.dpi,
+                                stroke_layer,
                             ) {
                                 Ok((new_stroke, layer)) => {
                                     engine.store.insert_stroke(new_stroke, Some(layer));
@@ -165,7 +273,9 @@ impl EngineSnapshot {
                                 xopp_import_prefs.dpi,
                             ) {
                                 Ok(new_image) => {
-                                    engine.store.insert_stroke(new_image, None);
+                                    // Images are kept on the image layer, unless they were exported from another
+                                    // non-user layer.
+                                    engine.store.insert_stroke(new_image, reserved_layer);
                                 }
                                 Err(e) => {
                                     error!(
@@ -195,3 +305,78 @@ impl EngineSnapshot {
         snapshot_receiver.await?
     }
 }
+
+/// The path of a background image that is attached to the xopp file.
+///
+/// Attached images are stored next to the xopp file, either with its name as prefix like `notes.xopp.bg_1.png`,
+/// or with just their file name. Only plain file names that resolve to a file in the directory of the xopp file
+/// are accepted.
+fn xopp_attached_pixmap_path(xopp_file_path: &Path, filename: &str) -> Option<PathBuf> {
+    let mut components = Path::new(filename).components();
+    let (Some(Component::Normal(name)), None) = (components.next(), components.next()) else {
+        return None;
+    };
+    let dir = xopp_file_path.parent()?;
+    let canonical_dir = dir.canonicalize().ok()?;
+    let prefixed_name = xopp_file_path.file_name().map(|xopp_file_name| {
+        let mut prefixed_name = xopp_file_name.to_os_string();
+        prefixed_name.push(".");
+        prefixed_name.push(name);
+        prefixed_name
+    });
+
+    prefixed_name
+        .into_iter()
+        .chain(std::iter::once(name.to_os_string()))
+        .map(|name| dir.join(name))
+        .find(|path| {
+            path.canonicalize()
+                .map(|canonical_path| {
+                    canonical_path.is_file()
+                        && canonical_path.parent() == Some(canonical_dir.as_path())
+                })
+                .unwrap_or(false)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn user_layers_default_when_missing() {
+        let mut value = ijson::to_value(EngineSnapshot::default()).unwrap();
+        let object = value.as_object_mut().unwrap();
+        object.remove("user_layers");
+        object.remove("active_user_layer");
+
+        let snapshot: EngineSnapshot = ijson::from_value(&value).unwrap();
+        assert_eq!(
+            *snapshot.user_layers,
+            *crate::store::user_layers::default_user_layers()
+        );
+        assert_eq!(snapshot.active_user_layer, 0);
+    }
+
+    #[test]
+    fn user_layers_kept_when_present() {
+        let user_layers = vec![
+            UserLayer::new_w_index(0),
+            UserLayer {
+                visible: false,
+                locked: true,
+                ..UserLayer::new_w_index(1)
+            },
+        ];
+        let snapshot = EngineSnapshot {
+            user_layers: Arc::new(user_layers.clone()),
+            active_user_layer: 1,
+            ..Default::default()
+        };
+
+        let loaded: EngineSnapshot =
+            ijson::from_value(&ijson::to_value(snapshot).unwrap()).unwrap();
+        assert_eq!(*loaded.user_layers, user_layers);
+        assert_eq!(loaded.active_user_layer, 1);
+    }
+}
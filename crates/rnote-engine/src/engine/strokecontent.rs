@@ -1,6 +1,7 @@
 // Imports
-use crate::document::Background;
+use crate::document::{Background, Format};
 use crate::render::Svg;
+use crate::store::{GroupComponent, StrokeKey};
 use crate::strokes::Stroke;
 use crate::Drawable;
 use p2d::bounding_volume::{Aabb, BoundingVolume};
@@ -17,10 +18,24 @@ use tracing::warn;
 pub struct StrokeContent {
     #[serde(rename = "strokes")]
     pub strokes: Vec<Arc<Stroke>>,
+    /// The keys the strokes had in the store they were taken from, in the same order as the strokes.
+    ///
+    /// Used to keep connectors attached to the strokes they are inserted together with.
+    #[serde(rename = "keys")]
+    pub keys: Vec<StrokeKey>,
+    /// The groups of the strokes, in the same order as the strokes.
+    #[serde(rename = "group_components")]
+    pub group_components: Vec<GroupComponent>,
     #[serde(rename = "bounds")]
     pub bounds: Option<Aabb>,
     #[serde(rename = "background")]
     pub background: Option<Background>,
+    /// The size of the document format, which backgrounds that are laid out per page depend on.
+    #[serde(rename = "format_size")]
+    pub format_size: Option<na::Vector2<f64>>,
+    /// Backgrounds that override the background inside the given page bounds.
+    #[serde(rename = "page_backgrounds")]
+    pub page_backgrounds: Vec<(Aabb, Background)>,
 }
 
 impl StrokeContent {
@@ -37,11 +52,31 @@ impl StrokeContent {
         self
     }
 
+    pub fn with_keys(mut self, keys: Vec<StrokeKey>) -> Self {
+        self.keys = keys;
+        self
+    }
+
+    pub fn with_group_components(mut self, group_components: Vec<GroupComponent>) -> Self {
+        self.group_components = group_components;
+        self
+    }
+
     pub fn with_background(mut self, background: Option<Background>) -> Self {
         self.background = background;
         self
     }
 
+    pub fn with_format_size(mut self, format_size: Option<na::Vector2<f64>>) -> Self {
+        self.format_size = format_size;
+        self
+    }
+
+    pub fn with_page_backgrounds(mut self, page_backgrounds: Vec<(Aabb, Background)>) -> Self {
+        self.page_backgrounds = page_backgrounds;
+        self
+    }
+
     pub fn bounds(&self) -> Option<Aabb> {
         if self.bounds.is_some() {
             return self.bounds;
@@ -120,10 +155,24 @@ impl StrokeContent {
         cairo_cx.clip();
 
         if draw_background {
+            let format_size = self.format_size.unwrap_or_else(|| Format::default().size());
             if let Some(background) = &self.background {
                 background.draw_to_cairo(
                     cairo_cx,
                     bounds_loosened,
+                    format_size,
+                    draw_pattern,
+                    optimize_printing,
+                )?;
+            }
+            for (page_bounds, page_background) in self.page_backgrounds.iter() {
+                let Some(page_bounds) = page_bounds.intersection(&bounds_loosened) else {
+                    continue;
+                };
+                page_background.draw_to_cairo(
+                    cairo_cx,
+                    page_bounds,
+                    format_size,
                     draw_pattern,
                     optimize_printing,
                 )?;
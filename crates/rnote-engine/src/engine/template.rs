@@ -0,0 +1,76 @@
+// Imports
+use super::EngineSnapshot;
+use crate::strokes::Stroke;
+use crate::{Camera, Engine, WidgetFlags};
+use futures::channel::oneshot;
+use std::sync::Arc;
+
+/// The values that replace the placeholders in the text strokes of a template when it is instantiated.
+#[derive(Debug, Clone)]
+pub struct TemplateValues {
+    /// Replaces [TemplateValues::TITLE_PLACEHOLDER].
+    pub title: String,
+    /// Replaces [TemplateValues::DATE_PLACEHOLDER].
+    pub date: String,
+}
+
+impl TemplateValues {
+    pub const TITLE_PLACEHOLDER: &'static str = "{title}";
+    pub const DATE_PLACEHOLDER: &'static str = "{date}";
+
+    /// Values with the given title and the current local date.
+    pub fn new(title: String) -> Self {
+        Self {
+            title,
+            date: chrono::Local::now().format("%Y-%m-%d").to_string(),
+        }
+    }
+}
+
+impl EngineSnapshot {
+    /// Convert the snapshot into a template.
+    ///
+    /// Templates are regular .rnote files, but the camera is reset so that new documents start at the origin.
+    pub fn into_template(mut self) -> Self {
+        self.camera = Camera::default();
+        self
+    }
+
+    /// Instantiate the snapshot as template, replacing the placeholders in all text strokes with the values.
+    pub fn instantiate_template(mut self, values: &TemplateValues) -> Self {
+        for stroke in Arc::make_mut(&mut self.stroke_components).values_mut() {
+            let Stroke::TextStroke(textstroke) = stroke.as_ref() else {
+                continue;
+            };
+            let mut textstroke = textstroke.clone();
+            if textstroke.replace_placeholders(&[
+                (TemplateValues::TITLE_PLACEHOLDER, &values.title),
+                (TemplateValues::DATE_PLACEHOLDER, &values.date),
+            ]) {
+                *stroke = Arc::new(Stroke::TextStroke(textstroke));
+            }
+        }
+        self
+    }
+}
+
+impl Engine {
+    /// Save the current document as a template .rnote file.
+    pub fn save_as_template_rnote_bytes(
+        &self,
+        file_name: String,
+    ) -> oneshot::Receiver<anyhow::Result<Vec<u8>>> {
+        self.take_snapshot()
+            .into_template()
+            .save_as_rnote_bytes(file_name)
+    }
+
+    /// Create a new document from the template, replacing the placeholders in its text strokes with the values.
+    pub fn load_template(
+        &mut self,
+        template: EngineSnapshot,
+        values: &TemplateValues,
+    ) -> WidgetFlags {
+        self.load_snapshot(template.instantiate_template(values))
+    }
+}
@@ -691,6 +691,8 @@ pub struct XoppStroke {
     ///
     /// As points where the vector (1.0, 0.0) has length 1/72 inch.
     pub coords: Vec<na::Vector2<f64>>,
+    /// The lengths of the alternating dashes and gaps of the line style. Empty if the stroke is solid.
+    pub dashes: Vec<f64>,
     /// Optional timestamp.
     pub timestamp: Option<u64>,
     /// Optional audio filename.
@@ -743,6 +745,11 @@ impl XmlLoadable for XoppStroke {
             .filter_map(|split| split.parse::<f64>().ok())
             .collect::<Vec<f64>>();
 
+        self.dashes = node
+            .attribute("style")
+            .map(XoppStroke::dashes_from_style_attr_value)
+            .unwrap_or_default();
+
         self.timestamp = if let Some(_ts) = node.attribute("ts") {
             // the timestamp parsing is fallible and currently not implemented
             // ts.parse::<u64>().ok()
@@ -792,6 +799,19 @@ impl XmlWritable for XoppStroke {
                 .collect::<Vec<String>>()
                 .join(" "),
         );
+        if !self.dashes.is_empty() {
+            w.write_attribute(
+                "style",
+                &format!(
+                    "cust: {}",
+                    self.dashes
+                        .iter()
+                        .map(|&dash| format!("{dash:.VALS_DEC_PLACES$}"))
+                        .collect::<Vec<String>>()
+                        .join(" ")
+                ),
+            );
+        }
 
         w.write_text(
             &self
@@ -812,6 +832,29 @@ impl XmlWritable for XoppStroke {
     }
 }
 
+impl XoppStroke {
+    /// Parses the dashes from the value of the `style` attribute.
+    ///
+    /// Xournal++ has the predefined styles "plain", "dash", "dashdot" and "dot",
+    /// and custom styles with the dash lengths following the "cust:" prefix.
+    fn dashes_from_style_attr_value(style: &str) -> Vec<f64> {
+        match style.trim() {
+            "dash" => vec![6.0, 3.0],
+            "dashdot" => vec![6.0, 3.0, 0.5, 3.0],
+            "dot" => vec![0.5, 3.0],
+            style => style
+                .strip_prefix("cust:")
+                .map(|dashes| {
+                    dashes
+                        .split_whitespace()
+                        .filter_map(|split| split.parse::<f64>().ok())
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+}
+
 /// A Xopp stroke tool.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum XoppTool {
@@ -18,6 +18,7 @@ pub mod engine;
 pub mod ext;
 pub mod fileformats;
 pub mod pens;
+pub mod recognition;
 pub mod render;
 pub mod selectioncollision;
 pub mod snap;
@@ -3,6 +3,7 @@ use super::pensconfig::brushconfig::BrushStyle;
 use super::PenBehaviour;
 use super::PenStyle;
 use crate::engine::{EngineView, EngineViewMut};
+use crate::store::chrono_comp::StrokeLayer;
 use crate::store::StrokeKey;
 use crate::strokes::BrushStroke;
 use crate::strokes::Stroke;
@@ -89,15 +90,16 @@ impl PenBehaviour for Brush {
                             .brush_config
                             .style_for_current_options(),
                     ));
-                    let current_stroke_key = engine_view.store.insert_stroke(
-                        brushstroke,
-                        Some(
-                            engine_view
-                                .pens_config
-                                .brush_config
-                                .layer_for_current_options(),
-                        ),
-                    );
+                    let layer = match engine_view
+                        .pens_config
+                        .brush_config
+                        .layer_for_current_options()
+                    {
+                        StrokeLayer::UserLayer(_) => engine_view.store.active_stroke_layer(),
+                        layer => layer,
+                    };
+                    let current_stroke_key =
+                        engine_view.store.insert_stroke(brushstroke, Some(layer));
 
                     engine_view.store.regenerate_rendering_for_stroke(
                         current_stroke_key,
@@ -229,6 +231,10 @@ impl PenBehaviour for Brush {
                             );
                         }
 
+                        if engine_view.pens_config.brush_config.shape_recognition {
+                            replace_w_recognized_shape(*current_stroke_key, engine_view);
+                        }
+
                         // Finish up the last stroke
                         engine_view
                             .store
@@ -293,7 +299,10 @@ impl DrawableOnDoc for Brush {
                     BrushStyle::Marker => {
                         // Don't draw the marker, as the pen would render on top of other strokes, while the stroke itself would render underneath them.
                     }
-                    BrushStyle::Solid | BrushStyle::Textured => {
+                    BrushStyle::Solid
+                    | BrushStyle::Textured
+                    | BrushStyle::Calligraphy
+                    | BrushStyle::Pencil => {
                         let style = engine_view
                             .pens_config
                             .brush_config
@@ -313,6 +322,21 @@ impl Brush {
     const INPUT_OVERSHOOT: f64 = 30.0;
 }
 
+/// Replaces the brush stroke with a shape stroke of the same color and width,
+/// if its path is recognized as a shape.
+fn replace_w_recognized_shape(key: StrokeKey, engine_view: &mut EngineViewMut) {
+    let Some(Stroke::BrushStroke(brushstroke)) = engine_view.store.get_stroke_ref(key) else {
+        return;
+    };
+    let Some(shapestroke) = brushstroke.to_recognized_shapestroke() else {
+        return;
+    };
+
+    if let Some(stroke) = engine_view.store.get_stroke_mut(key) {
+        *stroke = Stroke::ShapeStroke(shapestroke);
+    }
+}
+
 fn play_marker_sound(engine_view: &mut EngineViewMut) {
     if let Some(audioplayer) = engine_view.audioplayer {
         audioplayer.play_random_marker_sound();
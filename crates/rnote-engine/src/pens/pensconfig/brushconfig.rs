@@ -2,9 +2,11 @@
 use crate::store::chrono_comp::StrokeLayer;
 use rand::{Rng, SeedableRng};
 use rnote_compose::builders::PenPathBuilderType;
+use rnote_compose::style::calligraphy::CalligraphyOptions;
+use rnote_compose::style::pencil::PencilOptions;
 use rnote_compose::style::smooth::SmoothOptions;
 use rnote_compose::style::textured::TexturedOptions;
-use rnote_compose::style::PressureCurve;
+use rnote_compose::style::{CustomPressureCurve, PressureCurve};
 use rnote_compose::Style;
 use serde::{Deserialize, Serialize};
 
@@ -27,6 +29,10 @@ pub enum BrushStyle {
     Solid,
     #[serde(rename = "textured")]
     Textured,
+    #[serde(rename = "calligraphy")]
+    Calligraphy,
+    #[serde(rename = "pencil")]
+    Pencil,
 }
 
 impl Default for BrushStyle {
@@ -110,6 +116,16 @@ pub struct BrushConfig {
     pub solid_options: SolidOptions,
     #[serde(rename = "textured_options")]
     pub textured_options: TexturedOptions,
+    #[serde(rename = "calligraphy_options")]
+    pub calligraphy_options: CalligraphyOptions,
+    #[serde(rename = "pencil_options")]
+    pub pencil_options: PencilOptions,
+    /// Whether finished strokes that resemble a line, arrow, rectangle, ellipse or polygon are replaced by the shape.
+    #[serde(rename = "shape_recognition")]
+    pub shape_recognition: bool,
+    /// The last edited custom pressure curve, restored when a custom pressure curve is selected again.
+    #[serde(rename = "custom_pressure_curve")]
+    pub custom_pressure_curve: CustomPressureCurve,
 }
 
 impl BrushConfig {
@@ -119,7 +135,10 @@ impl BrushConfig {
     pub(crate) fn layer_for_current_options(&self) -> StrokeLayer {
         match &self.style {
             BrushStyle::Marker => StrokeLayer::Highlighter,
-            BrushStyle::Solid | BrushStyle::Textured => StrokeLayer::UserLayer(0),
+            BrushStyle::Solid
+            | BrushStyle::Textured
+            | BrushStyle::Calligraphy
+            | BrushStyle::Pencil => StrokeLayer::UserLayer(0),
         }
     }
 
@@ -127,6 +146,7 @@ impl BrushConfig {
     pub(crate) fn new_style_seeds(&mut self) {
         let seed = Some(rand_pcg::Pcg64::from_entropy().gen());
         self.textured_options.seed = seed;
+        self.pencil_options.seed = seed;
     }
 
     pub(crate) fn style_for_current_options(&self) -> Style {
@@ -146,6 +166,16 @@ impl BrushConfig {
 
                 Style::Textured(options)
             }
+            BrushStyle::Calligraphy => {
+                let options = self.calligraphy_options.clone();
+
+                Style::Calligraphy(options)
+            }
+            BrushStyle::Pencil => {
+                let options = self.pencil_options.clone();
+
+                Style::Pencil(options)
+            }
         }
     }
 }
@@ -94,9 +94,15 @@ impl Selector {
                                 .and_then(|key| engine_view.store.selected(key).map(|s| !s))
                                 .unwrap_or(false)
                         {
-                            let key_to_add = key_to_add.unwrap();
-                            engine_view.store.set_selected(key_to_add, true);
-                            selection.push(key_to_add);
+                            // members of the same group are added together
+                            let keys_to_add = engine_view
+                                .store
+                                .keys_with_group_members(&[key_to_add.unwrap()])
+                                .into_iter()
+                                .filter(|key| !selection.contains(key))
+                                .collect::<Vec<StrokeKey>>();
+                            engine_view.store.set_selected_keys(&keys_to_add, true);
+                            selection.extend(keys_to_add);
                             if let Some(new_bounds) =
                                 engine_view.store.bounds_for_strokes(selection)
                             {
@@ -216,6 +222,13 @@ impl Selector {
                             engine_view
                                 .store
                                 .translate_strokes_images(selection, offset);
+                            engine_view
+                                .store
+                                .regenerate_rendering_for_attached_connectors(
+                                    selection,
+                                    engine_view.camera.viewport(),
+                                    engine_view.camera.image_scale(),
+                                );
                             *selection_bounds = selection_bounds.translate(offset);
                             *current_pos += offset;
                         }
@@ -256,6 +269,13 @@ impl Selector {
                                 angle_delta,
                                 *rotation_center,
                             );
+                            engine_view
+                                .store
+                                .regenerate_rendering_for_attached_connectors(
+                                    selection,
+                                    engine_view.camera.viewport(),
+                                    engine_view.camera.image_scale(),
+                                );
 
                             if let Some(new_bounds) =
                                 engine_view.store.bounds_for_strokes(selection)
@@ -336,6 +356,13 @@ impl Selector {
                         engine_view
                             .store
                             .scale_strokes_images_with_pivot(selection, scale, pivot);
+                        engine_view
+                            .store
+                            .regenerate_rendering_for_attached_connectors(
+                                selection,
+                                engine_view.camera.viewport(),
+                                engine_view.camera.image_scale(),
+                            );
                         *selection_bounds = selection_bounds
                             .translate(-pivot)
                             .scale_non_uniform(scale)
@@ -444,7 +471,8 @@ impl Selector {
                                 )
                                 .pop()
                         }) {
-                            vec![key]
+                            // selecting one member of a group selects the entire group
+                            engine_view.store.keys_with_group_members(&[key])
                         } else {
                             vec![]
                         }
@@ -647,6 +675,25 @@ impl Selector {
                             progress: PenProgress::Finished,
                         }
                     }
+                    KeyboardKey::Unicode('g') | KeyboardKey::Unicode('G') => {
+                        // Group or ungroup selection
+                        if modifier_keys.contains(&ModifierKey::KeyboardCtrl) {
+                            let modified = if modifier_keys.contains(&ModifierKey::KeyboardShift) {
+                                engine_view.store.ungroup_strokes(selection)
+                            } else {
+                                engine_view.store.group_strokes(selection)
+                            };
+                            if modified {
+                                widget_flags |= engine_view.store.record(Instant::now());
+                                widget_flags.store_modified = true;
+                            }
+                        }
+                        EventResult {
+                            handled: true,
+                            propagate: EventPropagation::Stop,
+                            progress: PenProgress::InProgress,
+                        }
+                    }
                     KeyboardKey::Delete | KeyboardKey::BackSpace => {
                         engine_view.store.set_trashed_keys(selection, true);
                         widget_flags |= super::cancel_selection(selection, engine_view);
@@ -129,9 +129,10 @@ impl PenBehaviour for Shaper {
                         let shapes_emitted = !shapes.is_empty();
 
                         for shape in shapes {
+                            let layer = engine_view.store.active_stroke_layer();
                             let key = engine_view.store.insert_stroke(
                                 Stroke::ShapeStroke(ShapeStroke::new(shape, style.clone())),
-                                None,
+                                Some(layer),
                             );
                             style.advance_seed();
                             engine_view.store.regenerate_rendering_for_stroke(
@@ -155,9 +156,10 @@ impl PenBehaviour for Shaper {
 
                         let shapes_emitted = !shapes.is_empty();
                         for shape in shapes {
+                            let layer = engine_view.store.active_stroke_layer();
                             let key = engine_view.store.insert_stroke(
                                 Stroke::ShapeStroke(ShapeStroke::new(shape, style.clone())),
-                                None,
+                                Some(layer),
                             );
                             style.advance_seed();
                             engine_view.store.regenerate_rendering_for_stroke(
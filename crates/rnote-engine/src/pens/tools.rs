@@ -407,6 +407,13 @@ impl PenBehaviour for Tools {
                                 &self.verticalspace_tool.strokes_below,
                                 na::vector![0.0, y_offset],
                             );
+                            engine_view
+                                .store
+                                .regenerate_rendering_for_attached_connectors(
+                                    &self.verticalspace_tool.strokes_below,
+                                    engine_view.camera.viewport(),
+                                    engine_view.camera.image_scale(),
+                                );
                             self.verticalspace_tool.pos_y += y_offset;
 
                             widget_flags.store_modified = true;
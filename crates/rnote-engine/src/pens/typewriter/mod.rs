@@ -644,9 +644,10 @@ impl Typewriter {
                 let textstroke = TextStroke::new(text, pos, text_style);
                 let cursor = GraphemeCursor::new(text_len, textstroke.text.len(), true);
 
-                let stroke_key = engine_view
-                    .store
-                    .insert_stroke(Stroke::TextStroke(textstroke), None);
+                let stroke_key = engine_view.store.insert_stroke(
+                    Stroke::TextStroke(textstroke),
+                    Some(engine_view.store.active_stroke_layer()),
+                );
                 engine_view.store.regenerate_rendering_for_stroke(
                     stroke_key,
                     engine_view.camera.viewport(),
@@ -671,9 +672,10 @@ impl Typewriter {
                 let textstroke = TextStroke::new(text, *pos, text_style);
                 let cursor = GraphemeCursor::new(text_len, textstroke.text.len(), true);
 
-                let stroke_key = engine_view
-                    .store
-                    .insert_stroke(Stroke::TextStroke(textstroke), None);
+                let stroke_key = engine_view.store.insert_stroke(
+                    Stroke::TextStroke(textstroke),
+                    Some(engine_view.store.active_stroke_layer()),
+                );
                 engine_view.store.regenerate_rendering_for_stroke(
                     stroke_key,
                     engine_view.camera.viewport(),
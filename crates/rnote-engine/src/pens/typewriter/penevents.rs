@@ -252,6 +252,13 @@ impl Typewriter {
                                 engine_view
                                     .store
                                     .translate_strokes_images(&[*stroke_key], offset);
+                                engine_view
+                                    .store
+                                    .regenerate_rendering_for_attached_connectors(
+                                        &[*stroke_key],
+                                        engine_view.camera.viewport(),
+                                        engine_view.camera.image_scale(),
+                                    );
                                 *current_pos += offset;
 
                                 widget_flags.store_modified = true;
@@ -505,9 +512,10 @@ impl Typewriter {
                         let mut cursor = GraphemeCursor::new(0, textstroke.text.len(), true);
 
                         textstroke.move_cursor_forward(&mut cursor);
-                        let stroke_key = engine_view
-                            .store
-                            .insert_stroke(Stroke::TextStroke(textstroke), None);
+                        let stroke_key = engine_view.store.insert_stroke(
+                            Stroke::TextStroke(textstroke),
+                            Some(engine_view.store.active_stroke_layer()),
+                        );
                         widget_flags |= engine_view
                             .document
                             .resize_autoexpand(engine_view.store, engine_view.camera);
@@ -1076,9 +1084,10 @@ impl Typewriter {
                 let textstroke = TextStroke::new(text, *pos, text_style);
                 let cursor = GraphemeCursor::new(text_len, text_len, true);
 
-                let stroke_key = engine_view
-                    .store
-                    .insert_stroke(Stroke::TextStroke(textstroke), None);
+                let stroke_key = engine_view.store.insert_stroke(
+                    Stroke::TextStroke(textstroke),
+                    Some(engine_view.store.active_stroke_layer()),
+                );
                 engine_view.store.regenerate_rendering_for_stroke(
                     stroke_key,
                     engine_view.camera.viewport(),
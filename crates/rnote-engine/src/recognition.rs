@@ -0,0 +1,347 @@
+//! Handwriting recognition.
+//!
+//! Recognizers implement the [HandwritingRecognizer] trait and can be set on the engine.
+//! The recognized text is then for example embedded as invisible text layer when exporting as Pdf.
+
+// Imports
+use crate::strokes::BrushStroke;
+use crate::{render, Drawable};
+use p2d::bounding_volume::{Aabb, BoundingVolume};
+use pangocairo::pango;
+use piet::RenderContext;
+use rnote_compose::ext::AabbExt;
+use rnote_compose::shapes::Shapeable;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::Stdio;
+use tracing::error;
+
+/// Text that was recognized from handwritten strokes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecognizedText {
+    /// The recognized text. Can contain multiple lines.
+    pub text: String,
+    /// The bounds of the handwriting the text was recognized from, in document coordinates.
+    pub bounds: Aabb,
+}
+
+/// A handwriting recognizer.
+pub trait HandwritingRecognizer: std::fmt::Debug + Send + Sync {
+    /// Recognize the text of a group of brush strokes that are close to each other, e.g. a word or a line.
+    ///
+    /// The group is never empty.
+    fn recognize(&self, group: &[BrushStroke]) -> anyhow::Result<Vec<RecognizedText>>;
+}
+
+/// Group brush strokes that are close to each other.
+///
+/// Strokes are in the same group when their bounds are nearer than `distance` to any other stroke in the group.
+pub fn group_brushstrokes(
+    brushstrokes: impl IntoIterator<Item = BrushStroke>,
+    distance: f64,
+) -> Vec<Vec<BrushStroke>> {
+    let mut groups: Vec<(Aabb, Vec<BrushStroke>)> = vec![];
+
+    for brushstroke in brushstrokes {
+        let mut bounds = brushstroke.bounds();
+        let mut group = vec![brushstroke];
+
+        // Merge all groups that are within reach, until no more are found
+        while let Some(i) = groups
+            .iter()
+            .position(|(group_bounds, _)| group_bounds.intersects(&bounds.loosened(distance)))
+        {
+            let (other_bounds, other_group) = groups.remove(i);
+            bounds.merge(&other_bounds);
+            group.extend(other_group);
+        }
+        groups.push((bounds, group));
+    }
+
+    groups.into_iter().map(|(_, group)| group).collect()
+}
+
+/// Group the brush strokes and recognize the text of every group.
+///
+/// Groups where the recognition fails are skipped.
+pub fn recognize_brushstrokes(
+    recognizer: &dyn HandwritingRecognizer,
+    brushstrokes: impl IntoIterator<Item = BrushStroke>,
+) -> Vec<RecognizedText> {
+    group_brushstrokes(brushstrokes, GROUP_DISTANCE)
+        .into_iter()
+        .filter_map(|group| match recognizer.recognize(&group) {
+            Ok(recognized) => Some(recognized),
+            Err(e) => {
+                error!("Recognizing handwriting of group of strokes failed, Err: {e:?}");
+                None
+            }
+        })
+        .flatten()
+        .filter(|recognized| !recognized.text.trim().is_empty())
+        .collect()
+}
+
+/// The distance in which brush strokes are grouped together before they are recognized.
+pub const GROUP_DISTANCE: f64 = 16.0;
+
+/// The bounds of a group of brush strokes.
+fn group_bounds(group: &[BrushStroke]) -> Aabb {
+    group
+        .iter()
+        .map(|brushstroke| brushstroke.bounds())
+        .fold(Aabb::new_invalid(), |acc, x| acc.merged(&x))
+}
+
+/// Split the bounds vertically into equally high bands, one for each non-empty line of the text.
+fn recognized_lines(text: &str, bounds: Aabb) -> Vec<RecognizedText> {
+    let lines = text
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<&str>>();
+    let line_height = bounds.extents()[1] / lines.len().max(1) as f64;
+
+    lines
+        .into_iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let mins = na::point![bounds.mins[0], bounds.mins[1] + line_height * i as f64];
+            RecognizedText {
+                text: line.to_string(),
+                bounds: Aabb::new(mins, na::point![bounds.maxs[0], mins[1] + line_height]),
+            }
+        })
+        .collect()
+}
+
+/// A recognizer that runs a local command-line program.
+///
+/// Every group of strokes is rendered as Png image with a white background and written to the standard input
+/// of the program. The program is expected to print the recognized text to its standard output.
+/// Every non-empty line of the output is placed in an equally high band of the bounds of the group.
+///
+/// For example `tesseract` can be used with the arguments `- stdout`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename = "command_recognizer")]
+pub struct CommandRecognizer {
+    /// The program.
+    #[serde(rename = "program")]
+    pub program: PathBuf,
+    /// The arguments passed to the program.
+    #[serde(rename = "args")]
+    pub args: Vec<String>,
+    /// The scale-factor of the rendered images.
+    #[serde(rename = "image_scale")]
+    pub image_scale: f64,
+}
+
+impl Default for CommandRecognizer {
+    fn default() -> Self {
+        Self {
+            program: PathBuf::from("tesseract"),
+            args: vec![String::from("-"), String::from("stdout")],
+            image_scale: Self::IMAGE_SCALE_DEFAULT,
+        }
+    }
+}
+
+impl CommandRecognizer {
+    pub const IMAGE_SCALE_DEFAULT: f64 = 2.0;
+    /// The margin around the strokes in the rendered images.
+    const IMAGE_MARGIN: f64 = 8.0;
+
+    pub fn new(program: PathBuf, args: Vec<String>) -> Self {
+        Self {
+            program,
+            args,
+            ..Default::default()
+        }
+    }
+
+    fn gen_png_bytes(&self, group: &[BrushStroke], bounds: Aabb) -> anyhow::Result<Vec<u8>> {
+        let image = render::Image::gen_with_piet(
+            |piet_cx| {
+                piet_cx.fill(bounds.to_kurbo_rect(), &piet::Color::WHITE);
+                for brushstroke in group {
+                    brushstroke.draw(piet_cx, self.image_scale)?;
+                }
+                Ok(())
+            },
+            bounds,
+            self.image_scale,
+        )?;
+        image.into_encoded_bytes(image::ImageFormat::Png, None)
+    }
+}
+
+impl HandwritingRecognizer for CommandRecognizer {
+    fn recognize(&self, group: &[BrushStroke]) -> anyhow::Result<Vec<RecognizedText>> {
+        let bounds = group_bounds(group);
+        let png_bytes = self.gen_png_bytes(group, bounds.loosened(Self::IMAGE_MARGIN))?;
+
+        let mut child = std::process::Command::new(&self.program)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| {
+                anyhow::anyhow!(
+                    "Spawning recognizer program `{}` failed, Err: {e:?}",
+                    self.program.display()
+                )
+            })?;
+        let mut stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| anyhow::anyhow!("Taking stdin of recognizer program failed."))?;
+        // Write in a separate thread, so that the program can't block on a full output pipe
+        let writer = std::thread::spawn(move || stdin.write_all(&png_bytes));
+        let output = child.wait_with_output()?;
+        writer
+            .join()
+            .map_err(|e| anyhow::anyhow!("Joining stdin writer thread panicked, Err: {e:?}"))??;
+
+        if !output.status.success() {
+            return Err(anyhow::anyhow!(
+                "Recognizer program `{}` exited with {}, stderr: {}",
+                self.program.display(),
+                output.status,
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+        Ok(recognized_lines(
+            &String::from_utf8_lossy(&output.stdout),
+            bounds,
+        ))
+    }
+}
+
+/// A deterministic recognizer that does not do any real recognition.
+///
+/// Returns the text for every group of strokes, with `{strokes}` replaced by the number of strokes in the group.
+/// Intended for testing.
+#[derive(Debug, Clone)]
+pub struct StubRecognizer {
+    pub text: String,
+}
+
+impl Default for StubRecognizer {
+    fn default() -> Self {
+        Self {
+            text: String::from("{strokes} strokes"),
+        }
+    }
+}
+
+impl HandwritingRecognizer for StubRecognizer {
+    fn recognize(&self, group: &[BrushStroke]) -> anyhow::Result<Vec<RecognizedText>> {
+        Ok(recognized_lines(
+            &self.text.replace("{strokes}", &group.len().to_string()),
+            group_bounds(group),
+        ))
+    }
+}
+
+/// Draw the recognized text as invisible text to a cairo context, stretched to fill its bounds.
+///
+/// Cairo has no invisible text render mode and skips drawing operations that are fully transparent or fully clipped.
+/// So the text is drawn with the smallest alpha value that is not considered transparent,
+/// and clipped to a tiny area inside its bounds. It is not rendered, but Pdf viewers can select and search it.
+pub(crate) fn draw_recognized_text_to_cairo(
+    cx: &cairo::Context,
+    recognized: &[RecognizedText],
+) -> anyhow::Result<()> {
+    const INVISIBLE_ALPHA: f64 = 1.0 / 255.0;
+    /// Half the size of the area the text is clipped to.
+    const CLIP_HALF_SIZE: f64 = 0.001;
+
+    for recognized in recognized {
+        let size = recognized.bounds.extents();
+        if size[0] <= 0.0 || size[1] <= 0.0 {
+            continue;
+        }
+        let pango_layout = pangocairo::functions::create_layout(cx);
+        let mut font_description = pango::FontDescription::new();
+        font_description.set_family("sans-serif");
+        font_description.set_absolute_size(size[1] * f64::from(pango::SCALE));
+        pango_layout.set_font_description(Some(&font_description));
+        pango_layout.set_text(&recognized.text);
+        let (_, logical_rect) = pango_layout.extents();
+        let layout_width = f64::from(logical_rect.width()) / f64::from(pango::SCALE);
+        let layout_height = f64::from(logical_rect.height()) / f64::from(pango::SCALE);
+        if layout_width <= 0.0 || layout_height <= 0.0 {
+            continue;
+        }
+        let center = recognized.bounds.center();
+
+        cx.save()?;
+        cx.rectangle(
+            center[0] - CLIP_HALF_SIZE,
+            center[1] - CLIP_HALF_SIZE,
+            CLIP_HALF_SIZE * 2.0,
+            CLIP_HALF_SIZE * 2.0,
+        );
+        cx.clip();
+        cx.set_source_rgba(0.0, 0.0, 0.0, INVISIBLE_ALPHA);
+        cx.translate(recognized.bounds.mins[0], recognized.bounds.mins[1]);
+        cx.scale(size[0] / layout_width, size[1] / layout_height);
+        cx.move_to(0.0, 0.0);
+        pangocairo::functions::show_layout(cx, &pango_layout);
+        cx.restore()?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rnote_compose::penpath::{Element, Segment};
+    use rnote_compose::{PenPath, Style};
+
+    fn line_brushstroke(start: na::Vector2<f64>, end: na::Vector2<f64>) -> BrushStroke {
+        let mut path = PenPath::new(Element::new(start, 0.5));
+        path.segments.push(Segment::LineTo {
+            end: Element::new(end, 0.5),
+        });
+        BrushStroke::from_penpath(path, Style::default())
+    }
+
+    #[test]
+    fn group_close_strokes() {
+        let brushstrokes = vec![
+            line_brushstroke(na::vector![0.0, 0.0], na::vector![10.0, 10.0]),
+            line_brushstroke(na::vector![500.0, 0.0], na::vector![510.0, 10.0]),
+            line_brushstroke(na::vector![15.0, 0.0], na::vector![25.0, 10.0]),
+        ];
+        let mut group_lens = group_brushstrokes(brushstrokes, GROUP_DISTANCE)
+            .into_iter()
+            .map(|group| group.len())
+            .collect::<Vec<usize>>();
+        group_lens.sort_unstable();
+
+        assert_eq!(group_lens, vec![1, 2]);
+    }
+
+    #[test]
+    fn stub_recognizer() {
+        let brushstrokes = vec![
+            line_brushstroke(na::vector![0.0, 0.0], na::vector![10.0, 10.0]),
+            line_brushstroke(na::vector![15.0, 0.0], na::vector![25.0, 10.0]),
+        ];
+        let recognized = recognize_brushstrokes(
+            &StubRecognizer {
+                text: String::from("first {strokes}\nsecond"),
+            },
+            brushstrokes,
+        );
+
+        assert_eq!(recognized.len(), 2);
+        assert_eq!(recognized[0].text, "first 2");
+        assert_eq!(recognized[1].text, "second");
+        assert!(recognized[0].bounds.maxs[1] <= recognized[1].bounds.mins[1] + 1e-6);
+    }
+}
@@ -4,6 +4,7 @@ use p2d::bounding_volume::Aabb;
 use rayon::slice::ParallelSliceMut;
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
+use std::collections::HashSet;
 use std::sync::Arc;
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq)]
@@ -87,6 +88,13 @@ impl ChronoComponent {
 
 /// Systems that are related to their chronological ordering.
 impl StrokeStore {
+    /// The layer the stroke is on.
+    pub(crate) fn stroke_layer(&self, key: StrokeKey) -> Option<StrokeLayer> {
+        self.chrono_components
+            .get(key)
+            .map(|chrono_comp| chrono_comp.layer)
+    }
+
     pub(crate) fn update_chrono_to_last(&mut self, key: StrokeKey) {
         if let Some(chrono_comp) = Arc::make_mut(&mut self.chrono_components).get_mut(key) {
             self.chrono_counter += 1;
@@ -94,6 +102,120 @@ impl StrokeStore {
         }
     }
 
+    /// Move the strokes for the given keys in front of all other strokes on their layers,
+    /// keeping their order relative to each other.
+    ///
+    /// Returns false when the order did not change.
+    pub(crate) fn bring_strokes_to_front(&mut self, keys: &[StrokeKey]) -> bool {
+        let keys = keys.iter().copied().collect::<HashSet<StrokeKey>>();
+        let mut order = self.keys_sorted_chrono();
+        order.sort_by_key(|key| {
+            (
+                self.stroke_layer(*key).unwrap_or_default(),
+                keys.contains(key),
+            )
+        });
+        self.reorder_chrono(&order)
+    }
+
+    /// Move the strokes for the given keys behind all other strokes on their layers,
+    /// keeping their order relative to each other.
+    ///
+    /// Returns false when the order did not change.
+    pub(crate) fn send_strokes_to_back(&mut self, keys: &[StrokeKey]) -> bool {
+        let keys = keys.iter().copied().collect::<HashSet<StrokeKey>>();
+        let mut order = self.keys_sorted_chrono();
+        order.sort_by_key(|key| {
+            (
+                self.stroke_layer(*key).unwrap_or_default(),
+                !keys.contains(key),
+            )
+        });
+        self.reorder_chrono(&order)
+    }
+
+    /// Move the strokes for the given keys one step forward on their layers,
+    /// in front of the next visible stroke that is not moved.
+    ///
+    /// Returns false when the order did not change.
+    pub(crate) fn raise_strokes(&mut self, keys: &[StrokeKey]) -> bool {
+        let keys = keys.iter().copied().collect::<HashSet<StrokeKey>>();
+        let mut order = self.keys_sorted_chrono();
+        for i in (0..order.len()).rev() {
+            if !keys.contains(&order[i]) {
+                continue;
+            }
+            if let Some(j) = self.reorder_step_target(&order, &keys, i, i + 1..order.len()) {
+                let key = order.remove(i);
+                order.insert(j, key);
+            }
+        }
+        self.reorder_chrono(&order)
+    }
+
+    /// Move the strokes for the given keys one step backward on their layers,
+    /// behind the previous visible stroke that is not moved.
+    ///
+    /// Returns false when the order did not change.
+    pub(crate) fn lower_strokes(&mut self, keys: &[StrokeKey]) -> bool {
+        let keys = keys.iter().copied().collect::<HashSet<StrokeKey>>();
+        let mut order = self.keys_sorted_chrono();
+        for i in 0..order.len() {
+            if !keys.contains(&order[i]) {
+                continue;
+            }
+            if let Some(j) = self.reorder_step_target(&order, &keys, i, (0..i).rev()) {
+                let key = order.remove(i);
+                order.insert(j, key);
+            }
+        }
+        self.reorder_chrono(&order)
+    }
+
+    /// The index of the first stroke in the candidate indices that the stroke at index `i` steps over
+    /// when it is raised or lowered.
+    ///
+    /// Trashed strokes and strokes on hidden layers are skipped, because stepping over them has no visible effect.
+    /// Returns None when a moved stroke or the end of the layer is reached first.
+    fn reorder_step_target(
+        &self,
+        order: &[StrokeKey],
+        keys: &HashSet<StrokeKey>,
+        i: usize,
+        candidates: impl Iterator<Item = usize>,
+    ) -> Option<usize> {
+        let layer = self.stroke_layer(order[i]);
+        for j in candidates {
+            let key = order[j];
+            if keys.contains(&key) || self.stroke_layer(key) != layer {
+                return None;
+            }
+            if !self.trashed(key).unwrap_or(false) && self.stroke_visible(key) {
+                return Some(j);
+            }
+        }
+        None
+    }
+
+    /// Reassign the chrono components so that the strokes get drawn in the given order.
+    ///
+    /// Only the components that change are modified. Returns false when the order did not change.
+    fn reorder_chrono(&mut self, order: &[StrokeKey]) -> bool {
+        if order == self.keys_sorted_chrono() {
+            return false;
+        }
+        let chrono_components = Arc::make_mut(&mut self.chrono_components);
+        for (t, &key) in (1..).zip(order.iter()) {
+            if let Some(chrono_comp) = chrono_components.get_mut(key) {
+                if chrono_comp.t != t {
+                    Arc::make_mut(chrono_comp).t = t;
+                }
+            }
+        }
+        self.chrono_counter = self.chrono_counter.max(order.len() as u32);
+        true
+    }
+
     /// Returns the keys in chronological order, as in first: gets drawn first, last: gets drawn last.
     pub(crate) fn keys_sorted_chrono(&self) -> Vec<StrokeKey> {
         let chrono_components = &self.chrono_components;
@@ -0,0 +1,172 @@
+// Imports
+use super::{StrokeKey, StrokeStore};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// The groups a stroke is a member of.
+///
+/// Groups can be nested, so a stroke can be a member of several groups.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename = "group_component")]
+pub struct GroupComponent {
+    /// The ids of the groups, from the outermost to the innermost group.
+    #[serde(rename = "groups")]
+    pub groups: Vec<u32>,
+}
+
+impl GroupComponent {
+    /// The id of the outermost group. Strokes in the same outermost group are selected together.
+    pub fn outermost(&self) -> Option<u32> {
+        self.groups.first().copied()
+    }
+}
+
+/// Systems that are related to grouping.
+impl StrokeStore {
+    /// Rebuild the slotmap with the keys returned from the stroke components,
+    /// keeping the existing group components and inserting empty ones for strokes that have none.
+    pub(crate) fn rebuild_group_components_slotmap(&mut self) {
+        let mut group_components = slotmap::SecondaryMap::new();
+        self.stroke_components.keys().for_each(|key| {
+            group_components.insert(
+                key,
+                self.group_components
+                    .get(key)
+                    .cloned()
+                    .unwrap_or_else(|| Arc::new(GroupComponent::default())),
+            );
+        });
+        self.group_components = Arc::new(group_components);
+    }
+
+    /// Clone the group components for the given keys.
+    pub(crate) fn clone_group_components(&self, keys: &[StrokeKey]) -> Vec<GroupComponent> {
+        keys.iter()
+            .map(|&key| {
+                self.group_components
+                    .get(key)
+                    .map(|group_comp| (**group_comp).clone())
+                    .unwrap_or_default()
+            })
+            .collect()
+    }
+
+    pub(crate) fn set_group_component(&mut self, key: StrokeKey, group_comp: GroupComponent) {
+        if let Some(current) = Arc::make_mut(&mut self.group_components)
+            .get_mut(key)
+            .map(Arc::make_mut)
+        {
+            *current = group_comp;
+        }
+    }
+
+    /// The id of the outermost group of the stroke.
+    pub(crate) fn outermost_group(&self, key: StrokeKey) -> Option<u32> {
+        self.group_components.get(key)?.outermost()
+    }
+
+    /// The given keys extended by the editable members of their outermost groups, in the order they are rendered.
+    pub(crate) fn keys_with_group_members(&self, keys: &[StrokeKey]) -> Vec<StrokeKey> {
+        let groups = keys
+            .iter()
+            .filter_map(|&key| self.outermost_group(key))
+            .collect::<Vec<u32>>();
+        if groups.is_empty() {
+            return keys.to_vec();
+        }
+
+        self.keys_sorted_chrono()
+            .into_iter()
+            .filter(|&key| {
+                keys.contains(&key)
+                    || (!self.trashed(key).unwrap_or(false)
+                        && self.stroke_editable(key)
+                        && self
+                            .outermost_group(key)
+                            .map(|group| groups.contains(&group))
+                            .unwrap_or(false))
+            })
+            .collect()
+    }
+
+    /// A group id that is not used by any stroke.
+    fn unused_group_id(&self) -> u32 {
+        self.group_components
+            .values()
+            .flat_map(|group_comp| group_comp.groups.iter().copied())
+            .max()
+            .map(|id| id + 1)
+            .unwrap_or(0)
+    }
+
+    /// Group the strokes for the given keys in a new group, which becomes their outermost group.
+    ///
+    /// Returns false when there are less than two strokes to group.
+    pub(crate) fn group_strokes(&mut self, keys: &[StrokeKey]) -> bool {
+        if keys.len() < 2 {
+            return false;
+        }
+        let group = self.unused_group_id();
+
+        keys.iter().for_each(|&key| {
+            if let Some(group_comp) = Arc::make_mut(&mut self.group_components)
+                .get_mut(key)
+                .map(Arc::make_mut)
+            {
+                group_comp.groups.insert(0, group);
+            }
+        });
+        true
+    }
+
+    /// Remove the outermost group of the strokes for the given keys, so nested groups become the outermost groups.
+    ///
+    /// Returns false when none of the strokes were grouped.
+    pub(crate) fn ungroup_strokes(&mut self, keys: &[StrokeKey]) -> bool {
+        let mut ungrouped = false;
+
+        keys.iter().for_each(|&key| {
+            if self.outermost_group(key).is_none() {
+                return;
+            }
+            if let Some(group_comp) = Arc::make_mut(&mut self.group_components)
+                .get_mut(key)
+                .map(Arc::make_mut)
+            {
+                group_comp.groups.remove(0);
+                ungrouped = true;
+            }
+        });
+        ungrouped
+    }
+
+    /// Replace the ids of the groups of the given strokes with unused ones,
+    /// so that copies of strokes form groups of their own and are not joined with the groups of the original strokes.
+    pub(crate) fn regroup_with_unused_ids(&mut self, keys: &[StrokeKey]) {
+        let mut next_group = self.unused_group_id();
+        let mut group_map = HashMap::<u32, u32>::new();
+
+        keys.iter().for_each(|&key| {
+            if self
+                .group_components
+                .get(key)
+                .map(|group_comp| group_comp.groups.is_empty())
+                .unwrap_or(true)
+            {
+                return;
+            }
+            if let Some(group_comp) = Arc::make_mut(&mut self.group_components)
+                .get_mut(key)
+                .map(Arc::make_mut)
+            {
+                for group in group_comp.groups.iter_mut() {
+                    *group = *group_map.entry(*group).or_insert_with(|| {
+                        next_group += 1;
+                        next_group - 1
+                    });
+                }
+            }
+        });
+    }
+}
@@ -1,27 +1,33 @@
 // Modules
 pub mod chrono_comp;
+pub mod group_comp;
 pub mod keytree;
+pub mod page_backgrounds;
 pub mod render_comp;
 pub mod selection_comp;
 pub mod stroke_comp;
 pub mod trash_comp;
+pub mod user_layers;
 
 // Re-exports
 pub use chrono_comp::ChronoComponent;
+pub use group_comp::GroupComponent;
 use keytree::KeyTree;
 pub use render_comp::RenderComponent;
 pub use selection_comp::SelectionComponent;
 pub use trash_comp::TrashComponent;
+pub use user_layers::UserLayer;
 
 // Imports
 use self::chrono_comp::StrokeLayer;
+use crate::document::PageBackground;
 use crate::engine::EngineSnapshot;
 use crate::strokes::Stroke;
 use crate::WidgetFlags;
 use rnote_compose::shapes::Shapeable;
 use serde::{Deserialize, Serialize};
 use slotmap::{HopSlotMap, SecondaryMap};
-use std::collections::VecDeque;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
 use std::time::Instant;
 use tracing::debug;
@@ -41,6 +47,17 @@ pub struct HistoryEntry {
     pub chrono_components: Arc<SecondaryMap<StrokeKey, Arc<ChronoComponent>>>,
     #[serde(rename = "chrono_counter")]
     pub chrono_counter: u32,
+    #[serde(rename = "group_components")]
+    pub group_components: Arc<SecondaryMap<StrokeKey, Arc<GroupComponent>>>,
+    #[serde(rename = "user_layers")]
+    pub user_layers: Arc<Vec<UserLayer>>,
+    #[serde(rename = "page_backgrounds")]
+    pub page_backgrounds: Arc<Vec<PageBackground>>,
+    /// The height of the fixed-size document before and after the page operation that was recorded with the entry.
+    ///
+    /// The document is not part of the store, so its height is restored from this when undoing or redoing.
+    #[serde(rename = "doc_height_change")]
+    pub doc_height_change: Option<(f64, f64)>,
 }
 
 impl Default for HistoryEntry {
@@ -49,8 +66,12 @@ impl Default for HistoryEntry {
             stroke_components: Arc::new(HopSlotMap::with_key()),
             trash_components: Arc::new(SecondaryMap::new()),
             chrono_components: Arc::new(SecondaryMap::new()),
+            group_components: Arc::new(SecondaryMap::new()),
 
             chrono_counter: 0,
+            user_layers: user_layers::default_user_layers(),
+            page_backgrounds: Arc::new(Vec::new()),
+            doc_height_change: None,
         }
     }
 }
@@ -62,8 +83,12 @@ impl Default for HistoryEntry {
 ///     * 'trash_components': Holds state whether the strokes are trashed
 ///     * 'selection_components': Holds state whether the strokes are selected
 ///     * 'chrono_components': Holds state about the chronological ordering
+///     * 'group_components': Holds state about the (nested) groups the strokes are members of
 ///     * 'render_components': Holds state about the rendering.
 ///
+/// Next to the components the store holds the properties of the user layers the strokes can be on,
+/// and the backgrounds that override the document background for single pages.
+///
 /// The systems are implemented as methods on StrokesStore, loosely categorized to the different components (but often modify others as well).
 /// Most systems take a key or a slice of keys, and iterate with them over the different components.
 /// There also is a different category of methods which return filtered keys.
@@ -84,6 +109,19 @@ pub struct StrokeStore {
     /// Value must be kept equal to the [ChronoComponent] of the newest inserted or modified stroke.
     #[serde(rename = "chrono_counter")]
     chrono_counter: u32,
+    #[serde(rename = "group_components")]
+    group_components: Arc<SecondaryMap<StrokeKey, Arc<GroupComponent>>>,
+    /// The properties of the user layers, in the order they are rendered.
+    #[serde(rename = "user_layers")]
+    user_layers: Arc<Vec<UserLayer>>,
+    /// The index of the user layer new strokes are inserted into.
+    #[serde(rename = "active_user_layer")]
+    active_user_layer: u32,
+    /// Backgrounds that override the document background for single pages, sorted by their page index.
+    ///
+    /// Only honored in layouts that have pages, see [Document::has_pages](crate::Document::has_pages).
+    #[serde(rename = "page_backgrounds")]
+    page_backgrounds: Arc<Vec<PageBackground>>,
     #[serde(skip)]
     render_components: SecondaryMap<StrokeKey, RenderComponent>,
     #[serde(skip)]
@@ -96,6 +134,14 @@ pub struct StrokeStore {
     /// Needs to be updated with `update_with_key()` when strokes changed their geometry or position!
     #[serde(skip)]
     key_tree: KeyTree,
+    /// The keys of the connectors that are attached to each stroke, for faster lookups when strokes are moved.
+    ///
+    /// Needs to be updated when connectors are inserted, removed or attached to other strokes!
+    #[serde(skip)]
+    connector_index: HashMap<StrokeKey, HashSet<StrokeKey>>,
+    /// The change of the height of the fixed-size document that is recorded with the next history entry.
+    #[serde(skip)]
+    doc_height_change: Option<(f64, f64)>,
 }
 
 impl Default for StrokeStore {
@@ -105,6 +151,7 @@ impl Default for StrokeStore {
             trash_components: Arc::new(SecondaryMap::new()),
             selection_components: Arc::new(SecondaryMap::new()),
             chrono_components: Arc::new(SecondaryMap::new()),
+            group_components: Arc::new(SecondaryMap::new()),
             render_components: SecondaryMap::new(),
 
             // Start off with state in the history
@@ -112,8 +159,13 @@ impl Default for StrokeStore {
             live_index: 0,
 
             key_tree: KeyTree::default(),
+            connector_index: HashMap::new(),
 
             chrono_counter: 0,
+            user_layers: user_layers::default_user_layers(),
+            active_user_layer: 0,
+            page_backgrounds: Arc::new(Vec::new()),
+            doc_height_change: None,
         }
     }
 }
@@ -132,12 +184,19 @@ impl StrokeStore {
         self.stroke_components = Arc::clone(&snapshot.stroke_components);
         self.chrono_components = Arc::clone(&snapshot.chrono_components);
         self.chrono_counter = snapshot.chrono_counter;
+        self.group_components = Arc::clone(&snapshot.group_components);
+        self.user_layers = Arc::clone(&snapshot.user_layers);
+        self.active_user_layer = snapshot.active_user_layer;
+        self.ensure_user_layers_for_strokes();
+        self.page_backgrounds = Arc::clone(&snapshot.page_backgrounds);
 
         self.update_geometry_for_strokes(&self.keys_unordered());
         self.rebuild_selection_components_slotmap();
         self.rebuild_trash_components_slotmap();
+        self.rebuild_group_components_slotmap();
         self.rebuild_render_components_slotmap();
         self.rebuild_rtree();
+        self.rebuild_connector_index();
         widget_flags |= self.clear_history(self.create_history_entry());
         widget_flags
     }
@@ -159,6 +218,10 @@ impl StrokeStore {
             && Arc::ptr_eq(&self.trash_components, &history_entry.trash_components)
             && Arc::ptr_eq(&self.chrono_components, &history_entry.chrono_components)
             && self.chrono_counter == history_entry.chrono_counter
+            && Arc::ptr_eq(&self.group_components, &history_entry.group_components)
+            && Arc::ptr_eq(&self.user_layers, &history_entry.user_layers)
+            && Arc::ptr_eq(&self.page_backgrounds, &history_entry.page_backgrounds)
+            && self.doc_height_change.is_none()
     }
 
     /// Create a history entry from the current state.
@@ -168,6 +231,10 @@ impl StrokeStore {
             trash_components: Arc::clone(&self.trash_components),
             chrono_components: Arc::clone(&self.chrono_components),
             chrono_counter: self.chrono_counter,
+            group_components: Arc::clone(&self.group_components),
+            user_layers: Arc::clone(&self.user_layers),
+            page_backgrounds: Arc::clone(&self.page_backgrounds),
+            doc_height_change: self.doc_height_change,
         }
     }
 
@@ -177,9 +244,15 @@ impl StrokeStore {
         self.trash_components = Arc::clone(&history_entry.trash_components);
         self.chrono_components = Arc::clone(&history_entry.chrono_components);
         self.chrono_counter = history_entry.chrono_counter;
+        self.group_components = Arc::clone(&history_entry.group_components);
+        self.user_layers = Arc::clone(&history_entry.user_layers);
+        self.clamp_active_user_layer();
+        self.page_backgrounds = Arc::clone(&history_entry.page_backgrounds);
+        self.doc_height_change = None;
 
-        // Since we don't store the rtree in the history, we need to rebuild it.
+        // Since we don't store the rtree and the connector index in the history, we need to rebuild them.
         self.rebuild_rtree();
+        self.rebuild_connector_index();
         self.rebuild_selection_components_slotmap();
         // Rebuild but retain the render components for the strokes that are found in the history entry.
         // This ensures that we are able to continue displaying the strokes after undo/redo while they are rerendered.
@@ -205,6 +278,7 @@ impl StrokeStore {
             let current = self.create_history_entry();
             self.history.push_back(current);
             self.live_index += 1;
+            self.doc_height_change = None;
 
             // truncate history if necessary
             while self.history.len() > Self::HISTORY_MAX_LEN {
@@ -236,6 +310,7 @@ impl StrokeStore {
 
             let current = self.create_history_entry();
             self.history[self.live_index] = current;
+            self.doc_height_change = None;
         } else {
             debug!("State has not changed, no need to update history with current state.");
         }
@@ -288,6 +363,16 @@ impl StrokeStore {
         widget_flags
     }
 
+    /// Set the change of the height of the fixed-size document, which is recorded with the next history entry.
+    pub(crate) fn set_doc_height_change(&mut self, height_before: f64, height_after: f64) {
+        self.doc_height_change = Some((height_before, height_after));
+    }
+
+    /// The change of the height of the fixed-size document that was recorded with the current history entry.
+    pub(crate) fn live_doc_height_change(&self) -> Option<(f64, f64)> {
+        self.history.get(self.live_index)?.doc_height_change
+    }
+
     pub(crate) fn can_undo(&self) -> bool {
         self.live_index > 0
     }
@@ -333,8 +418,10 @@ impl StrokeStore {
             key,
             Arc::new(ChronoComponent::new(self.chrono_counter, layer)),
         );
+        Arc::make_mut(&mut self.group_components).insert(key, Arc::new(GroupComponent::default()));
         self.render_components
             .insert(key, RenderComponent::default());
+        self.index_connector(key);
 
         key
     }
@@ -342,9 +429,12 @@ impl StrokeStore {
     /// Permanently remove a stroke with the given key from the store.
     #[allow(unused)]
     pub(crate) fn remove_stroke(&mut self, key: StrokeKey) -> Option<Stroke> {
+        self.unindex_connector(key);
+        self.connector_index.remove(&key);
         Arc::make_mut(&mut self.trash_components).remove(key);
         Arc::make_mut(&mut self.selection_components).remove(key);
         Arc::make_mut(&mut self.chrono_components).remove(key);
+        Arc::make_mut(&mut self.group_components).remove(key);
         self.render_components.remove(key);
 
         self.key_tree.remove_with_key(key);
@@ -359,12 +449,18 @@ impl StrokeStore {
         Arc::make_mut(&mut self.trash_components).clear();
         Arc::make_mut(&mut self.selection_components).clear();
         Arc::make_mut(&mut self.chrono_components).clear();
+        Arc::make_mut(&mut self.group_components).clear();
 
         self.chrono_counter = 0;
+        self.user_layers = user_layers::default_user_layers();
+        self.active_user_layer = 0;
+        self.page_backgrounds = Arc::new(Vec::new());
+        self.doc_height_change = None;
         let widget_flags = self.clear_history(HistoryEntry::default());
 
         self.render_components.clear();
         self.key_tree.clear();
+        self.connector_index.clear();
 
         widget_flags
     }
@@ -0,0 +1,92 @@
+// Imports
+use super::StrokeStore;
+use crate::document::{Background, PageBackground};
+use std::sync::Arc;
+
+/// Systems that are related to the backgrounds that override the document background for single pages.
+///
+/// They are kept in the store, so that they are recorded in the history together with the strokes
+/// that are moved along with their pages.
+impl StrokeStore {
+    /// The page background overrides, sorted by their page index.
+    pub(crate) fn page_backgrounds(&self) -> &[PageBackground] {
+        &self.page_backgrounds
+    }
+
+    /// A shared handle to the page background overrides, to check whether they have changed with [Arc::ptr_eq].
+    pub(crate) fn page_backgrounds_handle(&self) -> Arc<Vec<PageBackground>> {
+        Arc::clone(&self.page_backgrounds)
+    }
+
+    /// The background that overrides the document background for the page, if there is one.
+    pub(crate) fn page_background_override(&self, page: u32) -> Option<Background> {
+        self.page_backgrounds
+            .iter()
+            .find(|page_background| page_background.page == page)
+            .map(|page_background| page_background.background.clone())
+    }
+
+    /// Set the background override for the page, or remove it when `background` is None.
+    pub(crate) fn set_page_background_override(
+        &mut self,
+        page: u32,
+        background: Option<Background>,
+    ) {
+        let existing = self
+            .page_backgrounds
+            .binary_search_by_key(&page, |page_background| page_background.page);
+        match (existing, background) {
+            (Ok(i), Some(background)) => {
+                Arc::make_mut(&mut self.page_backgrounds)[i].background = background
+            }
+            (Ok(i), None) => {
+                Arc::make_mut(&mut self.page_backgrounds).remove(i);
+            }
+            (Err(i), Some(background)) => Arc::make_mut(&mut self.page_backgrounds)
+                .insert(i, PageBackground { page, background }),
+            (Err(_), None) => {}
+        }
+    }
+
+    /// Remove all page background overrides.
+    pub(crate) fn clear_page_backgrounds(&mut self) {
+        if !self.page_backgrounds.is_empty() {
+            Arc::make_mut(&mut self.page_backgrounds).clear();
+        }
+    }
+
+    /// Move the background overrides of the page at the given index and all following pages one page down,
+    /// to make room for a new page.
+    pub(crate) fn page_backgrounds_insert_page(&mut self, page: u32) {
+        if self.page_backgrounds.iter().all(|pb| pb.page < page) {
+            return;
+        }
+        for page_background in Arc::make_mut(&mut self.page_backgrounds).iter_mut() {
+            if page_background.page >= page {
+                page_background.page += 1;
+            }
+        }
+    }
+
+    /// Remove the background override of the page and move the ones of all following pages one page up.
+    pub(crate) fn page_backgrounds_remove_page(&mut self, page: u32) {
+        if self.page_backgrounds.iter().all(|pb| pb.page < page) {
+            return;
+        }
+        let page_backgrounds = Arc::make_mut(&mut self.page_backgrounds);
+        page_backgrounds.retain(|page_background| page_background.page != page);
+        for page_background in page_backgrounds.iter_mut() {
+            if page_background.page > page {
+                page_background.page -= 1;
+            }
+        }
+    }
+
+    /// Swap the background overrides of the two pages.
+    pub(crate) fn page_backgrounds_swap_pages(&mut self, first: u32, second: u32) {
+        let first_background = self.page_background_override(first);
+        let second_background = self.page_background_override(second);
+        self.set_page_background_override(first, second_background);
+        self.set_page_background_override(second, first_background);
+    }
+}
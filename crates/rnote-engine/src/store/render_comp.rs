@@ -175,6 +175,20 @@ impl StrokeStore {
         }
     }
 
+    /// Regenerate the rendering of the connectors that are attached to the given strokes.
+    ///
+    /// Used while strokes are transformed together with their rendering, so that the attached connectors,
+    /// which change their geometry, don't lag behind.
+    pub(crate) fn regenerate_rendering_for_attached_connectors(
+        &mut self,
+        keys: &[StrokeKey],
+        viewport: Aabb,
+        image_scale: f64,
+    ) {
+        let connector_keys = self.connectors_attached_to(keys);
+        self.regenerate_rendering_for_strokes(&connector_keys, viewport, image_scale);
+    }
+
     pub(crate) fn regenerate_rendering_for_stroke_threaded(
         &mut self,
         tasks_tx: EngineTaskSender,
@@ -375,6 +389,7 @@ impl StrokeStore {
                 }
                 // regenerate everything for strokes that don't support generating svgs for the last added elements
                 Stroke::ShapeStroke(_)
+                | Stroke::Connector(_)
                 | Stroke::TextStroke(_)
                 | Stroke::VectorImage(_)
                 | Stroke::BitmapImage(_) => {
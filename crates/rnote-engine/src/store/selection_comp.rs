@@ -5,6 +5,7 @@ use crate::strokes::content::GeneratedContentImages;
 use crate::strokes::Stroke;
 use p2d::bounding_volume::Aabb;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -44,6 +45,9 @@ impl StrokeStore {
     }
 
     /// Set if the stroke is currently selected.
+    ///
+    /// Selecting brings the stroke in front, deselecting keeps its order
+    /// so that reordering the selection is not undone when it gets deselected.
     pub(crate) fn set_selected(&mut self, key: StrokeKey, selected: bool) {
         if let Some(selection_comp) = Arc::make_mut(&mut self.selection_components)
             .get_mut(key)
@@ -51,7 +55,9 @@ impl StrokeStore {
         {
             selection_comp.selected = selected;
 
-            self.update_chrono_to_last(key);
+            if selected {
+                self.update_chrono_to_last(key);
+            }
         }
     }
 
@@ -98,13 +104,21 @@ impl StrokeStore {
     pub(crate) fn duplicate_selection(&mut self) -> Vec<StrokeKey> {
         let old_selected = self.selection_keys_as_rendered();
         self.set_selected_keys(&old_selected, false);
+        let mut key_map = HashMap::new();
 
         let new_selected = old_selected
             .iter()
             .filter_map(|&old_key| {
-                let new_key =
-                    self.insert_stroke((**self.stroke_components.get(old_key)?).clone(), None);
+                let new_key = self.insert_stroke(
+                    (**self.stroke_components.get(old_key)?).clone(),
+                    self.stroke_layer(old_key),
+                );
                 self.set_selected(new_key, true);
+                key_map.insert(old_key, new_key);
+                if let Some(group_comp) = self.group_components.get(old_key).map(|g| (**g).clone())
+                {
+                    self.set_group_component(new_key, group_comp);
+                }
 
                 // duplicate and insert the render images of the old stroke to avoid flickering
                 if let Some(render_comp) = self.render_components.get(old_key) {
@@ -125,6 +139,10 @@ impl StrokeStore {
             })
             .collect::<Vec<StrokeKey>>();
 
+        // Connectors stay attached to the strokes that were duplicated together with them
+        self.remap_connectors(&new_selected, &key_map);
+        self.regroup_with_unused_ids(&new_selected);
+
         // Offsetting the new selected stroke to make the duplication apparent
         self.translate_strokes(&new_selected, Stroke::IMPORT_OFFSET_DEFAULT);
         self.translate_strokes_images(&new_selected, Stroke::IMPORT_OFFSET_DEFAULT);
@@ -132,3 +150,38 @@ impl StrokeStore {
         new_selected
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strokes::BrushStroke;
+    use rnote_compose::penpath::{Element, Segment};
+    use rnote_compose::{PenPath, Style};
+
+    fn line_stroke(start: na::Vector2<f64>, end: na::Vector2<f64>) -> Stroke {
+        let mut path = PenPath::new(Element::new(start, 0.5));
+        path.segments.push(Segment::LineTo {
+            end: Element::new(end, 0.5),
+        });
+        Stroke::BrushStroke(BrushStroke::from_penpath(path, Style::default()))
+    }
+
+    #[test]
+    fn deselect_keeps_order() {
+        let mut store = StrokeStore::default();
+        let first = store.insert_stroke(
+            line_stroke(na::vector![0.0, 0.0], na::vector![10.0, 10.0]),
+            None,
+        );
+        let second = store.insert_stroke(
+            line_stroke(na::vector![5.0, 0.0], na::vector![15.0, 10.0]),
+            None,
+        );
+
+        store.set_selected_keys(&[second], true);
+        assert!(store.send_strokes_to_back(&store.selection_keys_as_rendered()));
+        store.set_selected_keys(&[second], false);
+
+        assert_eq!(store.stroke_keys_as_rendered(), vec![second, first]);
+    }
+}
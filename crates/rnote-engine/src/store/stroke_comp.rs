@@ -2,7 +2,7 @@
 use super::render_comp::RenderCompState;
 use super::StrokeKey;
 use crate::engine::StrokeContent;
-use crate::strokes::{Content, Stroke};
+use crate::strokes::{Connector, Content, Stroke};
 use crate::{StrokeStore, WidgetFlags};
 use geo::intersects::Intersects;
 use geo::prelude::Contains;
@@ -11,6 +11,7 @@ use rnote_compose::penpath::Element;
 use rnote_compose::shapes::Shapeable;
 use rnote_compose::transform::Transformable;
 use rnote_compose::Color;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tracing::error;
 
@@ -67,10 +68,12 @@ impl StrokeStore {
     }
 
     /// Storke keys in the order that they should be rendered.
+    ///
+    /// Strokes on hidden layers are not included.
     pub(crate) fn stroke_keys_as_rendered(&self) -> Vec<StrokeKey> {
         self.keys_sorted_chrono()
             .into_iter()
-            .filter(|&key| !(self.trashed(key).unwrap_or(false)))
+            .filter(|&key| !(self.trashed(key).unwrap_or(false)) && self.stroke_visible(key))
             .collect::<Vec<StrokeKey>>()
     }
 
@@ -81,7 +84,7 @@ impl StrokeStore {
     ) -> Vec<StrokeKey> {
         self.keys_sorted_chrono_intersecting_bounds(bounds)
             .into_iter()
-            .filter(|&key| !(self.trashed(key).unwrap_or(false)))
+            .filter(|&key| !(self.trashed(key).unwrap_or(false)) && self.stroke_visible(key))
             .collect::<Vec<StrokeKey>>()
     }
 
@@ -89,7 +92,7 @@ impl StrokeStore {
     pub(crate) fn stroke_keys_as_rendered_in_bounds(&self, bounds: Aabb) -> Vec<StrokeKey> {
         self.keys_sorted_chrono_in_bounds(bounds)
             .into_iter()
-            .filter(|&key| !(self.trashed(key).unwrap_or(false)))
+            .filter(|&key| !(self.trashed(key).unwrap_or(false)) && self.stroke_visible(key))
             .collect::<Vec<StrokeKey>>()
     }
 
@@ -190,6 +193,8 @@ impl StrokeStore {
 
     /// Translate the strokes by the offset.
     ///
+    /// Connectors attached to the strokes follow them.
+    ///
     /// The strokes then need to update their geometry and rendering.
     pub(crate) fn translate_strokes(&mut self, keys: &[StrokeKey], offset: na::Vector2<f64>) {
         keys.iter().for_each(|&key| {
@@ -204,6 +209,7 @@ impl StrokeStore {
                 }
             }
         });
+        self.update_connectors_attached_to(keys);
     }
 
     /// Translate the stroke rendering images.
@@ -235,6 +241,8 @@ impl StrokeStore {
 
     /// Rotate the stroke by the given angle (in radians) around the center.
     ///
+    /// Connectors attached to the strokes follow them.
+    ///
     /// Strokes then need to update their rendering.
     pub(crate) fn rotate_strokes(
         &mut self,
@@ -254,6 +262,7 @@ impl StrokeStore {
                 }
             }
         });
+        self.update_connectors_attached_to(keys);
     }
 
     /// Change the stroke and text color for the given keys.
@@ -285,6 +294,10 @@ impl StrokeStore {
                             text_stroke.text_style.color = color;
                             self.set_rendering_dirty(key);
                         }
+                        Stroke::Connector(connector) => {
+                            connector.style.set_stroke_color(color);
+                            self.set_rendering_dirty(key);
+                        }
                         _ => {}
                     }
                 }
@@ -326,6 +339,125 @@ impl StrokeStore {
         widget_flags
     }
 
+    /// Smooth the paths of the brush strokes of the given keys, removing jitter and reducing their segments.
+    ///
+    /// Strokes then need to update their rendering.
+    pub(crate) fn smooth_brushstrokes(&mut self, keys: &[StrokeKey]) -> WidgetFlags {
+        /// The radius of neighbouring elements that jitter is averaged over.
+        const WINDOW_RADIUS: usize = 2;
+        /// The maximum deviation of the smoothed path from the averaged elements.
+        const TOLERANCE: f64 = 1.0;
+        let mut widget_flags = WidgetFlags::default();
+
+        if keys.is_empty() {
+            return widget_flags;
+        }
+
+        keys.iter().for_each(|&key| {
+            if let Some(Stroke::BrushStroke(brushstroke)) =
+                Arc::make_mut(&mut self.stroke_components)
+                    .get_mut(key)
+                    .map(Arc::make_mut)
+            {
+                brushstroke.path = brushstroke.path.smoothed(WINDOW_RADIUS, TOLERANCE);
+                brushstroke.update_geometry();
+                let bounds = brushstroke.bounds();
+
+                self.key_tree.update_with_key(key, bounds);
+                self.set_rendering_dirty(key);
+            }
+        });
+
+        widget_flags.redraw = true;
+        widget_flags.store_modified = true;
+
+        widget_flags
+    }
+
+    /// Replace the brush strokes of the given keys whose paths are recognized as shapes with shape strokes.
+    ///
+    /// Returns the number of replaced strokes. The strokes then need to update their rendering.
+    pub(crate) fn recognize_shapes_in_brushstrokes(
+        &mut self,
+        keys: &[StrokeKey],
+    ) -> (usize, WidgetFlags) {
+        let mut widget_flags = WidgetFlags::default();
+        let mut replaced_keys = Vec::new();
+
+        keys.iter().for_each(|&key| {
+            let Some(Stroke::BrushStroke(brushstroke)) = self.get_stroke_ref(key) else {
+                return;
+            };
+            let Some(shapestroke) = brushstroke.to_recognized_shapestroke() else {
+                return;
+            };
+            let bounds = shapestroke.bounds();
+            if let Some(stroke) = self.get_stroke_mut(key) {
+                *stroke = Stroke::ShapeStroke(shapestroke);
+            }
+
+            self.key_tree.update_with_key(key, bounds);
+            self.set_rendering_dirty(key);
+            replaced_keys.push(key);
+        });
+
+        if !replaced_keys.is_empty() {
+            self.update_connectors_attached_to(&replaced_keys);
+            widget_flags.redraw = true;
+            widget_flags.store_modified = true;
+        }
+
+        (replaced_keys.len(), widget_flags)
+    }
+
+    /// Simplify the brush strokes of the given keys, removing elements that deviate less than the tolerance.
+    ///
+    /// Returns the number of removed elements. The strokes then need to update their rendering.
+    pub(crate) fn simplify_brushstrokes(
+        &mut self,
+        keys: &[StrokeKey],
+        tolerance: f64,
+    ) -> (usize, WidgetFlags) {
+        let mut widget_flags = WidgetFlags::default();
+        let mut removed = 0;
+
+        if keys.is_empty() || tolerance.is_nan() || tolerance <= 0.0 {
+            return (removed, widget_flags);
+        }
+
+        keys.iter().for_each(|&key| {
+            if let Some(Stroke::BrushStroke(brushstroke)) =
+                Arc::make_mut(&mut self.stroke_components)
+                    .get_mut(key)
+                    .map(Arc::make_mut)
+            {
+                let simplified = brushstroke.path.simplified(tolerance);
+                let n_removed = brushstroke
+                    .path
+                    .segments
+                    .len()
+                    .saturating_sub(simplified.segments.len());
+                if n_removed == 0 {
+                    return;
+                }
+                removed += n_removed;
+                brushstroke.path = simplified;
+                brushstroke.update_geometry();
+                let bounds = brushstroke.bounds();
+
+                self.key_tree.update_with_key(key, bounds);
+                self.set_rendering_dirty(key);
+            }
+        });
+
+        if removed > 0 {
+            widget_flags.redraw = true;
+            widget_flags.store_modified = true;
+        }
+
+        (removed, widget_flags)
+    }
+
     /// Change the fill color of the given keys.
     ///
     /// The strokes then need to update their rendering.
@@ -395,6 +527,8 @@ impl StrokeStore {
 
     /// Scale the strokes with the factor.
     ///
+    /// Connectors attached to the strokes follow them.
+    ///
     /// The strokes then need to update their rendering.
     pub(crate) fn scale_strokes(&mut self, keys: &[StrokeKey], scale: na::Vector2<f64>) {
         keys.iter().for_each(|&key| {
@@ -409,6 +543,7 @@ impl StrokeStore {
                 }
             }
         });
+        self.update_connectors_attached_to(keys);
     }
 
     /// Scale the stroke rendering images.
@@ -490,8 +625,8 @@ impl StrokeStore {
         self.keys_sorted_chrono_intersecting_bounds(bounds)
             .into_iter()
             .filter_map(|key| {
-                // skip if stroke is trashed
-                if self.trashed(key)? {
+                // skip if stroke is trashed or on a hidden or locked layer
+                if self.trashed(key)? || !self.stroke_editable(key) {
                     return None;
                 }
 
@@ -545,8 +680,8 @@ impl StrokeStore {
         self.keys_sorted_chrono_intersecting_bounds(bounds)
             .into_iter()
             .filter_map(|key| {
-                // skip if stroke is trashed
-                if self.trashed(key)? {
+                // skip if stroke is trashed or on a hidden or locked layer
+                if self.trashed(key)? || !self.stroke_editable(key) {
                     return None;
                 }
 
@@ -578,8 +713,8 @@ impl StrokeStore {
         self.keys_sorted_chrono_intersecting_bounds(viewport.merged(&aabb))
             .into_iter()
             .filter_map(|key| {
-                // skip if stroke is trashed
-                if self.trashed(key)? {
+                // skip if stroke is trashed or on a hidden or locked layer
+                if self.trashed(key)? || !self.stroke_editable(key) {
                     return None;
                 }
 
@@ -615,6 +750,9 @@ impl StrokeStore {
         self.stroke_keys_as_rendered_intersecting_bounds(bounds)
             .into_iter()
             .filter(|&key| {
+                if !self.stroke_editable(key) {
+                    return false;
+                }
                 if let Some(stroke) = self.stroke_components.get(key) {
                     stroke
                         .hitboxes()
@@ -687,26 +825,34 @@ impl StrokeStore {
     }
 
     pub(crate) fn fetch_stroke_content(&self, keys: &[StrokeKey]) -> StrokeContent {
-        let strokes = keys
+        let (keys, strokes): (Vec<StrokeKey>, Vec<Arc<Stroke>>) = keys
             .iter()
-            .filter_map(|k| self.stroke_components.get(*k).cloned())
-            .collect();
-
-        StrokeContent::default().with_strokes(strokes)
+            .filter_map(|k| Some((*k, self.stroke_components.get(*k).cloned()?)))
+            .unzip();
+        let group_components = self.clone_group_components(&keys);
+
+        StrokeContent::default()
+            .with_strokes(strokes)
+            .with_keys(keys)
+            .with_group_components(group_components)
     }
 
     /// Cut the strokes for the given keys and return them as stroke content.
     pub(crate) fn cut_stroke_content(&mut self, keys: &[StrokeKey]) -> StrokeContent {
-        let strokes = keys
+        let (keys, strokes): (Vec<StrokeKey>, Vec<Arc<Stroke>>) = keys
             .iter()
             .filter_map(|k| {
                 self.set_selected(*k, false);
                 self.set_trashed(*k, true);
-                self.stroke_components.get(*k).cloned()
+                Some((*k, self.stroke_components.get(*k).cloned()?))
             })
-            .collect();
+            .unzip();
+        let group_components = self.clone_group_components(&keys);
 
-        StrokeContent::default().with_strokes(strokes)
+        StrokeContent::default()
+            .with_strokes(strokes)
+            .with_keys(keys)
+            .with_group_components(group_components)
     }
 
     /// Paste the clipboard content as a selection.
@@ -727,8 +873,19 @@ impl StrokeStore {
             .strokes
             .iter()
             .fold(Aabb::new_invalid(), |acc, s| acc.merged(&s.bounds()));
-
-        clipboard_content
+        let clipboard_keys = if clipboard_content.keys.len() == clipboard_content.strokes.len() {
+            clipboard_content.keys
+        } else {
+            vec![]
+        };
+        let clipboard_group_components =
+            if clipboard_content.group_components.len() == clipboard_content.strokes.len() {
+                clipboard_content.group_components
+            } else {
+                vec![]
+            };
+
+        let inserted_keys = clipboard_content
             .strokes
             .into_iter()
             .map(|s| {
@@ -746,6 +903,173 @@ impl StrokeStore {
                 self.set_selected(key, true);
                 key
             })
+            .collect::<Vec<StrokeKey>>();
+
+        // Connectors stay attached to the strokes that were inserted together with them
+        let key_map = clipboard_keys
+            .into_iter()
+            .zip(inserted_keys.iter().copied())
+            .collect::<HashMap<StrokeKey, StrokeKey>>();
+        self.remap_connectors(&inserted_keys, &key_map);
+
+        // The inserted strokes keep their groups, separate from the groups of the strokes they were copied from
+        for (&key, group_comp) in inserted_keys.iter().zip(clipboard_group_components) {
+            self.set_group_component(key, group_comp);
+        }
+        self.regroup_with_unused_ids(&inserted_keys);
+
+        inserted_keys
+    }
+
+    /// Insert a connector between the strokes with the given keys, styled with the given style.
+    ///
+    /// Returns None if one of the strokes does not exist or is a connector itself.
+    ///
+    /// The connector then needs to update its rendering.
+    pub(crate) fn insert_connector(
+        &mut self,
+        start_key: StrokeKey,
+        end_key: StrokeKey,
+        style: rnote_compose::Style,
+    ) -> Option<StrokeKey> {
+        let connectable_bounds = |key: StrokeKey| match self.stroke_components.get(key)?.as_ref() {
+            Stroke::Connector(_) => None,
+            stroke => Some(stroke.bounds()),
+        };
+        let start_bounds = connectable_bounds(start_key)?;
+        let end_bounds = connectable_bounds(end_key)?;
+        let connector = Connector::between((start_key, start_bounds), (end_key, end_bounds), style);
+
+        Some(self.insert_stroke(Stroke::Connector(connector), None))
+    }
+
+    /// Recompute the geometry of the connectors that are attached to any of the given strokes.
+    ///
+    /// The connectors then need to update their rendering.
+    pub(crate) fn update_connectors_attached_to(&mut self, keys: &[StrokeKey]) {
+        for key in self.connectors_attached_to(keys) {
+            self.update_connector(key);
+        }
+    }
+
+    /// The keys of the connectors that are attached to any of the given strokes.
+    pub(crate) fn connectors_attached_to(&self, keys: &[StrokeKey]) -> Vec<StrokeKey> {
+        keys.iter()
+            .filter_map(|key| self.connector_index.get(key))
+            .flatten()
+            .copied()
+            .collect::<HashSet<StrokeKey>>()
+            .into_iter()
             .collect()
     }
+
+    /// Rebuild the index of the connectors that are attached to each stroke.
+    pub(crate) fn rebuild_connector_index(&mut self) {
+        self.connector_index.clear();
+        let connector_keys = self
+            .stroke_components
+            .iter()
+            .filter(|(_, stroke)| matches!(stroke.as_ref(), Stroke::Connector(_)))
+            .map(|(key, _)| key)
+            .collect::<Vec<StrokeKey>>();
+        for key in connector_keys {
+            self.index_connector(key);
+        }
+    }
+
+    /// Add the connector to the index of the strokes it is attached to.
+    pub(super) fn index_connector(&mut self, key: StrokeKey) {
+        let Some(Stroke::Connector(connector)) =
+            self.stroke_components.get(key).map(|s| s.as_ref())
+        else {
+            return;
+        };
+        for attached_key in [connector.start_anchor.key, connector.end_anchor.key]
+            .into_iter()
+            .flatten()
+        {
+            self.connector_index
+                .entry(attached_key)
+                .or_default()
+                .insert(key);
+        }
+    }
+
+    /// Remove the connector from the index of the strokes it is attached to.
+    pub(super) fn unindex_connector(&mut self, key: StrokeKey) {
+        let Some(Stroke::Connector(connector)) =
+            self.stroke_components.get(key).map(|s| s.as_ref())
+        else {
+            return;
+        };
+        for attached_key in [connector.start_anchor.key, connector.end_anchor.key]
+            .into_iter()
+            .flatten()
+        {
+            if let Some(connector_keys) = self.connector_index.get_mut(&attached_key) {
+                connector_keys.remove(&key);
+                if connector_keys.is_empty() {
+                    self.connector_index.remove(&attached_key);
+                }
+            }
+        }
+    }
+
+    /// Recompute the ends of the connector from the bounds of the strokes it is attached to.
+    ///
+    /// The connector then needs to update its rendering.
+    pub(crate) fn update_connector(&mut self, key: StrokeKey) {
+        let Some(Stroke::Connector(connector)) =
+            self.stroke_components.get(key).map(|s| s.as_ref())
+        else {
+            return;
+        };
+        let attached_bounds =
+            |anchor_key: Option<StrokeKey>| Some(self.stroke_components.get(anchor_key?)?.bounds());
+        let line = connector.anchored_line(
+            attached_bounds(connector.start_anchor.key),
+            attached_bounds(connector.end_anchor.key),
+        );
+        if line.start == connector.line.start && line.end == connector.line.end {
+            return;
+        }
+
+        if let Some(Stroke::Connector(connector)) = Arc::make_mut(&mut self.stroke_components)
+            .get_mut(key)
+            .map(Arc::make_mut)
+        {
+            connector.line = line;
+            connector.update_geometry();
+            self.key_tree.update_with_key(key, connector.bounds());
+            self.set_rendering_dirty(key);
+        }
+    }
+
+    /// Attach the connectors of the given keys to the strokes that their attached keys map to,
+    /// detaching them from strokes which are not in the map.
+    ///
+    /// The connectors then need to update their rendering.
+    pub(crate) fn remap_connectors(
+        &mut self,
+        keys: &[StrokeKey],
+        key_map: &HashMap<StrokeKey, StrokeKey>,
+    ) {
+        for &key in keys {
+            if !matches!(
+                self.stroke_components.get(key).map(|s| s.as_ref()),
+                Some(Stroke::Connector(_))
+            ) {
+                continue;
+            }
+            self.unindex_connector(key);
+            if let Some(Stroke::Connector(connector)) = Arc::make_mut(&mut self.stroke_components)
+                .get_mut(key)
+                .map(Arc::make_mut)
+            {
+                connector.remap_anchor_keys(|k| key_map.get(&k).copied());
+            }
+            self.index_connector(key);
+            self.update_connector(key);
+        }
+    }
 }
@@ -88,11 +88,14 @@ impl StrokeStore {
         self.stroke_keys_as_rendered_intersecting_bounds(viewport)
             .into_iter()
             .for_each(|key| {
+                if !self.stroke_editable(key) {
+                    return;
+                }
                 let mut trash_current_stroke = false;
 
                 if let Some(stroke) = self.stroke_components.get(key) {
                     match stroke.as_ref() {
-                        Stroke::BrushStroke(_) | Stroke::ShapeStroke(_) => {
+                        Stroke::BrushStroke(_) | Stroke::ShapeStroke(_) | Stroke::Connector(_) => {
                             // First check if eraser even intersects stroke bounds, avoiding unnecessary work
                             if eraser_bounds.intersects(&stroke.bounds()) {
                                 for hitbox in stroke.hitboxes().into_iter() {
@@ -138,6 +141,9 @@ impl StrokeStore {
             .stroke_keys_as_rendered_intersecting_bounds(viewport)
             .into_iter()
             .flat_map(|key| {
+                if !self.stroke_editable(key) {
+                    return vec![];
+                }
                 let Some(stroke) = Arc::make_mut(&mut self.stroke_components)
                     .get_mut(key)
                     .map(Arc::make_mut)
@@ -211,7 +217,7 @@ impl StrokeStore {
                             }
                         }
                     }
-                    Stroke::ShapeStroke(_) => {
+                    Stroke::ShapeStroke(_) | Stroke::Connector(_) => {
                         if eraser_bounds.intersects(&stroke_bounds) {
                             for hitbox_elem in stroke.hitboxes().iter() {
                                 if eraser_bounds.intersects(hitbox_elem) {
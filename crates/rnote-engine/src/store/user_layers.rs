@@ -0,0 +1,317 @@
+// Imports
+use super::chrono_comp::StrokeLayer;
+use super::{StrokeKey, StrokeStore};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// The properties of a user layer.
+///
+/// The user layer at index `i` in the store holds the properties for all strokes on [StrokeLayer::UserLayer] `i`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename = "user_layer")]
+pub struct UserLayer {
+    /// The name of the layer.
+    #[serde(rename = "name")]
+    pub name: String,
+    /// Whether the strokes on the layer are rendered.
+    #[serde(rename = "visible")]
+    pub visible: bool,
+    /// Whether the strokes on the layer are protected from being selected or erased.
+    #[serde(rename = "locked")]
+    pub locked: bool,
+}
+
+impl Default for UserLayer {
+    fn default() -> Self {
+        Self {
+            name: Self::default_name(0),
+            visible: true,
+            locked: false,
+        }
+    }
+}
+
+impl UserLayer {
+    /// The default name of the user layer at the given index.
+    pub fn default_name(index: u32) -> String {
+        format!("Layer {}", index + 1)
+    }
+
+    pub(crate) fn new_w_index(index: u32) -> Self {
+        Self {
+            name: Self::default_name(index),
+            ..Default::default()
+        }
+    }
+}
+
+/// The default user layers. There always needs to be at least one user layer.
+pub(crate) fn default_user_layers() -> Arc<Vec<UserLayer>> {
+    Arc::new(vec![UserLayer::default()])
+}
+
+/// Systems that are related to the user layers.
+impl StrokeStore {
+    /// The user layers, in the order they are rendered.
+    pub(crate) fn user_layers(&self) -> &[UserLayer] {
+        &self.user_layers
+    }
+
+    /// The index of the active user layer, which new strokes are inserted into.
+    pub(crate) fn active_user_layer(&self) -> u32 {
+        self.active_user_layer
+    }
+
+    /// The stroke layer of the active user layer.
+    pub(crate) fn active_stroke_layer(&self) -> StrokeLayer {
+        StrokeLayer::UserLayer(self.active_user_layer)
+    }
+
+    /// Set the active user layer.
+    ///
+    /// Returns true if the active user layer has changed.
+    pub(crate) fn set_active_user_layer(&mut self, index: u32) -> bool {
+        if index as usize >= self.user_layers.len() || index == self.active_user_layer {
+            return false;
+        }
+        self.active_user_layer = index;
+        true
+    }
+
+    /// Whether the strokes on the stroke layer are rendered.
+    ///
+    /// Strokes on non-user layers are always visible.
+    pub(crate) fn stroke_layer_visible(&self, layer: StrokeLayer) -> bool {
+        match layer {
+            StrokeLayer::UserLayer(index) => self
+                .user_layers
+                .get(index as usize)
+                .map(|user_layer| user_layer.visible)
+                .unwrap_or(true),
+            StrokeLayer::Highlighter | StrokeLayer::Image | StrokeLayer::Document => true,
+        }
+    }
+
+    /// Whether the strokes on the stroke layer can be selected or erased.
+    ///
+    /// Strokes on hidden or locked user layers can't be.
+    pub(crate) fn stroke_layer_editable(&self, layer: StrokeLayer) -> bool {
+        match layer {
+            StrokeLayer::UserLayer(index) => self
+                .user_layers
+                .get(index as usize)
+                .map(|user_layer| user_layer.visible && !user_layer.locked)
+                .unwrap_or(true),
+            StrokeLayer::Highlighter | StrokeLayer::Image | StrokeLayer::Document => true,
+        }
+    }
+
+    /// Whether the stroke is rendered, depending on the layer it is on.
+    pub(crate) fn stroke_visible(&self, key: StrokeKey) -> bool {
+        self.stroke_layer(key)
+            .map(|layer| self.stroke_layer_visible(layer))
+            .unwrap_or(true)
+    }
+
+    /// Whether the stroke can be selected or erased, depending on the layer it is on.
+    pub(crate) fn stroke_editable(&self, key: StrokeKey) -> bool {
+        self.stroke_layer(key)
+            .map(|layer| self.stroke_layer_editable(layer))
+            .unwrap_or(true)
+    }
+
+    /// All stroke keys that are on the given user layer, including trashed.
+    pub(crate) fn keys_on_user_layer(&self, index: u32) -> Vec<StrokeKey> {
+        self.chrono_components
+            .iter()
+            .filter_map(|(key, chrono_comp)| {
+                (chrono_comp.layer == StrokeLayer::UserLayer(index)).then_some(key)
+            })
+            .collect()
+    }
+
+    /// Deselects the selected strokes on the given user layer.
+    fn deselect_keys_on_user_layer(&mut self, index: u32) {
+        let selected_keys = self
+            .keys_on_user_layer(index)
+            .into_iter()
+            .filter(|&key| self.selected(key).unwrap_or(false))
+            .collect::<Vec<StrokeKey>>();
+        self.set_selected_keys(&selected_keys, false);
+    }
+
+    /// Adds a new user layer on top of all other user layers.
+    ///
+    /// Returns the index of the new layer.
+    pub(crate) fn add_user_layer(&mut self, name: Option<String>) -> u32 {
+        let index = self.user_layers.len() as u32;
+        let mut user_layer = UserLayer::new_w_index(index);
+        if let Some(name) = name {
+            user_layer.name = name;
+        }
+        Arc::make_mut(&mut self.user_layers).push(user_layer);
+        index
+    }
+
+    /// Add the given user layers, for example from another document.
+    ///
+    /// Layers that are equal to an existing layer are mapped to it, all others are added on top.
+    /// Returns the new index for each of the given layers.
+    pub(crate) fn import_user_layers(&mut self, user_layers: &[UserLayer]) -> Vec<u32> {
+        let mut mapped = Vec::<u32>::with_capacity(user_layers.len());
+
+        for user_layer in user_layers {
+            let existing = self
+                .user_layers
+                .iter()
+                .enumerate()
+                .position(|(i, existing)| existing == user_layer && !mapped.contains(&(i as u32)));
+            let index = match existing {
+                Some(i) => i as u32,
+                None => {
+                    Arc::make_mut(&mut self.user_layers).push(user_layer.clone());
+                    self.user_layers.len() as u32 - 1
+                }
+            };
+            mapped.push(index);
+        }
+
+        mapped
+    }
+
+    /// Ensures that there are user layers for all user layer indices up to (excluding) the given length.
+    pub(crate) fn ensure_user_layers_len(&mut self, len: usize) {
+        while self.user_layers.len() < len.max(1) {
+            let index = self.user_layers.len() as u32;
+            Arc::make_mut(&mut self.user_layers).push(UserLayer::new_w_index(index));
+        }
+    }
+
+    /// Ensures that every user layer a stroke is on exists and that the active user layer is valid.
+    pub(crate) fn ensure_user_layers_for_strokes(&mut self) {
+        let len = self
+            .chrono_components
+            .values()
+            .filter_map(|chrono_comp| match chrono_comp.layer {
+                StrokeLayer::UserLayer(index) => Some(index as usize + 1),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(1);
+        self.ensure_user_layers_len(len);
+        self.clamp_active_user_layer();
+    }
+
+    /// Clamps the active user layer to the existing user layers.
+    pub(crate) fn clamp_active_user_layer(&mut self) {
+        self.active_user_layer = self
+            .active_user_layer
+            .min(self.user_layers.len().saturating_sub(1) as u32);
+    }
+
+    /// Rename the user layer.
+    ///
+    /// Returns true if the user layer has changed.
+    pub(crate) fn rename_user_layer(&mut self, index: u32, name: String) -> bool {
+        match self.user_layers.get(index as usize) {
+            Some(user_layer) if user_layer.name != name => {}
+            _ => return false,
+        }
+        Arc::make_mut(&mut self.user_layers)[index as usize].name = name;
+        true
+    }
+
+    /// Show or hide the user layer. Strokes on hidden layers are deselected.
+    ///
+    /// Returns true if the user layer has changed.
+    pub(crate) fn set_user_layer_visible(&mut self, index: u32, visible: bool) -> bool {
+        match self.user_layers.get(index as usize) {
+            Some(user_layer) if user_layer.visible != visible => {}
+            _ => return false,
+        }
+        Arc::make_mut(&mut self.user_layers)[index as usize].visible = visible;
+        if !visible {
+            self.deselect_keys_on_user_layer(index);
+        }
+        true
+    }
+
+    /// Lock or unlock the user layer. Strokes on locked layers are deselected.
+    ///
+    /// Returns true if the user layer has changed.
+    pub(crate) fn set_user_layer_locked(&mut self, index: u32, locked: bool) -> bool {
+        match self.user_layers.get(index as usize) {
+            Some(user_layer) if user_layer.locked != locked => {}
+            _ => return false,
+        }
+        Arc::make_mut(&mut self.user_layers)[index as usize].locked = locked;
+        if locked {
+            self.deselect_keys_on_user_layer(index);
+        }
+        true
+    }
+
+    /// Moves the user layer from index `from` to index `to`, shifting the layers in between.
+    ///
+    /// The strokes are moved together with their layer.
+    ///
+    /// Returns true if the user layers have changed.
+    pub(crate) fn move_user_layer(&mut self, from: u32, to: u32) -> bool {
+        let len = self.user_layers.len() as u32;
+        if from >= len || to >= len || from == to {
+            return false;
+        }
+        let user_layer = Arc::make_mut(&mut self.user_layers).remove(from as usize);
+        Arc::make_mut(&mut self.user_layers).insert(to as usize, user_layer);
+
+        let remap = |index: u32| -> u32 {
+            if index == from {
+                to
+            } else if from < to && index > from && index <= to {
+                index - 1
+            } else if to < from && index >= to && index < from {
+                index + 1
+            } else {
+                index
+            }
+        };
+        self.remap_user_layers(remap);
+        self.active_user_layer = remap(self.active_user_layer);
+        true
+    }
+
+    /// Removes the user layer together with all strokes on it. The last remaining user layer can't be removed.
+    ///
+    /// The strokes are removed instead of trashed, because their layer index would point to the following layer
+    /// once the indices are shifted. Undo restores them from the history together with their layer.
+    ///
+    /// Returns true if the user layer was removed.
+    pub(crate) fn remove_user_layer(&mut self, index: u32) -> bool {
+        if index as usize >= self.user_layers.len() || self.user_layers.len() <= 1 {
+            return false;
+        }
+        for key in self.keys_on_user_layer(index) {
+            self.remove_stroke(key);
+        }
+        Arc::make_mut(&mut self.user_layers).remove(index as usize);
+
+        self.remap_user_layers(|i| if i > index { i - 1 } else { i });
+        if self.active_user_layer > index {
+            self.active_user_layer -= 1;
+        }
+        self.clamp_active_user_layer();
+        true
+    }
+
+    /// Reassign the user layer index of every stroke that is on a user layer.
+    fn remap_user_layers(&mut self, remap: impl Fn(u32) -> u32) {
+        for (_, chrono_comp) in Arc::make_mut(&mut self.chrono_components).iter_mut() {
+            if let StrokeLayer::UserLayer(index) = chrono_comp.layer {
+                let new_index = remap(index);
+                if new_index != index {
+                    Arc::make_mut(chrono_comp).layer = StrokeLayer::UserLayer(new_index);
+                }
+            }
+        }
+    }
+}
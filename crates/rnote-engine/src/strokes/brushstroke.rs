@@ -1,6 +1,6 @@
 // Imports
 use super::content::GeneratedContentImages;
-use super::Content;
+use super::{Content, ShapeStroke};
 use crate::Drawable;
 use crate::{
     render::{self},
@@ -10,7 +10,8 @@ use p2d::bounding_volume::{Aabb, BoundingVolume};
 use rnote_compose::ext::AabbExt;
 use rnote_compose::penpath::{Element, Segment};
 use rnote_compose::shapes::Shapeable;
-use rnote_compose::style::Composer;
+use rnote_compose::style::smooth::SmoothOptions;
+use rnote_compose::style::{Composer, PressureCurve};
 use rnote_compose::transform::Transformable;
 use rnote_compose::{PenPath, Style};
 use serde::{Deserialize, Serialize};
@@ -58,14 +59,18 @@ impl Content for BrushStroke {
             > IMAGES_STROKE_WIDTH_BOUNDS_THRESHOLD * bounds_extents[0]
             || self.style.stroke_width() > IMAGES_STROKE_WIDTH_BOUNDS_THRESHOLD * bounds_extents[1];
 
+        // Pencil strokes are always rendered as a single image,
+        // because the overlapping images of the segments would darken the grain at the joints.
+        let pencil_condition = matches!(self.style, Style::Pencil(_));
+
         // if these conditions evaluate true the stroke is rendered as a single image
-        let images = if image_size_condition || stroke_width_condition {
+        let images = if image_size_condition || stroke_width_condition || pencil_condition {
             // generate a single image when bounds are smaller than threshold
             match &self.style {
-                Style::Smooth(options) => {
+                Style::Smooth(_) | Style::Calligraphy(_) | Style::Pencil(_) => {
                     let image = render::Image::gen_with_piet(
                         |piet_cx| {
-                            self.path.draw_composed(piet_cx, options);
+                            self.path.draw_composed(piet_cx, &self.style);
                             Ok(())
                         },
                         bounds,
@@ -105,7 +110,8 @@ impl Content for BrushStroke {
             }
         } else {
             match &self.style {
-                Style::Smooth(options) => {
+                Style::Smooth(_) | Style::Calligraphy(_) | Style::Pencil(_) => {
+                    let mut style = self.style.clone();
                     let mut images = Vec::with_capacity(self.path.segments.len());
 
                     let mut prev = self.path.start;
@@ -113,10 +119,10 @@ impl Content for BrushStroke {
                         let seg_path = PenPath::new_w_segments(prev, [*seg]);
                         let image = render::Image::gen_with_piet(
                             |piet_cx| {
-                                seg_path.draw_composed(piet_cx, options);
+                                seg_path.draw_composed(piet_cx, &style);
                                 Ok(())
                             },
-                            seg_path.composed_bounds(options),
+                            seg_path.composed_bounds(&style),
                             image_scale,
                         );
                         match image {
@@ -126,6 +132,11 @@ impl Content for BrushStroke {
                             }
                         }
 
+                        // The dash pattern continues in the next segment
+                        if let Style::Smooth(options) = &mut style {
+                            options.advance_dash_offset(&prev, seg);
+                        }
+
                         prev = seg.end();
                     }
 
@@ -216,6 +227,8 @@ impl Drawable for BrushStroke {
                 unimplemented!()
             }
             Style::Textured(options) => self.path.draw_composed(cx, options),
+            Style::Calligraphy(options) => self.path.draw_composed(cx, options),
+            Style::Pencil(options) => self.path.draw_composed(cx, options),
         };
 
         cx.restore().map_err(|e| anyhow::anyhow!("{e:?}"))?;
@@ -229,6 +242,8 @@ impl Shapeable for BrushStroke {
             Style::Smooth(options) => self.path.composed_bounds(options),
             Style::Rough(_options) => unimplemented!(),
             Style::Textured(options) => self.path.composed_bounds(options),
+            Style::Calligraphy(options) => self.path.composed_bounds(options),
+            Style::Pencil(options) => self.path.composed_bounds(options),
         }
     }
 
@@ -247,6 +262,9 @@ impl Transformable for BrushStroke {
     }
     fn rotate(&mut self, angle: f64, center: na::Point2<f64>) {
         self.path.rotate(angle, center);
+        if let Style::Calligraphy(options) = &mut self.style {
+            options.nib_angle += angle;
+        }
     }
     fn scale(&mut self, scale: na::Vector2<f64>) {
         self.path.scale(scale);
@@ -258,6 +276,9 @@ impl Transformable for BrushStroke {
 }
 
 impl BrushStroke {
+    /// The tolerance the path is flattened with before recognizing its shape.
+    const SHAPE_RECOGNITION_FLATTEN_TOLERANCE: f64 = 0.5;
+
     pub fn new(start: Element, style: Style) -> Self {
         let path = PenPath::new(start);
 
@@ -290,6 +311,30 @@ impl BrushStroke {
         self.update_geometry();
     }
 
+    /// A shape stroke with the same color, width and dash pattern,
+    /// if the path is recognized as a shape.
+    pub fn to_recognized_shapestroke(&self) -> Option<ShapeStroke> {
+        let shape = rnote_compose::recognition::recognize_penpath(
+            &self.path,
+            Self::SHAPE_RECOGNITION_FLATTEN_TOLERANCE,
+        )?;
+        let (dash_array, dash_offset) = match &self.style {
+            Style::Smooth(options) => (options.dash_array.clone(), options.dash_offset),
+            _ => (vec![], 0.0),
+        };
+        let options = SmoothOptions {
+            stroke_width: self.style.stroke_width(),
+            stroke_color: self.style.stroke_color(),
+            fill_color: None,
+            pressure_curve: PressureCurve::Const,
+            dash_array,
+            dash_offset,
+            ..SmoothOptions::default()
+        };
+
+        Some(ShapeStroke::new(shape, Style::Smooth(options)))
+    }
+
     // internal method generating the current hitboxes.
     fn gen_hitboxes_int(&self) -> Vec<Aabb> {
         let stroke_width = self.style.stroke_width();
@@ -307,9 +352,20 @@ impl BrushStroke {
         image_scale: f64,
     ) -> Result<Option<render::Image>, anyhow::Error> {
         let image = match &self.style {
-            Style::Smooth(options) => {
+            Style::Smooth(_) | Style::Calligraphy(_) | Style::Pencil(_) => {
+                let mut style = self.style.clone();
                 let path_len = self.path.segments.len();
 
+                // The dash pattern continues from the preceding segments
+                if let Style::Smooth(options) = &mut style {
+                    let mut prev = self.path.start;
+                    for seg in self.path.segments[..path_len.saturating_sub(n_last_segments)].iter()
+                    {
+                        options.advance_dash_offset(&prev, seg);
+                        prev = seg.end();
+                    }
+                }
+
                 let start_el = self
                     .path
                     .segments
@@ -326,10 +382,10 @@ impl BrushStroke {
 
                 let image = render::Image::gen_with_piet(
                     |piet_cx| {
-                        range_path.draw_composed(piet_cx, options);
+                        range_path.draw_composed(piet_cx, &style);
                         Ok(())
                     },
-                    range_path.composed_bounds(options),
+                    range_path.composed_bounds(&style),
                     image_scale,
                 )?;
 
@@ -0,0 +1,247 @@
+// Imports
+use super::{content, Content};
+use crate::store::StrokeKey;
+use crate::Drawable;
+use p2d::bounding_volume::{Aabb, BoundingVolume};
+use rnote_compose::ext::AabbExt;
+use rnote_compose::shapes::{Line, Shapeable};
+use rnote_compose::style::Composer;
+use rnote_compose::transform::Transformable;
+use rnote_compose::Style;
+use serde::{Deserialize, Serialize};
+
+/// An end of a connector, which can be attached to another stroke.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename = "connector_anchor")]
+pub struct ConnectorAnchor {
+    /// The key of the stroke the anchor is attached to. The anchor is free when it is not attached.
+    #[serde(rename = "key")]
+    pub key: Option<StrokeKey>,
+    /// The position of the anchor relative to the bounds of the attached stroke,
+    /// with the mins of the bounds at [0.0, 0.0] and the maxs at [1.0, 1.0].
+    #[serde(
+        rename = "rel_pos",
+        with = "rnote_compose::serialize::na_vector2_f64_dp3"
+    )]
+    pub rel_pos: na::Vector2<f64>,
+}
+
+impl ConnectorAnchor {
+    /// A new anchor attached to the stroke with the given key and bounds, at the given position.
+    pub fn attached(key: StrokeKey, bounds: Aabb, pos: na::Vector2<f64>) -> Self {
+        let extents = bounds.extents();
+        let rel_pos = na::vector![
+            if extents[0] > 0.0 {
+                (pos[0] - bounds.mins[0]) / extents[0]
+            } else {
+                0.5
+            },
+            if extents[1] > 0.0 {
+                (pos[1] - bounds.mins[1]) / extents[1]
+            } else {
+                0.5
+            }
+        ];
+
+        Self {
+            key: Some(key),
+            rel_pos,
+        }
+    }
+
+    /// The position of the anchor on the given bounds of the attached stroke.
+    pub fn pos_on(&self, bounds: Aabb) -> na::Vector2<f64> {
+        bounds.mins.coords + self.rel_pos.component_mul(&bounds.extents())
+    }
+}
+
+/// A line connecting two strokes.
+///
+/// The ends are recomputed from the bounds of the attached strokes by the store when the strokes are transformed,
+/// so the connector stays attached to them. Rotated strokes keep the anchors relative to their new bounds.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename = "connector")]
+pub struct Connector {
+    /// The current geometry.
+    #[serde(rename = "line")]
+    pub line: Line,
+    #[serde(rename = "start_anchor")]
+    pub start_anchor: ConnectorAnchor,
+    #[serde(rename = "end_anchor")]
+    pub end_anchor: ConnectorAnchor,
+    #[serde(rename = "style")]
+    pub style: Style,
+    #[serde(skip)]
+    hitboxes: Vec<Aabb>,
+}
+
+impl Content for Connector {
+    fn draw_highlight(
+        &self,
+        cx: &mut impl piet::RenderContext,
+        total_zoom: f64,
+    ) -> anyhow::Result<()> {
+        const PATH_HIGHLIGHT_MIN_STROKE_WIDTH: f64 = 5.0;
+        const DRAW_BOUNDS_THRESHOLD_AREA: f64 = 10_u32.pow(2) as f64;
+        let bounds = self.bounds();
+
+        if bounds.scale(total_zoom).volume() < DRAW_BOUNDS_THRESHOLD_AREA {
+            cx.fill(bounds.to_kurbo_rect(), &content::CONTENT_HIGHLIGHT_COLOR);
+        } else {
+            cx.stroke_styled(
+                self.line.outline_path(),
+                &content::CONTENT_HIGHLIGHT_COLOR,
+                (PATH_HIGHLIGHT_MIN_STROKE_WIDTH / total_zoom)
+                    .max(self.style.stroke_width() + 10.0 / total_zoom),
+                &piet::StrokeStyle::new()
+                    .line_join(piet::LineJoin::Round)
+                    .line_cap(piet::LineCap::Round),
+            );
+        }
+
+        Ok(())
+    }
+
+    fn update_geometry(&mut self) {
+        let width = self.style.stroke_width();
+
+        self.hitboxes = self
+            .line
+            .hitboxes()
+            .into_iter()
+            .map(|hitbox| hitbox.loosened(width * 0.5))
+            .collect();
+    }
+}
+
+impl Drawable for Connector {
+    fn draw(&self, cx: &mut impl piet::RenderContext, _image_scale: f64) -> anyhow::Result<()> {
+        cx.save().map_err(|e| anyhow::anyhow!("{e:?}"))?;
+
+        self.line.draw_composed(cx, &self.style);
+
+        cx.restore().map_err(|e| anyhow::anyhow!("{e:?}"))?;
+        Ok(())
+    }
+}
+
+impl Shapeable for Connector {
+    fn bounds(&self) -> Aabb {
+        match &self.style {
+            Style::Smooth(options) => self.line.composed_bounds(options),
+            Style::Rough(options) => self.line.composed_bounds(options),
+            Style::Textured(_) | Style::Calligraphy(_) | Style::Pencil(_) => {
+                self.line.bounds().loosened(self.style.stroke_width() * 0.5)
+            }
+        }
+    }
+
+    fn hitboxes(&self) -> Vec<Aabb> {
+        self.hitboxes.clone()
+    }
+
+    fn outline_path(&self) -> kurbo::BezPath {
+        self.line.outline_path()
+    }
+}
+
+impl Transformable for Connector {
+    fn translate(&mut self, offset: na::Vector2<f64>) {
+        self.line.translate(offset);
+    }
+    fn rotate(&mut self, angle: f64, center: na::Point2<f64>) {
+        self.line.rotate(angle, center);
+    }
+    fn scale(&mut self, scale: na::Vector2<f64>) {
+        self.line.scale(scale);
+        // Using the geometric mean behaves the best when scaling non-uniformly.
+        let scale_scalar = (scale[0] * scale[1]).sqrt();
+        self.style
+            .set_stroke_width(self.style.stroke_width() * scale_scalar);
+    }
+}
+
+impl Connector {
+    pub fn new(
+        line: Line,
+        start_anchor: ConnectorAnchor,
+        end_anchor: ConnectorAnchor,
+        style: Style,
+    ) -> Self {
+        let mut connector = Self {
+            line,
+            start_anchor,
+            end_anchor,
+            style,
+            hitboxes: vec![],
+        };
+        connector.update_geometry();
+
+        connector
+    }
+
+    /// A new connector between the strokes with the given keys and bounds.
+    ///
+    /// The ends are attached to the midpoints of the edges of the bounds that are closest to each other.
+    pub fn between(
+        (start_key, start_bounds): (StrokeKey, Aabb),
+        (end_key, end_bounds): (StrokeKey, Aabb),
+        style: Style,
+    ) -> Self {
+        let edge_midpoints = |bounds: Aabb| {
+            let center = bounds.center().coords;
+            [
+                na::vector![bounds.mins[0], center[1]],
+                na::vector![bounds.maxs[0], center[1]],
+                na::vector![center[0], bounds.mins[1]],
+                na::vector![center[0], bounds.maxs[1]],
+            ]
+        };
+        let (start, end) = edge_midpoints(start_bounds)
+            .into_iter()
+            .flat_map(|start| {
+                edge_midpoints(end_bounds)
+                    .into_iter()
+                    .map(move |end| (start, end))
+            })
+            .min_by(|(a_start, a_end), (b_start, b_end)| {
+                (a_end - a_start)
+                    .magnitude()
+                    .total_cmp(&(b_end - b_start).magnitude())
+            })
+            .unwrap_or((start_bounds.center().coords, end_bounds.center().coords));
+
+        Self::new(
+            Line { start, end },
+            ConnectorAnchor::attached(start_key, start_bounds, start),
+            ConnectorAnchor::attached(end_key, end_bounds, end),
+            style,
+        )
+    }
+
+    /// Whether one of the ends is attached to the stroke with the given key.
+    pub fn attached_to(&self, key: StrokeKey) -> bool {
+        self.start_anchor.key == Some(key) || self.end_anchor.key == Some(key)
+    }
+
+    /// The line with the ends moved to the anchors on the given bounds of the attached strokes.
+    ///
+    /// Ends for which no bounds are given keep their current position.
+    pub fn anchored_line(&self, start_bounds: Option<Aabb>, end_bounds: Option<Aabb>) -> Line {
+        Line {
+            start: start_bounds
+                .map(|bounds| self.start_anchor.pos_on(bounds))
+                .unwrap_or(self.line.start),
+            end: end_bounds
+                .map(|bounds| self.end_anchor.pos_on(bounds))
+                .unwrap_or(self.line.end),
+        }
+    }
+
+    /// Replace the keys of the attached strokes by the keys they map to,
+    /// detaching the ends whose keys are mapped to `None`.
+    pub fn remap_anchor_keys(&mut self, map: impl Fn(StrokeKey) -> Option<StrokeKey>) {
+        self.start_anchor.key = self.start_anchor.key.and_then(&map);
+        self.end_anchor.key = self.end_anchor.key.and_then(&map);
+    }
+}
@@ -1,6 +1,7 @@
 // Modules
 pub mod bitmapimage;
 pub mod brushstroke;
+pub mod connector;
 pub mod content;
 pub mod resize;
 pub mod shapestroke;
@@ -11,6 +12,7 @@ pub mod vectorimage;
 // Re-exports
 pub use bitmapimage::BitmapImage;
 pub use brushstroke::BrushStroke;
+pub use connector::Connector;
 pub use content::Content;
 pub use resize::Resize;
 pub use shapestroke::ShapeStroke;
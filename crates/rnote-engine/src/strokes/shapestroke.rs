@@ -71,7 +71,7 @@ impl Shapeable for ShapeStroke {
         match &self.style {
             Style::Smooth(options) => self.shape.composed_bounds(options),
             Style::Rough(options) => self.shape.composed_bounds(options),
-            Style::Textured(_) => self.shape.bounds(),
+            Style::Textured(_) | Style::Calligraphy(_) | Style::Pencil(_) => self.shape.bounds(),
         }
     }
 
@@ -1,6 +1,7 @@
 // Imports
 use super::bitmapimage::BitmapImage;
 use super::brushstroke::BrushStroke;
+use super::connector::Connector;
 use super::content::GeneratedContentImages;
 use super::shapestroke::ShapeStroke;
 use super::vectorimage::VectorImage;
@@ -33,6 +34,8 @@ pub enum Stroke {
     VectorImage(VectorImage),
     #[serde(rename = "bitmapimage")]
     BitmapImage(BitmapImage),
+    #[serde(rename = "connector")]
+    Connector(Connector),
 }
 
 impl Content for Stroke {
@@ -43,6 +46,7 @@ impl Content for Stroke {
             Stroke::TextStroke(textstroke) => textstroke.gen_svg(),
             Stroke::VectorImage(vectorimage) => vectorimage.gen_svg(),
             Stroke::BitmapImage(bitmapimage) => bitmapimage.gen_svg(),
+            Stroke::Connector(connector) => connector.gen_svg(),
         }
     }
 
@@ -57,6 +61,7 @@ impl Content for Stroke {
             Stroke::TextStroke(textstroke) => textstroke.gen_images(viewport, image_scale),
             Stroke::VectorImage(vectorimage) => vectorimage.gen_images(viewport, image_scale),
             Stroke::BitmapImage(bitmapimage) => bitmapimage.gen_images(viewport, image_scale),
+            Stroke::Connector(connector) => connector.gen_images(viewport, image_scale),
         }
     }
 
@@ -71,6 +76,7 @@ impl Content for Stroke {
             Stroke::TextStroke(textstroke) => textstroke.draw_highlight(cx, total_zoom),
             Stroke::VectorImage(vectorimage) => vectorimage.draw_highlight(cx, total_zoom),
             Stroke::BitmapImage(bitmapimage) => bitmapimage.draw_highlight(cx, total_zoom),
+            Stroke::Connector(connector) => connector.draw_highlight(cx, total_zoom),
         }
     }
 
@@ -81,6 +87,7 @@ impl Content for Stroke {
             Stroke::TextStroke(textstroke) => textstroke.update_geometry(),
             Stroke::VectorImage(vectorimage) => vectorimage.update_geometry(),
             Stroke::BitmapImage(bitmapimage) => bitmapimage.update_geometry(),
+            Stroke::Connector(connector) => connector.update_geometry(),
         }
     }
 }
@@ -93,6 +100,7 @@ impl Drawable for Stroke {
             Stroke::TextStroke(textstroke) => textstroke.draw(cx, image_scale),
             Stroke::VectorImage(vectorimage) => vectorimage.draw(cx, image_scale),
             Stroke::BitmapImage(bitmapimage) => bitmapimage.draw(cx, image_scale),
+            Stroke::Connector(connector) => connector.draw(cx, image_scale),
         }
     }
 
@@ -103,6 +111,7 @@ impl Drawable for Stroke {
             Stroke::TextStroke(textstroke) => textstroke.draw_to_cairo(cx, image_scale),
             Stroke::VectorImage(vectorimage) => vectorimage.draw_to_cairo(cx, image_scale),
             Stroke::BitmapImage(bitmapimage) => bitmapimage.draw_to_cairo(cx, image_scale),
+            Stroke::Connector(connector) => connector.draw_to_cairo(cx, image_scale),
         }
     }
 }
@@ -115,6 +124,7 @@ impl Shapeable for Stroke {
             Self::TextStroke(textstroke) => textstroke.bounds(),
             Self::VectorImage(vectorimage) => vectorimage.bounds(),
             Self::BitmapImage(bitmapimage) => bitmapimage.bounds(),
+            Self::Connector(connector) => connector.bounds(),
         }
     }
 
@@ -125,6 +135,7 @@ impl Shapeable for Stroke {
             Self::TextStroke(textstroke) => textstroke.hitboxes(),
             Self::VectorImage(vectorimage) => vectorimage.hitboxes(),
             Self::BitmapImage(bitmapimage) => bitmapimage.hitboxes(),
+            Self::Connector(connector) => connector.hitboxes(),
         }
     }
 
@@ -135,6 +146,7 @@ impl Shapeable for Stroke {
             Self::TextStroke(textstroke) => textstroke.outline_path(),
             Self::VectorImage(vectorimage) => vectorimage.outline_path(),
             Self::BitmapImage(bitmapimage) => bitmapimage.outline_path(),
+            Self::Connector(connector) => connector.outline_path(),
         }
     }
 }
@@ -157,6 +169,9 @@ impl Transformable for Stroke {
             Self::BitmapImage(bitmapimage) => {
                 bitmapimage.translate(offset);
             }
+            Self::Connector(connector) => {
+                connector.translate(offset);
+            }
         }
     }
 
@@ -177,6 +192,9 @@ impl Transformable for Stroke {
             Self::BitmapImage(bitmapimage) => {
                 bitmapimage.rotate(angle, center);
             }
+            Self::Connector(connector) => {
+                connector.rotate(angle, center);
+            }
         }
     }
 
@@ -197,6 +215,9 @@ impl Transformable for Stroke {
             Self::BitmapImage(bitmapimage) => {
                 bitmapimage.scale(scale);
             }
+            Self::Connector(connector) => {
+                connector.scale(scale);
+            }
         }
     }
 }
@@ -210,6 +231,7 @@ impl Stroke {
             Stroke::BrushStroke(_) => StrokeLayer::UserLayer(0),
             Stroke::ShapeStroke(_) => StrokeLayer::UserLayer(0),
             Stroke::TextStroke(_) => StrokeLayer::UserLayer(0),
+            Stroke::Connector(_) => StrokeLayer::UserLayer(0),
             Stroke::VectorImage(_) | Stroke::BitmapImage(_) => StrokeLayer::Image,
         }
     }
@@ -255,6 +277,15 @@ impl Stroke {
 
                 true
             }
+            Stroke::Connector(connector) => {
+                if let Some(color) = connector.style.stroke_color() {
+                    connector
+                        .style
+                        .set_stroke_color(color.to_inverted_brightness_color());
+                }
+
+                true
+            }
             Stroke::VectorImage(_) => false,
             Stroke::BitmapImage(_) => false,
         }
@@ -296,15 +327,27 @@ impl Stroke {
 
                 true
             }
+            Stroke::Connector(connector) => {
+                if let Some(color) = connector.style.stroke_color() {
+                    connector.style.set_stroke_color(color.to_darkest_color());
+                }
+
+                true
+            }
             Stroke::VectorImage(_) => false,
             Stroke::BitmapImage(_) => false,
         }
     }
 
+    /// Create a stroke from a Xournal++ stroke.
+    ///
+    /// `layer` is the stroke layer the Xournal++ layer containing the stroke maps to.
+    /// Highlighter strokes are always placed on the highlighter layer.
     pub fn from_xoppstroke(
         stroke: xoppformat::XoppStroke,
         offset: na::Vector2<f64>,
         target_dpi: f64,
+        layer: StrokeLayer,
     ) -> Result<(Self, StrokeLayer), anyhow::Error> {
         let mut widths: Vec<f64> = stroke
             .width
@@ -332,7 +375,7 @@ impl Stroke {
         let layer = match stroke.tool {
             xoppformat::XoppTool::Pen => {
                 smooth_options.stroke_color = Some(crate::utils::color_from_xopp(stroke.color));
-                StrokeLayer::UserLayer(0)
+                layer
             }
             xoppformat::XoppTool::Highlighter => {
                 let mut color = crate::utils::color_from_xopp(stroke.color);
@@ -344,7 +387,7 @@ impl Stroke {
             }
             xoppformat::XoppTool::Eraser => {
                 smooth_options.stroke_color = Some(Color::WHITE);
-                StrokeLayer::UserLayer(0)
+                layer
             }
         };
 
@@ -368,6 +411,16 @@ impl Stroke {
         };
 
         smooth_options.stroke_width = stroke_width;
+        if stroke_width > 0.0 {
+            smooth_options.dash_array = stroke
+                .dashes
+                .iter()
+                .map(|&dash| {
+                    crate::utils::convert_value_dpi(dash, xoppformat::XoppFile::DPI, target_dpi)
+                        / stroke_width
+                })
+                .collect();
+        }
 
         let penpath = PenPath::try_from_elements(
             coords
@@ -430,6 +483,10 @@ impl Stroke {
     pub fn into_xopp(self, current_dpi: f64) -> Option<xoppformat::XoppStrokeType> {
         match self {
             Stroke::BrushStroke(brushstroke) => {
+                let dash_pattern = match &brushstroke.style {
+                    Style::Smooth(options) => options.absolute_dash_pattern(),
+                    _ => None,
+                };
                 let (stroke_width, color): (f64, XoppColor) = match &brushstroke.style {
                     // Return early if color is None
                     Style::Smooth(options) => (
@@ -444,6 +501,19 @@ impl Stroke {
                         options.stroke_width,
                         crate::utils::xoppcolor_from_color(options.stroke_color?),
                     ),
+                    Style::Calligraphy(options) => (
+                        options.stroke_width,
+                        crate::utils::xoppcolor_from_color(options.stroke_color?),
+                    ),
+                    // Xopp has no grain, so the opacity is approximated with the color alpha
+                    Style::Pencil(options) => {
+                        let mut color = options.stroke_color?;
+                        color.a *= options.opacity.clamp(0.0, 1.0);
+                        (
+                            options.stroke_width,
+                            crate::utils::xoppcolor_from_color(color),
+                        )
+                    }
                 };
 
                 let tool = xoppformat::XoppTool::Pen;
@@ -462,6 +532,12 @@ impl Stroke {
                         Style::Smooth(options) => {
                             options.pressure_curve.apply(stroke_width, element.pressure)
                         }
+                        Style::Calligraphy(options) => {
+                            options.pressure_curve.apply(stroke_width, element.pressure)
+                        }
+                        Style::Pencil(options) => {
+                            options.pressure_curve.apply(stroke_width, element.pressure)
+                        }
                         Style::Rough(_) | Style::Textured(_) => stroke_width * element.pressure,
                     })
                     .collect();
@@ -483,12 +559,29 @@ impl Stroke {
                     })
                     .collect::<Vec<na::Vector2<f64>>>();
 
+                // Xopp has no dash offset, so it is dropped
+                let dashes = dash_pattern
+                    .map(|(dash_pattern, _)| {
+                        dash_pattern
+                            .into_iter()
+                            .map(|dash| {
+                                utils::convert_value_dpi(
+                                    dash,
+                                    current_dpi,
+                                    xoppformat::XoppFile::DPI,
+                                )
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
                 Some(xoppformat::XoppStrokeType::XoppStroke(
                     xoppformat::XoppStroke {
                         tool,
                         color,
                         width: width_vec,
                         coords,
+                        dashes,
                         fill: None,
                         timestamp: None,
                         audio_filename: None,
@@ -670,6 +763,48 @@ impl Stroke {
                     },
                 ))
             }
+            Stroke::Connector(connector) => {
+                let png_data = match connector.export_to_bitmap_image_bytes(
+                    image::ImageFormat::Png,
+                    Engine::STROKE_EXPORT_IMAGE_SCALE,
+                ) {
+                    Ok(image_bytes) => image_bytes,
+                    Err(e) => {
+                        error!("Converting Connector to XoppImage failed, Err: {e:?}");
+                        return None;
+                    }
+                };
+                let connector_bounds = connector.bounds();
+
+                Some(xoppformat::XoppStrokeType::XoppImage(
+                    xoppformat::XoppImage {
+                        left: utils::convert_value_dpi(
+                            connector_bounds.mins[0],
+                            current_dpi,
+                            xoppformat::XoppFile::DPI,
+                        ),
+                        top: utils::convert_value_dpi(
+                            connector_bounds.mins[1],
+                            current_dpi,
+                            xoppformat::XoppFile::DPI,
+                        ),
+                        right: utils::convert_value_dpi(
+                            connector_bounds.maxs[0],
+                            current_dpi,
+                            xoppformat::XoppFile::DPI,
+                        ),
+                        bottom: utils::convert_value_dpi(
+                            connector_bounds.maxs[1],
+                            current_dpi,
+                            xoppformat::XoppFile::DPI,
+                        ),
+                        data: base64::Engine::encode(
+                            &base64::engine::general_purpose::STANDARD,
+                            png_data,
+                        ),
+                    },
+                ))
+            }
         }
     }
 }
@@ -4,6 +4,7 @@ use crate::{Camera, Drawable};
 use itertools::Itertools;
 use kurbo::Shape;
 use p2d::bounding_volume::Aabb;
+use pangocairo::pango;
 use piet::{RenderContext, TextLayout, TextLayoutBuilder};
 use rnote_compose::ext::{AabbExt, Affine2Ext, Vector2Ext};
 use rnote_compose::shapes::Shapeable;
@@ -178,6 +179,66 @@ pub struct RangedTextAttribute {
     pub attribute: TextAttribute,
 }
 
+/// The attributes of a run of text, with the text style and all ranged text attributes applied.
+#[derive(Debug, Clone)]
+pub(crate) struct ResolvedTextAttributes {
+    pub(crate) font_family: String,
+    pub(crate) font_size: f64,
+    pub(crate) font_weight: u16,
+    pub(crate) font_style: FontStyle,
+    pub(crate) color: Color,
+    pub(crate) underline: bool,
+    pub(crate) strikethrough: bool,
+}
+
+impl ResolvedTextAttributes {
+    /// Font weights starting from semi-bold are considered bold.
+    pub(crate) const BOLD_FONT_WEIGHT_MIN: u16 = 600;
+
+    fn apply(&mut self, text_attribute: &TextAttribute) {
+        match text_attribute {
+            TextAttribute::FontFamily(font_family) => self.font_family.clone_from(font_family),
+            TextAttribute::FontSize(font_size) => self.font_size = *font_size,
+            TextAttribute::FontWeight(font_weight) => self.font_weight = *font_weight,
+            TextAttribute::TextColor(color) => self.color = *color,
+            TextAttribute::Style(font_style) => self.font_style = *font_style,
+            TextAttribute::Underline(underline) => self.underline = *underline,
+            TextAttribute::Strikethrough(strikethrough) => self.strikethrough = *strikethrough,
+        }
+    }
+
+    pub(crate) fn bold(&self) -> bool {
+        self.font_weight >= Self::BOLD_FONT_WEIGHT_MIN
+    }
+
+    pub(crate) fn italic(&self) -> bool {
+        matches!(self.font_style, FontStyle::Italic)
+    }
+
+    /// The pango weight that is nearest to the font weight.
+    pub(crate) fn pango_weight(&self) -> pango::Weight {
+        const PANGO_WEIGHTS: [(u16, pango::Weight); 12] = [
+            (100, pango::Weight::Thin),
+            (200, pango::Weight::Ultralight),
+            (300, pango::Weight::Light),
+            (350, pango::Weight::Semilight),
+            (380, pango::Weight::Book),
+            (400, pango::Weight::Normal),
+            (500, pango::Weight::Medium),
+            (600, pango::Weight::Semibold),
+            (700, pango::Weight::Bold),
+            (800, pango::Weight::Ultrabold),
+            (900, pango::Weight::Heavy),
+            (1000, pango::Weight::Ultraheavy),
+        ];
+        PANGO_WEIGHTS
+            .iter()
+            .min_by_key(|(weight, _)| weight.abs_diff(self.font_weight))
+            .map(|(_, pango_weight)| *pango_weight)
+            .unwrap_or(pango::Weight::Normal)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default, rename = "text_style")]
 pub struct TextStyle {
@@ -283,6 +344,107 @@ impl TextStyle {
             .map_err(|e| anyhow::anyhow!("Building piet text layout failed, Err: {e:?}"))
     }
 
+    /// Build a pango layout for the given cairo context, with the same attributes as the piet text layout.
+    pub(crate) fn build_pango_layout(&self, cx: &cairo::Context, text: &str) -> pango::Layout {
+        let pango_layout = pangocairo::functions::create_layout(cx);
+        pango_layout.set_text(text);
+        pango_layout.set_alignment(match self.alignment {
+            TextAlignment::Start | TextAlignment::Fill => pango::Alignment::Left,
+            TextAlignment::Center => pango::Alignment::Center,
+            TextAlignment::End => pango::Alignment::Right,
+        });
+        pango_layout.set_justify(matches!(self.alignment, TextAlignment::Fill));
+        if let Some(max_width) = self.max_width {
+            pango_layout.set_width((max_width * f64::from(pango::SCALE)) as i32);
+            pango_layout.set_wrap(pango::WrapMode::WordChar);
+        }
+
+        let attr_list = pango::AttrList::new();
+        for (range, attributes) in self.resolved_text_runs(text) {
+            let to_u16 = |v: f64| (v.clamp(0.0, 1.0) * f64::from(u16::MAX)).round() as u16;
+            let mut run_attrs: Vec<pango::Attribute> = vec![
+                pango::AttrString::new_family(&attributes.font_family).into(),
+                pango::AttrSize::new_size_absolute(
+                    (attributes.font_size * f64::from(pango::SCALE)).round() as i32,
+                )
+                .into(),
+                pango::AttrInt::new_weight(attributes.pango_weight()).into(),
+                pango::AttrInt::new_style(if attributes.italic() {
+                    pango::Style::Italic
+                } else {
+                    pango::Style::Normal
+                })
+                .into(),
+                pango::AttrColor::new_foreground(
+                    to_u16(attributes.color.r),
+                    to_u16(attributes.color.g),
+                    to_u16(attributes.color.b),
+                )
+                .into(),
+                pango::AttrInt::new_foreground_alpha(to_u16(attributes.color.a)).into(),
+            ];
+            if attributes.underline {
+                run_attrs.push(pango::AttrInt::new_underline(pango::Underline::Single).into());
+            }
+            if attributes.strikethrough {
+                run_attrs.push(pango::AttrInt::new_strikethrough(true).into());
+            }
+            for mut attr in run_attrs {
+                attr.set_start_index(range.start as u32);
+                attr.set_end_index(range.end as u32);
+                attr_list.insert(attr);
+            }
+        }
+        pango_layout.set_attributes(Some(&attr_list));
+
+        pango_layout
+    }
+
+    /// Split the text into runs that have the same attributes, with the ranged attributes resolved.
+    ///
+    /// Overlapping ranged attributes are resolved the same way as when building the text layout.
+    pub(crate) fn resolved_text_runs(
+        &self,
+        text: &str,
+    ) -> Vec<(Range<usize>, ResolvedTextAttributes)> {
+        let base_attributes = ResolvedTextAttributes {
+            font_family: self.font_family.clone(),
+            font_size: self.font_size,
+            font_weight: self.font_weight,
+            font_style: self.font_style,
+            color: self.color,
+            underline: false,
+            strikethrough: false,
+        };
+        let mut ranged_text_attributes = self.ranged_text_attributes.clone();
+        ranged_text_attributes.sort_by(|first, second| first.range.start.cmp(&second.range.start));
+
+        let len = text.len();
+        let mut boundaries = vec![0, len];
+        for ranged_attr in ranged_text_attributes.iter() {
+            boundaries.push(ranged_attr.range.start.min(len));
+            boundaries.push(ranged_attr.range.end.min(len));
+        }
+        boundaries.retain(|&i| text.is_char_boundary(i));
+        boundaries.sort_unstable();
+        boundaries.dedup();
+
+        boundaries
+            .into_iter()
+            .tuple_windows()
+            .map(|(start, end)| {
+                let mut attributes = base_attributes.clone();
+                for ranged_attr in ranged_text_attributes
+                    .iter()
+                    .filter(|attr| attr.range.start <= start && attr.range.end >= end)
+                {
+                    attributes.apply(&ranged_attr.attribute);
+                }
+                (start..end, attributes)
+            })
+            .collect()
+    }
+
     pub fn untransformed_size<T>(&self, piet_text: &mut T, text: String) -> Option<na::Vector2<f64>>
     where
         T: piet::Text,
@@ -537,6 +699,16 @@ impl Drawable for TextStroke {
         cx.restore().map_err(|e| anyhow::anyhow!("{e:?}"))?;
         Ok(())
     }
+
+    fn draw_to_cairo(&self, cx: &cairo::Context, image_scale: f64) -> anyhow::Result<()> {
+        // Pdf viewers can only select and search text that is drawn as text objects
+        if cx.target().type_() == cairo::SurfaceType::Pdf {
+            return self.draw_as_text_objects_to_cairo(cx);
+        }
+        let mut piet_cx = piet_cairo::CairoRenderContext::new(cx);
+        self.draw(&mut piet_cx, image_scale)?;
+        piet_cx.finish().map_err(|e| anyhow::anyhow!("{e:?}"))
+    }
 }
 
 impl TextStroke {
@@ -552,6 +724,58 @@ impl TextStroke {
         &self.text[range]
     }
 
+    /// Draw the text as text objects with pangocairo.
+    ///
+    /// The pango layout is built from the same text style as the regular rendering,
+    /// so shaping, font fallback, line breaks and alignment match.
+    fn draw_as_text_objects_to_cairo(&self, cx: &cairo::Context) -> anyhow::Result<()> {
+        let coeffs = self.transform.affine.to_kurbo().as_coeffs();
+
+        cx.save()?;
+        cx.transform(cairo::Matrix::new(
+            coeffs[0], coeffs[1], coeffs[2], coeffs[3], coeffs[4], coeffs[5],
+        ));
+        let pango_layout = self.text_style.build_pango_layout(cx, &self.text);
+        pangocairo::functions::show_layout(cx, &pango_layout);
+        cx.restore()?;
+
+        Ok(())
+    }
+
+    /// Convert the text to Markdown.
+    ///
+    /// Bold, italic, underlined and struck through text is converted to the corresponding inline markup.
+    /// Underline has no Markdown equivalent, so inline `<u>` Html tags are used.
+    pub fn to_markdown(&self) -> String {
+        let bytes = self.text.as_bytes();
+        let mut out = String::with_capacity(self.text.len());
+        let mut open_markers = Vec::<MarkdownMarker>::new();
+
+        for (range, run_attributes) in self.text_style.resolved_text_runs(&self.text) {
+            let formatting = MarkdownFormatting::from(&run_attributes);
+            let mut segment_start = range.start;
+            for (i, segment) in self.text[range.clone()].split('\n').enumerate() {
+                if i > 0 {
+                    // Markup can't span multiple lines
+                    close_markdown_markers(&mut out, &mut open_markers, &[]);
+                    let newline = segment_start - 1;
+                    let hard_line_break = newline > 0
+                        && bytes[newline - 1] != b'\n'
+                        && bytes.get(newline + 1).is_some_and(|b| *b != b'\n');
+                    if hard_line_break {
+                        out.push_str("  ");
+                    }
+                    out.push('\n');
+                }
+                push_markdown_segment(&mut out, &mut open_markers, segment, formatting);
+                segment_start += segment.len() + 1;
+            }
+        }
+        close_markdown_markers(&mut out, &mut open_markers, &[]);
+
+        out
+    }
+
     /// Get a cursor matching best for the given coordinate.
     ///
     /// `coord` must be in global coordinate space.
@@ -696,6 +920,57 @@ impl TextStroke {
         );
     }
 
+    /// Replace all occurrences of the placeholders in the text with their replacements.
+    ///
+    /// The text is searched in a single pass, so placeholders that appear in a replacement are not replaced.
+    /// Text attributes that cover a part of a placeholder are clamped to cover its replacement.
+    ///
+    /// Returns true if any occurrence was replaced.
+    pub fn replace_placeholders(&mut self, replacements: &[(&str, &str)]) -> bool {
+        let mut replaced = false;
+        let mut search_start = 0;
+
+        loop {
+            let next = replacements
+                .iter()
+                .filter(|(placeholder, _)| !placeholder.is_empty())
+                .filter_map(|&(placeholder, replacement)| {
+                    let found = self.text[search_start..].find(placeholder)?;
+                    Some((search_start + found, placeholder, replacement))
+                })
+                .min_by_key(|(start, placeholder, _)| {
+                    (*start, std::cmp::Reverse(placeholder.len()))
+                });
+            let Some((start, placeholder, replacement)) = next else {
+                break;
+            };
+            let end = start + placeholder.len();
+            let replacement_end = start + replacement.len();
+            self.text.replace_range(start..end, replacement);
+
+            let map_pos = |pos: usize, is_range_end: bool| {
+                if pos <= start {
+                    pos
+                } else if pos >= end {
+                    pos - end + replacement_end
+                } else if is_range_end {
+                    replacement_end
+                } else {
+                    start
+                }
+            };
+            for attr in self.text_style.ranged_text_attributes.iter_mut() {
+                attr.range.start = map_pos(attr.range.start, false);
+                attr.range.end = map_pos(attr.range.end, true);
+            }
+
+            search_start = replacement_end;
+            replaced = true;
+        }
+
+        replaced
+    }
+
     /// Translate the ranged text attributes after the given cursor.
     ///
     /// Overlapping ranges are extended / shrunk
@@ -1024,3 +1299,119 @@ fn remove_intersecting_attrs_in_range(
         .filter(|attr| !attr.range.is_empty())
         .collect::<Vec<RangedTextAttribute>>()
 }
+
+/// The inline formatting of a text run, as far as it can be expressed in Markdown.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct MarkdownFormatting {
+    bold: bool,
+    italic: bool,
+    underline: bool,
+    strikethrough: bool,
+}
+
+impl From<&ResolvedTextAttributes> for MarkdownFormatting {
+    fn from(attributes: &ResolvedTextAttributes) -> Self {
+        Self {
+            bold: attributes.bold(),
+            italic: attributes.italic(),
+            underline: attributes.underline,
+            strikethrough: attributes.strikethrough,
+        }
+    }
+}
+
+impl MarkdownFormatting {
+    /// The markers in the order they get opened.
+    fn markers(self) -> Vec<MarkdownMarker> {
+        [
+            (self.underline, MarkdownMarker::Underline),
+            (self.strikethrough, MarkdownMarker::Strikethrough),
+            (self.bold, MarkdownMarker::Bold),
+            (self.italic, MarkdownMarker::Italic),
+        ]
+        .into_iter()
+        .filter_map(|(enabled, marker)| enabled.then_some(marker))
+        .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MarkdownMarker {
+    Underline,
+    Strikethrough,
+    Bold,
+    Italic,
+}
+
+impl MarkdownMarker {
+    fn open(self) -> &'static str {
+        match self {
+            MarkdownMarker::Underline => "<u>",
+            MarkdownMarker::Strikethrough => "~~",
+            MarkdownMarker::Bold => "**",
+            MarkdownMarker::Italic => "*",
+        }
+    }
+
+    fn close(self) -> &'static str {
+        match self {
+            MarkdownMarker::Underline => "</u>",
+            MarkdownMarker::Strikethrough => "~~",
+            MarkdownMarker::Bold => "**",
+            MarkdownMarker::Italic => "*",
+        }
+    }
+}
+
+/// Close the open markers that are not in `keep`, together with all markers that were opened after them.
+///
+/// Markup must not end with whitespace, so trailing whitespace is moved behind the closing markers.
+fn close_markdown_markers(
+    out: &mut String,
+    open_markers: &mut Vec<MarkdownMarker>,
+    keep: &[MarkdownMarker],
+) {
+    while open_markers.iter().any(|marker| !keep.contains(marker)) {
+        let Some(marker) = open_markers.pop() else {
+            break;
+        };
+        let trailing_whitespace = out.split_off(out.trim_end().len());
+        out.push_str(marker.close());
+        out.push_str(&trailing_whitespace);
+    }
+}
+
+/// Push a single line segment of a text run with the given formatting.
+fn push_markdown_segment(
+    out: &mut String,
+    open_markers: &mut Vec<MarkdownMarker>,
+    segment: &str,
+    formatting: MarkdownFormatting,
+) {
+    // Whitespace only segments keep the current markup open, so that it doesn't end up empty
+    if segment.trim().is_empty() {
+        out.push_str(segment);
+        return;
+    }
+    let markers = formatting.markers();
+    close_markdown_markers(out, open_markers, &markers);
+
+    // Markup must not start with whitespace
+    let trimmed = segment.trim_start();
+    out.push_str(&segment[..segment.len() - trimmed.len()]);
+    for marker in markers {
+        if !open_markers.contains(&marker) {
+            out.push_str(marker.open());
+            open_markers.push(marker);
+        }
+    }
+    for c in trimmed.chars() {
+        if matches!(
+            c,
+            '\\' | '`' | '*' | '_' | '~' | '<' | '>' | '[' | ']' | '#'
+        ) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+}
@@ -1,5 +1,7 @@
 // Imports
 use crate::fileformats::xoppformat;
+use crate::store::chrono_comp::StrokeLayer;
+use crate::store::UserLayer;
 use geo::line_string;
 use p2d::bounding_volume::Aabb;
 use rnote_compose::Color;
@@ -27,6 +29,58 @@ pub fn xoppcolor_from_color(color: Color) -> xoppformat::XoppColor {
     }
 }
 
+/// The name of the Xournal++ layer that strokes on the given stroke layer are exported to.
+///
+/// User layers are named with their default name, followed by the name of the user layer if it differs,
+/// so that their index can be restored on import. The names of the non-user layers have a prefix
+/// that is not used by Xournal++.
+pub fn xopp_layer_name_from_stroke_layer(
+    layer: StrokeLayer,
+    user_layer_name: Option<&str>,
+) -> String {
+    match layer {
+        StrokeLayer::UserLayer(i) => {
+            let default_name = UserLayer::default_name(i);
+            match user_layer_name {
+                Some(name) if name != default_name => format!("{default_name}: {name}"),
+                _ => default_name,
+            }
+        }
+        StrokeLayer::Highlighter => String::from("rnote:highlighter"),
+        StrokeLayer::Image => String::from("rnote:images"),
+        StrokeLayer::Document => String::from("rnote:document"),
+    }
+}
+
+/// The stroke layer for the given Xournal++ layer name, if it is one of the names of the non-user layers
+/// that are used on export.
+pub fn stroke_layer_from_xopp_layer_name(name: &str) -> Option<StrokeLayer> {
+    match name {
+        "rnote:highlighter" => Some(StrokeLayer::Highlighter),
+        "rnote:images" => Some(StrokeLayer::Image),
+        "rnote:document" => Some(StrokeLayer::Document),
+        _ => None,
+    }
+}
+
+/// The index and the name of the user layer for the given Xournal++ layer name, if it was written
+/// by [xopp_layer_name_from_stroke_layer]. The name is `None` for user layers that had their default name.
+pub fn user_layer_from_xopp_layer_name(name: &str) -> Option<(u32, Option<String>)> {
+    /// Limits the number of user layers that get created when importing crafted files.
+    const USER_LAYER_INDEX_MAX: u32 = 1024;
+
+    let rest = name.strip_prefix("Layer ")?;
+    let (number, user_layer_name) = match rest.split_once(": ") {
+        Some((number, user_layer_name)) => (number, Some(user_layer_name.to_string())),
+        None => (rest, None),
+    };
+    let index = number.parse::<u32>().ok()?.checked_sub(1)?;
+    if index > USER_LAYER_INDEX_MAX {
+        return None;
+    }
+    Some((index, user_layer_name))
+}
+
 pub fn now_formatted_string() -> String {
     chrono::Local::now().format("%Y-%m-%d_%H:%M:%S").to_string()
 }
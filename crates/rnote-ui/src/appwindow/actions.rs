@@ -9,7 +9,7 @@ use gtk4::{
 use p2d::bounding_volume::BoundingVolume;
 use rnote_compose::penevent::ShortcutKey;
 use rnote_compose::SplitOrder;
-use rnote_engine::engine::StrokeContent;
+use rnote_engine::engine::{Alignment, Distribution, StrokeContent};
 use rnote_engine::pens::PenStyle;
 use rnote_engine::strokes::resize::{ImageSizeOption, Resize};
 use rnote_engine::{Camera, Engine};
@@ -118,6 +118,36 @@ impl RnAppWindow {
         self.add_action(&action_selection_duplicate);
         let action_selection_invert_color = gio::SimpleAction::new("selection-invert-color", None);
         self.add_action(&action_selection_invert_color);
+        let action_selection_bring_to_front =
+            gio::SimpleAction::new("selection-bring-to-front", None);
+        self.add_action(&action_selection_bring_to_front);
+        let action_selection_send_to_back = gio::SimpleAction::new("selection-send-to-back", None);
+        self.add_action(&action_selection_send_to_back);
+        let action_selection_raise = gio::SimpleAction::new("selection-raise", None);
+        self.add_action(&action_selection_raise);
+        let action_selection_lower = gio::SimpleAction::new("selection-lower", None);
+        self.add_action(&action_selection_lower);
+        let action_selection_align =
+            gio::SimpleAction::new("selection-align", Some(&String::static_variant_type()));
+        self.add_action(&action_selection_align);
+        let action_selection_distribute =
+            gio::SimpleAction::new("selection-distribute", Some(&String::static_variant_type()));
+        self.add_action(&action_selection_distribute);
+        let action_selection_group = gio::SimpleAction::new("selection-group", None);
+        self.add_action(&action_selection_group);
+        let action_selection_ungroup = gio::SimpleAction::new("selection-ungroup", None);
+        self.add_action(&action_selection_ungroup);
+        let action_selection_connect = gio::SimpleAction::new("selection-connect", None);
+        self.add_action(&action_selection_connect);
+        let action_selection_smooth = gio::SimpleAction::new("selection-smooth", None);
+        self.add_action(&action_selection_smooth);
+        let action_selection_simplify = gio::SimpleAction::new("selection-simplify", None);
+        self.add_action(&action_selection_simplify);
+        let action_selection_recognize_shapes =
+            gio::SimpleAction::new("selection-recognize-shapes", None);
+        self.add_action(&action_selection_recognize_shapes);
+        let action_doc_simplify = gio::SimpleAction::new("doc-simplify", None);
+        self.add_action(&action_doc_simplify);
         let action_selection_select_all = gio::SimpleAction::new("selection-select-all", None);
         self.add_action(&action_selection_select_all);
         let action_selection_deselect_all = gio::SimpleAction::new("selection-deselect-all", None);
@@ -429,6 +459,137 @@ impl RnAppWindow {
             }),
         );
 
+        // bring the selected strokes to the front
+        action_selection_bring_to_front.connect_activate(
+            clone!(@weak self as appwindow => move |_, _| {
+                let canvas = appwindow.active_tab_wrapper().canvas();
+                let widget_flags = canvas.engine_mut().selection_bring_to_front();
+                appwindow.handle_widget_flags(widget_flags, &canvas);
+            }),
+        );
+
+        // send the selected strokes to the back
+        action_selection_send_to_back.connect_activate(
+            clone!(@weak self as appwindow => move |_, _| {
+                let canvas = appwindow.active_tab_wrapper().canvas();
+                let widget_flags = canvas.engine_mut().selection_send_to_back();
+                appwindow.handle_widget_flags(widget_flags, &canvas);
+            }),
+        );
+
+        // raise the selected strokes one step
+        action_selection_raise.connect_activate(clone!(@weak self as appwindow => move |_, _| {
+            let canvas = appwindow.active_tab_wrapper().canvas();
+            let widget_flags = canvas.engine_mut().selection_raise();
+            appwindow.handle_widget_flags(widget_flags, &canvas);
+        }));
+
+        // lower the selected strokes one step
+        action_selection_lower.connect_activate(clone!(@weak self as appwindow => move |_, _| {
+            let canvas = appwindow.active_tab_wrapper().canvas();
+            let widget_flags = canvas.engine_mut().selection_lower();
+            appwindow.handle_widget_flags(widget_flags, &canvas);
+        }));
+
+        // align the selected strokes
+        action_selection_align.connect_activate(
+            clone!(@weak self as appwindow => move |_, target| {
+                let alignment = match Alignment::from_str(target.unwrap().str().unwrap()) {
+                    Ok(a) => a,
+                    Err(e) => {
+                        error!("Activated selection-align action with invalid target, Err: {e:}");
+                        return;
+                    }
+                };
+                let canvas = appwindow.active_tab_wrapper().canvas();
+                let widget_flags = canvas.engine_mut().align_selection(alignment);
+                appwindow.handle_widget_flags(widget_flags, &canvas);
+            }),
+        );
+
+        // distribute the selected strokes
+        action_selection_distribute.connect_activate(
+            clone!(@weak self as appwindow => move |_, target| {
+                let distribution = match Distribution::from_str(target.unwrap().str().unwrap()) {
+                    Ok(d) => d,
+                    Err(e) => {
+                        error!("Activated selection-distribute action with invalid target, Err: {e:}");
+                        return;
+                    }
+                };
+                let canvas = appwindow.active_tab_wrapper().canvas();
+                let widget_flags = canvas.engine_mut().distribute_selection(distribution);
+                appwindow.handle_widget_flags(widget_flags, &canvas);
+            }),
+        );
+
+        // group the selected strokes
+        action_selection_group.connect_activate(clone!(@weak self as appwindow => move |_, _| {
+            let canvas = appwindow.active_tab_wrapper().canvas();
+            let widget_flags = canvas.engine_mut().group_selection();
+            appwindow.handle_widget_flags(widget_flags, &canvas);
+        }));
+
+        // ungroup the selected strokes
+        action_selection_ungroup.connect_activate(clone!(@weak self as appwindow => move |_, _| {
+            let canvas = appwindow.active_tab_wrapper().canvas();
+            let widget_flags = canvas.engine_mut().ungroup_selection();
+            appwindow.handle_widget_flags(widget_flags, &canvas);
+        }));
+
+        // connect the two selected strokes
+        action_selection_connect.connect_activate(clone!(@weak self as appwindow => move |_, _| {
+            let canvas = appwindow.active_tab_wrapper().canvas();
+            let widget_flags = canvas.engine_mut().connect_selection();
+            appwindow.handle_widget_flags(widget_flags, &canvas);
+        }));
+
+        // smooth the selected brush strokes
+        action_selection_smooth.connect_activate(clone!(@weak self as appwindow => move |_, _| {
+            let canvas = appwindow.active_tab_wrapper().canvas();
+            let widget_flags = canvas.engine_mut().smooth_selection_brushstrokes();
+            appwindow.handle_widget_flags(widget_flags, &canvas);
+        }));
+
+        // Simplify selection
+        action_selection_simplify.connect_activate(clone!(@weak self as appwindow => move |_, _| {
+            let canvas = appwindow.active_tab_wrapper().canvas();
+            let (removed, widget_flags) = canvas
+                .engine_mut()
+                .simplify_selection_brushstrokes(Engine::SIMPLIFY_TOLERANCE_DEFAULT);
+            appwindow.handle_widget_flags(widget_flags, &canvas);
+            appwindow.overlays().dispatch_toast_text(
+                &format!("{}: {removed}", gettext("Removed stroke elements")),
+                crate::overlays::TEXT_TOAST_TIMEOUT_DEFAULT,
+            );
+        }));
+
+        // replace the selected brush strokes that are recognized as shapes
+        action_selection_recognize_shapes.connect_activate(
+            clone!(@weak self as appwindow => move |_, _| {
+                let canvas = appwindow.active_tab_wrapper().canvas();
+                let (replaced, widget_flags) = canvas.engine_mut().recognize_shapes_in_selection();
+                appwindow.handle_widget_flags(widget_flags, &canvas);
+                appwindow.overlays().dispatch_toast_text(
+                    &format!("{}: {replaced}", gettext("Recognized shapes")),
+                    crate::overlays::TEXT_TOAST_TIMEOUT_DEFAULT,
+                );
+            }),
+        );
+
+        // Simplify document
+        action_doc_simplify.connect_activate(clone!(@weak self as appwindow => move |_, _| {
+            let canvas = appwindow.active_tab_wrapper().canvas();
+            let (removed, widget_flags) = canvas
+                .engine_mut()
+                .simplify_doc_brushstrokes(Engine::SIMPLIFY_TOLERANCE_DEFAULT);
+            appwindow.handle_widget_flags(widget_flags, &canvas);
+            appwindow.overlays().dispatch_toast_text(
+                &format!("{}: {removed}", gettext("Removed stroke elements")),
+                crate::overlays::TEXT_TOAST_TIMEOUT_DEFAULT,
+            );
+        }));
+
         // select all strokes
         action_selection_select_all.connect_activate(
             clone!(@weak self as appwindow => move |_, _| {
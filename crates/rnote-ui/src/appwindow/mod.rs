@@ -745,6 +745,30 @@ impl RnAppWindow {
                             .colorpicker()
                             .set_stroke_color(gdk::RGBA::from_compose_color(stroke_color));
                     }
+                    BrushStyle::Calligraphy => {
+                        let stroke_color = canvas
+                            .engine_ref()
+                            .pens_config
+                            .brush_config
+                            .calligraphy_options
+                            .stroke_color
+                            .unwrap_or(Color::TRANSPARENT);
+                        self.overlays()
+                            .colorpicker()
+                            .set_stroke_color(gdk::RGBA::from_compose_color(stroke_color));
+                    }
+                    BrushStyle::Pencil => {
+                        let stroke_color = canvas
+                            .engine_ref()
+                            .pens_config
+                            .brush_config
+                            .pencil_options
+                            .stroke_color
+                            .unwrap_or(Color::TRANSPARENT);
+                        self.overlays()
+                            .colorpicker()
+                            .set_stroke_color(gdk::RGBA::from_compose_color(stroke_color));
+                    }
                 }
             }
             PenStyle::Shaper => {
@@ -5,7 +5,9 @@ use futures::channel::oneshot;
 use futures::AsyncWriteExt;
 use gtk4::{gio, prelude::*};
 use rnote_compose::ext::Vector2Ext;
-use rnote_engine::engine::export::{DocExportPrefs, DocPagesExportPrefs, SelectionExportPrefs};
+use rnote_engine::engine::export::{
+    DocExportFormat, DocExportPrefs, DocPagesExportPrefs, SelectionExportPrefs,
+};
 use rnote_engine::engine::{EngineSnapshot, StrokeContent};
 use rnote_engine::strokes::resize::ImageSizeOption;
 use rnote_engine::strokes::Stroke;
@@ -60,10 +62,24 @@ impl RnCanvas {
         Ok(())
     }
 
-    pub(crate) async fn load_in_xopp_bytes(&self, bytes: Vec<u8>) -> anyhow::Result<()> {
+    /// Load the bytes of a `.xopp` file and imports it into the engine.
+    ///
+    /// `file_path` is needed to load the background images that are attached to the file.
+    pub(crate) async fn load_in_xopp_bytes<P>(
+        &self,
+        bytes: Vec<u8>,
+        file_path: Option<P>,
+    ) -> anyhow::Result<()>
+    where
+        P: AsRef<Path>,
+    {
         let xopp_import_prefs = self.engine_ref().import_prefs.xopp_import_prefs;
-        let engine_snapshot =
-            EngineSnapshot::load_from_xopp_bytes(bytes, xopp_import_prefs).await?;
+        let engine_snapshot = EngineSnapshot::load_from_xopp_bytes(
+            bytes,
+            file_path.map(|file_path| file_path.as_ref().to_path_buf()),
+            xopp_import_prefs,
+        )
+        .await?;
         let widget_flags = self.engine_mut().load_snapshot(engine_snapshot);
         self.emit_handle_widget_flags(widget_flags);
 
@@ -281,6 +297,23 @@ impl RnCanvas {
 
         crate::utils::create_replace_file_future(export_bytes.await??, file).await?;
 
+        let export_format = export_prefs_override
+            .unwrap_or(self.engine_ref().export_prefs.doc_export_prefs)
+            .export_format;
+        if export_format == DocExportFormat::Xopp {
+            // Background images are attached next to the xopp file
+            if let (Some(file_name), Some(dir)) = (file.basename(), file.parent()) {
+                let attachments = self.engine_ref().export_doc_xopp_attachments(
+                    &file_name.to_string_lossy(),
+                    export_prefs_override,
+                );
+                for (attachment_name, bytes) in attachments.await?? {
+                    crate::utils::create_replace_file_future(bytes, &dir.child(attachment_name))
+                        .await?;
+                }
+            }
+        }
+
         self.set_last_export_dir(file.parent());
 
         Ok(())
@@ -394,8 +394,19 @@ fn retrieve_pointer_elements(
             } else {
                 Element::PRESSURE_DEFAULT
             };
+            let mut element = Element::new(pos, pressure);
+            if is_stylus
+                && available_axes.contains(gdk::AxisFlags::XTILT)
+                && available_axes.contains(gdk::AxisFlags::YTILT)
+            {
+                element = element_w_tilt(
+                    element,
+                    axes[crate::utils::axis_use_idx(gdk::AxisUse::Xtilt)],
+                    axes[crate::utils::axis_use_idx(gdk::AxisUse::Ytilt)],
+                );
+            }
 
-            entries.push((Element::new(pos, pressure), entry_time));
+            entries.push((element, entry_time));
         }
 
         elements.extend(entries.into_iter().rev());
@@ -410,12 +421,26 @@ fn retrieve_pointer_elements(
     } else {
         Element::PRESSURE_DEFAULT
     };
+    let mut element = Element::new(pos, pressure);
+    if is_stylus {
+        if let (Some(xtilt), Some(ytilt)) = (
+            event.axis(gdk::AxisUse::Xtilt),
+            event.axis(gdk::AxisUse::Ytilt),
+        ) {
+            element = element_w_tilt(element, xtilt, ytilt);
+        }
+    }
 
-    elements.push((Element::new(pos, pressure), now));
+    elements.push((element, now));
 
     Some(elements)
 }
 
+/// Adds the tilt to the element. The x and y tilt are expected in degrees, like they are reported by libinput.
+fn element_w_tilt(element: Element, xtilt: f64, ytilt: f64) -> Element {
+    element.with_xy_tilt(xtilt.to_radians(), ytilt.to_radians())
+}
+
 pub(crate) fn retrieve_button_shortcut_key(
     gdk_button: u32,
     is_stylus: bool,
@@ -326,6 +326,28 @@ fn create_filedialog_export_doc(
             }
             filter.set_name(Some(&gettext("Xopp")));
         }
+        DocExportFormat::Markdown => {
+            if cfg!(target_os = "windows") {
+                filter.add_pattern("*.md");
+            } else {
+                filter.add_mime_type("text/markdown");
+            }
+            if cfg!(target_os = "macos") {
+                filter.add_suffix("md");
+            }
+            filter.set_name(Some(&gettext("Markdown")));
+        }
+        DocExportFormat::PlainText => {
+            if cfg!(target_os = "windows") {
+                filter.add_pattern("*.txt");
+            } else {
+                filter.add_mime_type("text/plain");
+            }
+            if cfg!(target_os = "macos") {
+                filter.add_suffix("txt");
+            }
+            filter.set_name(Some(&gettext("Plain Text")));
+        }
     }
     let file_ext = doc_export_prefs.export_format.file_ext();
     let file_name = crate::utils::default_file_title_for_export(
@@ -11,6 +11,7 @@ use gtk4::{
     Shortcut, ShortcutController, ShortcutTrigger, ToggleButton,
 };
 use num_traits::ToPrimitive;
+use rnote_engine::document::BackgroundImage;
 use rnote_engine::engine::import::{PdfImportPageSpacing, PdfImportPagesType};
 use tracing::{debug, error};
 
@@ -111,6 +112,75 @@ pub(crate) async fn filedialog_import_file(appwindow: &RnAppWindow) {
     }
 }
 
+/// Chooses an image file and sets it as the background image of the document.
+pub(crate) async fn filedialog_background_image(appwindow: &RnAppWindow) {
+    let filter = FileFilter::new();
+    // note : mimetypes are not supported with the native file picker on windows
+    // See the limitations on FileChooserNative
+    // https://gtk-rs.org/gtk3-rs/stable/latest/docs/gtk/struct.FileChooserNative.html#win32-details--gtkfilechooserdialognative-win32
+    if cfg!(target_os = "windows") {
+        filter.add_pattern("*.svg");
+        filter.add_pattern("*.png");
+        filter.add_pattern("*.jpeg");
+    } else {
+        filter.add_mime_type("image/svg+xml");
+        filter.add_mime_type("image/png");
+        filter.add_mime_type("image/jpeg");
+    }
+    filter.add_suffix("svg");
+    filter.add_suffix("png");
+    filter.add_suffix("jpg");
+    filter.add_suffix("jpeg");
+    filter.set_name(Some(&gettext("Jpg, Png, Svg")));
+
+    let filter_list = gio::ListStore::new::<FileFilter>();
+    filter_list.append(&filter);
+
+    let dialog = FileDialog::builder()
+        .title(gettext("Choose Background Image"))
+        .modal(true)
+        .accept_label(gettext("Choose"))
+        .filters(&filter_list)
+        .default_filter(&filter)
+        .build();
+
+    if let Some(current_workspace_dir) = appwindow.sidebar().workspacebrowser().dir_list_dir() {
+        dialog.set_initial_folder(Some(&gio::File::for_path(current_workspace_dir)));
+    }
+
+    let selected_file = match dialog.open_future(Some(appwindow)).await {
+        Ok(selected_file) => selected_file,
+        Err(e) => {
+            debug!(
+                "Did not choose background image (Error or dialog dismissed by user), Err: {e:?}"
+            );
+            return;
+        }
+    };
+    let Some(path) = selected_file.path() else {
+        error!("Choosing background image failed, the selected file has no path");
+        return;
+    };
+    let mode = appwindow.sidebar().settings_panel().background_image_mode();
+
+    match BackgroundImage::load_from_file(path, mode) {
+        Ok(image) => {
+            let canvas = appwindow.active_tab_wrapper().canvas();
+            canvas.engine_mut().document.background.image = Some(image);
+            let mut widget_flags = canvas.engine_mut().background_rendering_regenerate();
+            widget_flags.refresh_ui = true;
+            widget_flags.store_modified = true;
+            appwindow.handle_widget_flags(widget_flags, &canvas);
+        }
+        Err(e) => {
+            error!("Loading background image failed, Err: {e:?}");
+            appwindow
+                .overlays()
+                .dispatch_toast_error(&gettext("Loading background image failed"));
+        }
+    }
+}
+
 /// Imports the file as Pdf with an import dialog.
 ///
 /// Returns true when the file was imported, else false.
@@ -410,7 +480,7 @@ pub(crate) async fn dialog_import_xopp_w_prefs(
                     return;
                 }
             };
-            if let Err(e) = canvas.load_in_xopp_bytes(bytes.to_vec()).await {
+            if let Err(e) = canvas.load_in_xopp_bytes(bytes.to_vec(), input_file.path()).await {
                 if let Err(e) = inner_tx_confirm.unbounded_send(Err(e)) {
                     error!("Failed to load XOPP, but failed to send signal through channel. Err: {e:?}");
                 }
@@ -7,8 +7,10 @@ use gtk4::{
 };
 use num_traits::cast::ToPrimitive;
 use rnote_compose::builders::PenPathBuilderType;
+use rnote_compose::style::calligraphy::CalligraphyOptions;
+use rnote_compose::style::pencil::PencilOptions;
 use rnote_compose::style::textured::{TexturedDotsDistribution, TexturedOptions};
-use rnote_compose::style::PressureCurve;
+use rnote_compose::style::{CustomPressureCurve, PressureCurve, PressureCurvePoint};
 use rnote_engine::pens::pensconfig::brushconfig::{BrushStyle, SolidOptions};
 use rnote_engine::pens::pensconfig::BrushConfig;
 
@@ -33,6 +35,10 @@ mod imp {
         #[template_child]
         pub(crate) brushstyle_textured_row: TemplateChild<adw::ActionRow>,
         #[template_child]
+        pub(crate) brushstyle_calligraphy_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub(crate) brushstyle_pencil_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
         pub(crate) brushconfig_menubutton: TemplateChild<MenuButton>,
         #[template_child]
         pub(crate) brushconfig_popover: TemplateChild<Popover>,
@@ -47,12 +53,40 @@ mod imp {
         #[template_child]
         pub(crate) brush_buildertype_modeled: TemplateChild<adw::ActionRow>,
         #[template_child]
+        pub(crate) brush_shape_recognition_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
         pub(crate) solidstyle_pressure_curves_row: TemplateChild<adw::ComboRow>,
         #[template_child]
+        pub(crate) solidstyle_pressure_curve_points_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub(crate) solidstyle_pressure_curve_min_width_row: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        pub(crate) solidstyle_pressure_curve_max_width_row: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        pub(crate) solidstyle_line_pattern_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub(crate) texturedstyle_pressure_curves_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub(crate) texturedstyle_pressure_curve_points_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub(crate) texturedstyle_pressure_curve_min_width_row: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        pub(crate) texturedstyle_pressure_curve_max_width_row: TemplateChild<adw::SpinRow>,
+        #[template_child]
         pub(crate) texturedstyle_density_row: TemplateChild<adw::SpinRow>,
         #[template_child]
         pub(crate) texturedstyle_distribution_row: TemplateChild<adw::ComboRow>,
         #[template_child]
+        pub(crate) calligraphystyle_nib_angle_row: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        pub(crate) calligraphystyle_thin_width_ratio_row: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        pub(crate) calligraphystyle_follow_tilt_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub(crate) pencilstyle_opacity_row: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        pub(crate) pencilstyle_grain_row: TemplateChild<adw::SpinRow>,
+        #[template_child]
         pub(crate) stroke_width_picker: TemplateChild<RnStrokeWidthPicker>,
     }
 
@@ -129,6 +163,14 @@ impl RnBrushPage {
                 .imp()
                 .brushstyle_listbox
                 .select_row(Some(&*self.imp().brushstyle_textured_row)),
+            BrushStyle::Calligraphy => self
+                .imp()
+                .brushstyle_listbox
+                .select_row(Some(&*self.imp().brushstyle_calligraphy_row)),
+            BrushStyle::Pencil => self
+                .imp()
+                .brushstyle_listbox
+                .select_row(Some(&*self.imp().brushstyle_pencil_row)),
         }
     }
 
@@ -156,17 +198,30 @@ impl RnBrushPage {
         }
     }
 
-    pub(crate) fn solidstyle_pressure_curve(&self) -> PressureCurve {
-        PressureCurve::try_from(self.imp().solidstyle_pressure_curves_row.get().selected()).unwrap()
-    }
-
     pub(crate) fn set_solidstyle_pressure_curve(&self, pressure_curve: PressureCurve) {
-        let position = pressure_curve.to_u32().unwrap();
+        let imp = self.imp();
+        imp.solidstyle_pressure_curves_row
+            .get()
+            .set_selected(pressure_curve.to_u32());
+        refresh_custom_pressure_curve_rows(
+            &pressure_curve,
+            &imp.solidstyle_pressure_curve_points_row,
+            &imp.solidstyle_pressure_curve_min_width_row,
+            &imp.solidstyle_pressure_curve_max_width_row,
+        );
+    }
 
-        self.imp()
-            .solidstyle_pressure_curves_row
+    pub(crate) fn set_texturedstyle_pressure_curve(&self, pressure_curve: PressureCurve) {
+        let imp = self.imp();
+        imp.texturedstyle_pressure_curves_row
             .get()
-            .set_selected(position);
+            .set_selected(pressure_curve.to_u32());
+        refresh_custom_pressure_curve_rows(
+            &pressure_curve,
+            &imp.texturedstyle_pressure_curve_points_row,
+            &imp.texturedstyle_pressure_curve_min_width_row,
+            &imp.texturedstyle_pressure_curve_max_width_row,
+        );
     }
 
     pub(crate) fn texturedstyle_dots_distribution(&self) -> TexturedDotsDistribution {
@@ -234,6 +289,12 @@ impl RnBrushPage {
                     BrushStyle::Textured => {
                         canvas.engine_mut().pens_config.brush_config.textured_options.stroke_width = stroke_width;
                     },
+                    BrushStyle::Calligraphy => {
+                        canvas.engine_mut().pens_config.brush_config.calligraphy_options.stroke_width = stroke_width;
+                    },
+                    BrushStyle::Pencil => {
+                        canvas.engine_mut().pens_config.brush_config.pencil_options.stroke_width = stroke_width;
+                    },
                 }
             }),
         );
@@ -261,6 +322,16 @@ impl RnBrushPage {
                             brushpage.imp().stroke_width_picker.set_stroke_width(stroke_width);
                             brushpage.imp().brushstyle_menubutton.set_icon_name("pen-brush-style-textured-symbolic");
                         },
+                        BrushStyle::Calligraphy => {
+                            let stroke_width = appwindow.active_tab_wrapper().canvas().engine_mut().pens_config.brush_config.calligraphy_options.stroke_width;
+                            brushpage.imp().stroke_width_picker.set_stroke_width(stroke_width);
+                            brushpage.imp().brushstyle_menubutton.set_icon_name("pen-brush-style-calligraphy-symbolic");
+                        },
+                        BrushStyle::Pencil => {
+                            let stroke_width = appwindow.active_tab_wrapper().canvas().engine_mut().pens_config.brush_config.pencil_options.stroke_width;
+                            brushpage.imp().stroke_width_picker.set_stroke_width(stroke_width);
+                            brushpage.imp().brushstyle_menubutton.set_icon_name("pen-brush-style-pencil-symbolic");
+                        },
                     }
                 }
             }),
@@ -277,11 +348,39 @@ impl RnBrushPage {
 
         // Solid style
         // Pressure curve
-        imp.solidstyle_pressure_curves_row.get().connect_selected_notify(clone!(@weak self as brushpage, @weak appwindow => move |_smoothstyle_pressure_curves_row| {
-            appwindow.active_tab_wrapper().canvas().engine_mut().pens_config.brush_config.solid_options.pressure_curve = brushpage.solidstyle_pressure_curve();
-        }));
+        connect_pressure_curve_rows(
+            &appwindow,
+            &imp.solidstyle_pressure_curves_row,
+            &imp.solidstyle_pressure_curve_points_row,
+            &imp.solidstyle_pressure_curve_min_width_row,
+            &imp.solidstyle_pressure_curve_max_width_row,
+            |brush_config| &mut brush_config.solid_options.pressure_curve,
+        );
+
+        // Line pattern
+        imp.solidstyle_line_pattern_row
+            .get()
+            .connect_selected_notify(clone!(@weak appwindow => move |row| {
+                let canvas = appwindow.active_tab_wrapper().canvas();
+                let mut engine = canvas.engine_mut();
+                let solid_options = &mut engine.pens_config.brush_config.solid_options;
+                // keep dash arrays which are not one of the patterns until another pattern is picked
+                if super::line_pattern_for_dash_array(&solid_options.dash_array) != row.selected() {
+                    solid_options.dash_array = super::dash_array_for_line_pattern(row.selected());
+                }
+            }));
 
         // Textured style
+        // Pressure curve
+        connect_pressure_curve_rows(
+            &appwindow,
+            &imp.texturedstyle_pressure_curves_row,
+            &imp.texturedstyle_pressure_curve_points_row,
+            &imp.texturedstyle_pressure_curve_min_width_row,
+            &imp.texturedstyle_pressure_curve_max_width_row,
+            |brush_config| &mut brush_config.textured_options.pressure_curve,
+        );
+
         // Density
         imp.texturedstyle_density_row
             .get()
@@ -297,10 +396,79 @@ impl RnBrushPage {
             }),
         );
 
+        // shape recognition
+        imp.brush_shape_recognition_row.get().connect_active_notify(clone!(@weak appwindow => move |row| {
+            appwindow.active_tab_wrapper().canvas().engine_mut().pens_config.brush_config.shape_recognition = row.is_active();
+        }));
+
         // dots distribution
         imp.texturedstyle_distribution_row.get().connect_selected_notify(clone!(@weak self as brushpage, @weak appwindow => move |_texturedstyle_distribution_row| {
             appwindow.active_tab_wrapper().canvas().engine_mut().pens_config.brush_config.textured_options.distribution = brushpage.texturedstyle_dots_distribution();
         }));
+
+        // Calligraphy style
+        // Nib angle
+        imp.calligraphystyle_nib_angle_row
+            .get()
+            .set_value(CalligraphyOptions::NIB_ANGLE_DEFAULT.to_degrees());
+
+        imp.calligraphystyle_nib_angle_row.get().connect_changed(
+            clone!(@weak appwindow => move |row| {
+                appwindow.active_tab_wrapper().canvas().engine_mut().pens_config.brush_config.calligraphy_options.nib_angle = row.value().to_radians();
+            }),
+        );
+
+        // Thin width ratio
+        imp.calligraphystyle_thin_width_ratio_row.get().set_range(
+            CalligraphyOptions::THIN_WIDTH_RATIO_MIN,
+            CalligraphyOptions::THIN_WIDTH_RATIO_MAX,
+        );
+        // set value after the range!
+        imp.calligraphystyle_thin_width_ratio_row
+            .get()
+            .set_value(CalligraphyOptions::THIN_WIDTH_RATIO_DEFAULT);
+
+        imp.calligraphystyle_thin_width_ratio_row.get().connect_changed(
+            clone!(@weak appwindow => move |row| {
+                appwindow.active_tab_wrapper().canvas().engine_mut().pens_config.brush_config.calligraphy_options.thin_width_ratio = row.value();
+            }),
+        );
+
+        // Follow tilt
+        imp.calligraphystyle_follow_tilt_row.get().connect_active_notify(clone!(@weak appwindow => move |row| {
+            appwindow.active_tab_wrapper().canvas().engine_mut().pens_config.brush_config.calligraphy_options.follow_tilt = row.is_active();
+        }));
+
+        // Pencil style
+        // Opacity
+        imp.pencilstyle_opacity_row
+            .get()
+            .set_range(PencilOptions::OPACITY_MIN, PencilOptions::OPACITY_MAX);
+        // set value after the range!
+        imp.pencilstyle_opacity_row
+            .get()
+            .set_value(PencilOptions::OPACITY_DEFAULT);
+
+        imp.pencilstyle_opacity_row.get().connect_changed(
+            clone!(@weak appwindow => move |row| {
+                appwindow.active_tab_wrapper().canvas().engine_mut().pens_config.brush_config.pencil_options.opacity = row.value();
+            }),
+        );
+
+        // Grain
+        imp.pencilstyle_grain_row
+            .get()
+            .set_range(PencilOptions::GRAIN_MIN, PencilOptions::GRAIN_MAX);
+        // set value after the range!
+        imp.pencilstyle_grain_row
+            .get()
+            .set_value(PencilOptions::GRAIN_DEFAULT);
+
+        imp.pencilstyle_grain_row.get().connect_changed(
+            clone!(@weak appwindow => move |row| {
+                appwindow.active_tab_wrapper().canvas().engine_mut().pens_config.brush_config.pencil_options.grain = row.value();
+            }),
+        );
     }
 
     pub(crate) fn refresh_ui(&self, active_tab: &RnCanvasWrapper) {
@@ -312,10 +480,29 @@ impl RnBrushPage {
             .brush_config
             .clone();
 
+        imp.brush_shape_recognition_row
+            .set_active(brush_config.shape_recognition);
         self.set_solidstyle_pressure_curve(brush_config.solid_options.pressure_curve);
+        imp.solidstyle_line_pattern_row
+            .set_selected(super::line_pattern_for_dash_array(
+                &brush_config.solid_options.dash_array,
+            ));
+        self.set_texturedstyle_pressure_curve(brush_config.textured_options.pressure_curve);
         imp.texturedstyle_density_row
             .set_value(brush_config.textured_options.density);
         self.set_texturedstyle_distribution_variant(brush_config.textured_options.distribution);
+        imp.calligraphystyle_nib_angle_row
+            .set_value(nib_angle_degrees_for_display(
+                brush_config.calligraphy_options.nib_angle,
+            ));
+        imp.calligraphystyle_thin_width_ratio_row
+            .set_value(brush_config.calligraphy_options.thin_width_ratio);
+        imp.calligraphystyle_follow_tilt_row
+            .set_active(brush_config.calligraphy_options.follow_tilt);
+        imp.pencilstyle_opacity_row
+            .set_value(brush_config.pencil_options.opacity);
+        imp.pencilstyle_grain_row
+            .set_value(brush_config.pencil_options.grain);
 
         self.set_brush_style(brush_config.style);
         self.set_buildertype(brush_config.builder_type);
@@ -333,6 +520,142 @@ impl RnBrushPage {
                 imp.stroke_width_picker
                     .set_stroke_width(brush_config.textured_options.stroke_width);
             }
+            BrushStyle::Calligraphy => {
+                imp.stroke_width_picker
+                    .set_stroke_width(brush_config.calligraphy_options.stroke_width);
+            }
+            BrushStyle::Pencil => {
+                imp.stroke_width_picker
+                    .set_stroke_width(brush_config.pencil_options.stroke_width);
+            }
         }
     }
 }
+
+/// The nib angle in degrees, in the range [-90, 90] of the spin row.
+///
+/// The nib is symmetric, so angles that differ by 180 degrees are equivalent.
+fn nib_angle_degrees_for_display(nib_angle: f64) -> f64 {
+    (nib_angle.to_degrees() + 90.0).rem_euclid(180.0) - 90.0
+}
+
+/// Shows the rows for editing the custom pressure curve only when it is selected.
+fn refresh_custom_pressure_curve_rows(
+    pressure_curve: &PressureCurve,
+    points_row: &adw::EntryRow,
+    min_width_row: &adw::SpinRow,
+    max_width_row: &adw::SpinRow,
+) {
+    let custom = match pressure_curve {
+        PressureCurve::Custom(custom) => Some(custom),
+        _ => None,
+    };
+
+    points_row.set_visible(custom.is_some());
+    min_width_row.set_visible(custom.is_some());
+    max_width_row.set_visible(custom.is_some());
+
+    if let Some(custom) = custom {
+        points_row.set_text(&format_pressure_curve_points(custom.points()));
+        min_width_row.set_value(custom.min_width);
+        max_width_row.set_value(custom.max_width);
+    }
+}
+
+/// Connects the rows for choosing and editing the pressure curve that is returned by `pressure_curve`.
+///
+/// The last edited custom pressure curve is kept in the brush config,
+/// so that it is restored when the custom pressure curve is selected again.
+fn connect_pressure_curve_rows(
+    appwindow: &RnAppWindow,
+    curves_row: &adw::ComboRow,
+    points_row: &adw::EntryRow,
+    min_width_row: &adw::SpinRow,
+    max_width_row: &adw::SpinRow,
+    pressure_curve: fn(&mut BrushConfig) -> &mut PressureCurve,
+) {
+    curves_row.connect_selected_notify(
+        clone!(@weak appwindow, @weak points_row, @weak min_width_row, @weak max_width_row => move |row| {
+            let canvas = appwindow.active_tab_wrapper().canvas();
+            let current = {
+                let mut engine = canvas.engine_mut();
+                let brush_config = &mut engine.pens_config.brush_config;
+                let selected = PressureCurve::try_from(row.selected()).unwrap();
+                let last_custom = brush_config.custom_pressure_curve.clone();
+                let curve = pressure_curve(brush_config);
+                let current_custom = match curve {
+                    PressureCurve::Custom(custom) => Some(custom.clone()),
+                    _ => None,
+                };
+                match (current_custom, selected) {
+                    // keep the control points when the custom curve is already set
+                    (Some(_), PressureCurve::Custom(_)) => {}
+                    (Some(custom), selected) => {
+                        *curve = selected;
+                        brush_config.custom_pressure_curve = custom;
+                    }
+                    (None, PressureCurve::Custom(_)) => *curve = PressureCurve::Custom(last_custom),
+                    (None, selected) => *curve = selected,
+                }
+                pressure_curve(brush_config).clone()
+            };
+            refresh_custom_pressure_curve_rows(&current, &points_row, &min_width_row, &max_width_row);
+        }),
+    );
+
+    points_row.connect_changed(|row| {
+        row.remove_css_class("error");
+    });
+    points_row.connect_apply(clone!(@weak appwindow => move |row| {
+        let Some(points) = parse_pressure_curve_points(&row.text()) else {
+            row.add_css_class("error");
+            return;
+        };
+        modify_custom(&appwindow, pressure_curve, |custom| custom.set_points(points));
+    }));
+    min_width_row.connect_changed(clone!(@weak appwindow => move |row| {
+        modify_custom(&appwindow, pressure_curve, |custom| custom.min_width = row.value());
+    }));
+    max_width_row.connect_changed(clone!(@weak appwindow => move |row| {
+        modify_custom(&appwindow, pressure_curve, |custom| custom.max_width = row.value());
+    }));
+}
+
+/// Modifies the custom pressure curve when it is selected, and keeps it as the last edited one.
+fn modify_custom(
+    appwindow: &RnAppWindow,
+    pressure_curve: fn(&mut BrushConfig) -> &mut PressureCurve,
+    modify: impl FnOnce(&mut CustomPressureCurve),
+) {
+    let canvas = appwindow.active_tab_wrapper().canvas();
+    let mut engine = canvas.engine_mut();
+    let brush_config = &mut engine.pens_config.brush_config;
+    if let PressureCurve::Custom(custom) = pressure_curve(brush_config) {
+        modify(custom);
+        let custom = custom.clone();
+        brush_config.custom_pressure_curve = custom;
+    }
+}
+
+/// Formats the points as comma separated pairs of pressure and factor, e.g. "0:0.1, 1:1".
+fn format_pressure_curve_points(points: &[PressureCurvePoint]) -> String {
+    points
+        .iter()
+        .map(|p| format!("{}:{}", p.pressure, p.factor))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+fn parse_pressure_curve_points(text: &str) -> Option<Vec<PressureCurvePoint>> {
+    text.split(',')
+        .filter(|pair| !pair.trim().is_empty())
+        .map(|pair| {
+            let (pressure, factor) = pair.split_once(':')?;
+            Some(PressureCurvePoint::new(
+                pressure.trim().parse().ok()?,
+                factor.trim().parse().ok()?,
+            ))
+        })
+        .collect::<Option<Vec<PressureCurvePoint>>>()
+        .filter(|points| !points.is_empty())
+}
@@ -21,6 +21,7 @@ use gtk4::{
     glib, glib::clone, prelude::*, subclass::prelude::*, CompositeTemplate, Stack, StackPage,
     Widget,
 };
+use rnote_compose::style::smooth::SmoothOptions;
 
 mod imp {
     use super::*;
@@ -167,3 +168,26 @@ impl RnPensSideBar {
             }));
     }
 }
+
+/// The dash array for the index of a line pattern row.
+pub(crate) fn dash_array_for_line_pattern(line_pattern: u32) -> Vec<f64> {
+    match line_pattern {
+        1 => SmoothOptions::DASH_ARRAY_DASHED.to_vec(),
+        2 => SmoothOptions::DASH_ARRAY_DOTTED.to_vec(),
+        3 => SmoothOptions::DASH_ARRAY_DASH_DOTTED.to_vec(),
+        _ => vec![],
+    }
+}
+
+/// The index of the line pattern row for the dash array. Unknown dash arrays are shown as dashed.
+pub(crate) fn line_pattern_for_dash_array(dash_array: &[f64]) -> u32 {
+    if dash_array.is_empty() {
+        0
+    } else if dash_array == SmoothOptions::DASH_ARRAY_DOTTED {
+        2
+    } else if dash_array == SmoothOptions::DASH_ARRAY_DASH_DOTTED {
+        3
+    } else {
+        1
+    }
+}
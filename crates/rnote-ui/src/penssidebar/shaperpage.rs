@@ -12,7 +12,7 @@ use num_traits::cast::ToPrimitive;
 use rnote_compose::builders::ShapeBuilderType;
 use rnote_compose::constraints::ConstraintRatio;
 use rnote_compose::style::rough::roughoptions::FillStyle;
-use rnote_compose::style::smooth::SmoothOptions;
+use rnote_compose::style::smooth::{Arrowhead, SmoothOptions};
 use rnote_engine::pens::pensconfig::shaperconfig::ShaperStyle;
 use rnote_engine::pens::pensconfig::ShaperConfig;
 
@@ -33,6 +33,12 @@ mod imp {
         #[template_child]
         pub(crate) shapeconfig_menubutton: TemplateChild<MenuButton>,
         #[template_child]
+        pub(crate) smoothstyle_line_pattern_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub(crate) smoothstyle_start_arrowhead_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub(crate) smoothstyle_end_arrowhead_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
         pub(crate) roughstyle_fillstyle_row: TemplateChild<adw::ComboRow>,
         #[template_child]
         pub(crate) roughstyle_hachure_angle_row: TemplateChild<adw::SpinRow>,
@@ -257,6 +263,29 @@ impl RnShaperPage {
             }),
         );
 
+        // Smooth style
+        // Line pattern
+        imp.smoothstyle_line_pattern_row.get().connect_selected_notify(clone!(@weak appwindow => move |row| {
+            let canvas = appwindow.active_tab_wrapper().canvas();
+            let mut engine = canvas.engine_mut();
+            let smooth_options = &mut engine.pens_config.shaper_config.smooth_options;
+            // keep dash arrays which are not one of the patterns until another pattern is picked
+            if super::line_pattern_for_dash_array(&smooth_options.dash_array) != row.selected() {
+                smooth_options.dash_array = super::dash_array_for_line_pattern(row.selected());
+            }
+        }));
+
+        // Arrowheads
+        imp.smoothstyle_start_arrowhead_row.get().connect_selected_notify(clone!(@weak appwindow => move |row| {
+            let arrowhead = Arrowhead::try_from(row.selected()).unwrap();
+            appwindow.active_tab_wrapper().canvas().engine_mut().pens_config.shaper_config.smooth_options.start_arrowhead = arrowhead;
+        }));
+
+        imp.smoothstyle_end_arrowhead_row.get().connect_selected_notify(clone!(@weak appwindow => move |row| {
+            let arrowhead = Arrowhead::try_from(row.selected()).unwrap();
+            appwindow.active_tab_wrapper().canvas().engine_mut().pens_config.shaper_config.smooth_options.end_arrowhead = arrowhead;
+        }));
+
         // Rough style
         // Fill style
         imp.roughstyle_fillstyle_row.get().connect_selected_notify(clone!(@weak self as shaperpage, @weak appwindow => move |_roughstyle_fillstyle_row| {
@@ -352,6 +381,21 @@ impl RnShaperPage {
         // builder type
         self.set_shapebuildertype(shaper_config.builder_type);
 
+        // Smooth style
+        imp.smoothstyle_line_pattern_row
+            .set_selected(super::line_pattern_for_dash_array(
+                &shaper_config.smooth_options.dash_array,
+            ));
+        imp.smoothstyle_start_arrowhead_row.set_selected(
+            shaper_config
+                .smooth_options
+                .start_arrowhead
+                .to_u32()
+                .unwrap(),
+        );
+        imp.smoothstyle_end_arrowhead_row
+            .set_selected(shaper_config.smooth_options.end_arrowhead.to_u32().unwrap());
+
         // Rough style
         self.set_roughstyle_fillstyle(shaper_config.rough_options.fill_style);
         imp.roughstyle_hachure_angle_row
@@ -7,7 +7,7 @@ pub(crate) use penshortcutrow::RnPenShortcutRow;
 use rnote_compose::ext::Vector2Ext;
 
 // Imports
-use crate::{RnAppWindow, RnCanvasWrapper, RnIconPicker, RnUnitEntry};
+use crate::{dialogs, RnAppWindow, RnCanvasWrapper, RnIconPicker, RnUnitEntry};
 use adw::prelude::*;
 use gettextrs::{gettext, pgettext};
 use gtk4::{
@@ -18,6 +18,7 @@ use num_traits::ToPrimitive;
 use rnote_compose::penevent::ShortcutKey;
 use rnote_engine::document::background::PatternStyle;
 use rnote_engine::document::format::{self, Format, PredefinedFormat};
+use rnote_engine::document::BackgroundImageMode;
 use rnote_engine::document::Layout;
 use rnote_engine::ext::GdkRGBAExt;
 use std::cell::RefCell;
@@ -86,10 +87,18 @@ mod imp {
         #[template_child]
         pub(crate) doc_background_color_button: TemplateChild<ColorDialogButton>,
         #[template_child]
+        pub(crate) doc_background_image_choose_button: TemplateChild<Button>,
+        #[template_child]
+        pub(crate) doc_background_image_remove_button: TemplateChild<Button>,
+        #[template_child]
+        pub(crate) doc_background_image_mode_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
         pub(crate) doc_background_patterns_row: TemplateChild<adw::ComboRow>,
         #[template_child]
         pub(crate) doc_background_pattern_color_button: TemplateChild<ColorDialogButton>,
         #[template_child]
+        pub(crate) doc_background_pattern_major_color_button: TemplateChild<ColorDialogButton>,
+        #[template_child]
         pub(crate) doc_background_pattern_width_unitentry: TemplateChild<RnUnitEntry>,
         #[template_child]
         pub(crate) doc_background_pattern_height_unitentry: TemplateChild<RnUnitEntry>,
@@ -299,6 +308,20 @@ impl RnSettingsPanel {
             .set_selected(position);
     }
 
+    pub(crate) fn background_image_mode(&self) -> BackgroundImageMode {
+        BackgroundImageMode::try_from(self.imp().doc_background_image_mode_row.get().selected())
+            .unwrap()
+    }
+
+    pub(crate) fn set_background_image_mode(&self, mode: BackgroundImageMode) {
+        let position = mode.to_u32().unwrap();
+
+        self.imp()
+            .doc_background_image_mode_row
+            .get()
+            .set_selected(position);
+    }
+
     #[allow(unused)]
     pub(crate) fn format_orientation(&self) -> format::Orientation {
         if self.imp().format_orientation_portrait_toggle.is_active() {
@@ -392,15 +415,24 @@ impl RnSettingsPanel {
     fn refresh_doc_ui(&self, active_tab: &RnCanvasWrapper) {
         let imp = self.imp();
         let canvas = active_tab.canvas();
-        let background = canvas.engine_ref().document.background;
+        let background = canvas.engine_ref().document.background.clone();
         let format = canvas.engine_ref().document.format;
         let document_layout = canvas.engine_ref().document.layout;
 
         imp.doc_background_color_button
             .set_rgba(&gdk::RGBA::from_compose_color(background.color));
         self.set_background_pattern(background.pattern);
+        if let Some(image) = &background.image {
+            self.set_background_image_mode(image.mode);
+        }
+        imp.doc_background_image_remove_button
+            .set_sensitive(background.image.is_some());
         imp.doc_background_pattern_color_button
             .set_rgba(&gdk::RGBA::from_compose_color(background.pattern_color));
+        imp.doc_background_pattern_major_color_button
+            .set_rgba(&gdk::RGBA::from_compose_color(
+                background.pattern_major_color,
+            ));
         imp.doc_background_pattern_width_unitentry
             .set_dpi(format.dpi());
         imp.doc_background_pattern_width_unitentry
@@ -650,6 +682,48 @@ impl RnSettingsPanel {
                 }),
             );
 
+        imp.doc_background_image_choose_button
+            .get()
+            .connect_clicked(clone!(@weak appwindow => move |_| {
+                glib::spawn_future_local(clone!(@weak appwindow => async move {
+                    dialogs::import::filedialog_background_image(&appwindow).await;
+                }));
+            }));
+
+        imp.doc_background_image_remove_button
+            .get()
+            .connect_clicked(clone!(@weak appwindow => move |_| {
+                let canvas = appwindow.active_tab_wrapper().canvas();
+
+                if canvas.engine_ref().document.background.image.is_some() {
+                    canvas.engine_mut().document.background.image = None;
+                    let mut widget_flags = canvas.engine_mut().background_rendering_regenerate();
+                    widget_flags.refresh_ui = true;
+                    widget_flags.store_modified = true;
+                    appwindow.handle_widget_flags(widget_flags, &canvas);
+                }
+            }));
+
+        imp.doc_background_image_mode_row.get().connect_selected_item_notify(
+            clone!(@weak self as settings_panel, @weak appwindow => move |_| {
+                let mode = settings_panel.background_image_mode();
+                let canvas = appwindow.active_tab_wrapper().canvas();
+
+                let changed = match canvas.engine_mut().document.background.image.as_mut() {
+                    Some(image) if image.mode != mode => {
+                        image.mode = mode;
+                        true
+                    }
+                    _ => false,
+                };
+                if changed {
+                    let mut widget_flags = canvas.engine_mut().background_rendering_regenerate();
+                    widget_flags.store_modified = true;
+                    appwindow.handle_widget_flags(widget_flags, &canvas);
+                }
+            }),
+        );
+
         imp.doc_background_patterns_row.get().connect_selected_item_notify(clone!(@weak self as settings_panel, @weak appwindow => move |_| {
             let pattern = settings_panel.background_pattern();
             let canvas = appwindow.active_tab_wrapper().canvas();
@@ -679,7 +753,27 @@ impl RnSettingsPanel {
                     settings_panel.imp().doc_background_pattern_width_unitentry.set_sensitive(false);
                     settings_panel.imp().doc_background_pattern_height_unitentry.set_sensitive(true);
                 },
+                PatternStyle::MusicStaves => {
+                    settings_panel.imp().doc_background_pattern_width_unitentry.set_sensitive(false);
+                    settings_panel.imp().doc_background_pattern_height_unitentry.set_sensitive(true);
+                },
+                PatternStyle::CornellNotes => {
+                    settings_panel.imp().doc_background_pattern_width_unitentry.set_sensitive(false);
+                    settings_panel.imp().doc_background_pattern_height_unitentry.set_sensitive(true);
+                },
+                PatternStyle::HexagonalGrid => {
+                    settings_panel.imp().doc_background_pattern_width_unitentry.set_sensitive(false);
+                    settings_panel.imp().doc_background_pattern_height_unitentry.set_sensitive(true);
+                },
+                PatternStyle::EngineeringPaper => {
+                    settings_panel.imp().doc_background_pattern_width_unitentry.set_sensitive(true);
+                    settings_panel.imp().doc_background_pattern_height_unitentry.set_sensitive(true);
+                },
             }
+            settings_panel
+                .imp()
+                .doc_background_pattern_major_color_button
+                .set_sensitive(pattern == PatternStyle::EngineeringPaper);
 
             if canvas.engine_ref().document.background.pattern != pattern {
                 canvas.engine_mut().document.background.pattern = pattern;
@@ -703,6 +797,20 @@ impl RnSettingsPanel {
             }),
         );
 
+        imp.doc_background_pattern_major_color_button.connect_rgba_notify(
+            clone!(@weak appwindow => move |button| {
+                let canvas = appwindow.active_tab_wrapper().canvas();
+                let pattern_major_color = button.rgba().into_compose_color();
+
+                if !canvas.engine_ref().document.background.pattern_major_color.approx_eq_f32(pattern_major_color) {
+                    canvas.engine_mut().document.background.pattern_major_color = pattern_major_color;
+                    let mut widget_flags = canvas.engine_mut().background_rendering_regenerate();
+                    widget_flags.store_modified = true;
+                    appwindow.handle_widget_flags(widget_flags, &canvas);
+                }
+            }),
+        );
+
         imp.doc_background_pattern_width_unitentry
             .get()
             .connect_notify_local(
@@ -747,6 +855,7 @@ impl RnSettingsPanel {
                         let mut engine = canvas.engine_mut();
                         engine.document.background.color = engine.document.background.color.to_inverted_brightness_color();
                         engine.document.background.pattern_color = engine.document.background.pattern_color.to_inverted_brightness_color();
+                        engine.document.background.pattern_major_color = engine.document.background.pattern_major_color.to_inverted_brightness_color();
                         engine.document.format.border_color = engine.document.format.border_color.to_inverted_brightness_color();
                         engine.background_rendering_regenerate()
                     };